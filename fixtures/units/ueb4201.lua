@@ -0,0 +1,33 @@
+-- FAF-style tactical missile defense fixture (UEF T2 TMD); one defensive weapon, no guns
+{
+    BlueprintId = "ueb4201",
+    DisplayName = "UEF T2 Tactical Missile Defense",
+    UnitId = "ueb4201",
+    SizeX = 1,
+    SizeZ = 1,
+    Categories = { "STRUCTURE", "TECH2", "ANTIMISSILE" },
+    Defense = {
+        ArmorType = "Structure",
+        Health = 1400,
+        MaxHealth = 1400,
+    },
+    Economy = {
+        BuildCostEnergy = 3600,
+        BuildCostMass = 240,
+        BuildTime = 300,
+    },
+    Physics = {
+        MotionType = "RULEUMT_None",
+    },
+    Weapon = {
+        {
+            Label = "AntiMissile",
+            WeaponCategory = "Defense",
+            Damage = 2,
+            MaxRadius = 31,
+            RateOfFire = 0.3,
+            TargetRestrictOnlyAllow = "TACTICAL MISSILE",
+            Turreted = true,
+        },
+    },
+}
@@ -0,0 +1,83 @@
+-- FAF-style commander fixture (UEF ACU) with an Enhancements table
+{
+    BlueprintId = "uel0001",
+    DisplayName = "UEF Armored Command Unit",
+    UnitId = "uel0001",
+    SizeX = 1.2,
+    SizeZ = 1.2,
+    Categories = { "COMMAND", "LAND", "DIRECTFIRE" },
+    Defense = {
+        ArmorType = "Commander",
+        Health = 12000,
+        MaxHealth = 12000,
+        RegenRate = 10,
+    },
+    Enhancements = {
+        Slots = {
+            Back = { x = 0, y = -0.1, z = -0.45 },
+            LCH = { x = 0.1, y = 0, z = 0 },
+            RCH = { x = -0.1, y = 0, z = 0 },
+        },
+        HeavyAntiMatterCannon = {
+            BuildCostEnergy = 37500,
+            BuildCostMass = 1500,
+            BuildTime = 750,
+            NewMaxRadius = 30,
+            ZephyrDamageMod = 200,
+            Slot = "RCH",
+        },
+        HeavyAntiMatterCannonRemove = {
+            BuildCostEnergy = 1,
+            BuildCostMass = 1,
+            BuildTime = 0.1,
+            Prerequisite = "HeavyAntiMatterCannon",
+            RemoveEnhancements = { "HeavyAntiMatterCannon", "HeavyAntiMatterCannonRemove" },
+            Slot = "RCH",
+        },
+        DamageStabilization = {
+            BuildCostEnergy = 15000,
+            BuildCostMass = 750,
+            BuildTime = 500,
+            NewHealth = 3000,
+            NewRegenRate = 20,
+            Slot = "LCH",
+        },
+        DamageStabilizationAdvanced = {
+            BuildCostEnergy = 40000,
+            BuildCostMass = 2000,
+            BuildTime = 1000,
+            NewHealth = 6000,
+            NewRegenRate = 40,
+            Prerequisite = "DamageStabilization",
+            Slot = "LCH",
+        },
+        TacticalMissile = {
+            BuildCostEnergy = 24000,
+            BuildCostMass = 800,
+            BuildTime = 600,
+            Slot = "Back",
+        },
+    },
+    Weapon = {
+        {
+            Label = "RightZephyr",
+            Damage = 100,
+            DamageRadius = 0,
+            MaxRadius = 22,
+            MuzzleVelocity = 35,
+            RateOfFire = 1,
+            TurretCapable = true,
+            TargetCategories = { "GROUND", "STRUCTURE" }
+        },
+        {
+            Label = "TacMissile",
+            EnabledByEnhancement = "TacticalMissile",
+            Damage = 6000,
+            DamageRadius = 2,
+            MaxRadius = 256,
+            MuzzleVelocity = 5,
+            RateOfFire = 0.083,
+            TargetCategories = { "STRUCTURE" }
+        }
+    }
+}
@@ -0,0 +1,24 @@
+-- FAF-style shield unit fixture (UEF T2 Mobile Shield Generator); no weapons
+{
+    BlueprintId = "uel0307",
+    DisplayName = "UEF T2 Mobile Shield Generator",
+    UnitId = "uel0307",
+    SizeX = 0.8,
+    SizeZ = 0.9,
+    Categories = { "LAND", "TECH2", "SHIELD" },
+    Defense = {
+        ArmorType = "Normal",
+        Health = 250,
+        MaxHealth = 250,
+        Shield = {
+            ShieldMaxHealth = 2000,
+            ShieldRechargeTime = 30,
+            ShieldRegenRate = 15,
+            ShieldSize = 16,
+        },
+    },
+    Physics = {
+        MaxSpeed = 2.6,
+        MotionType = "RULEUMT_Land",
+    },
+}
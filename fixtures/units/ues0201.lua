@@ -0,0 +1,49 @@
+-- FAF-style unit with two charged guns on one turret (shared-turret cadence fixture)
+{
+    BlueprintId = "ues0201",
+    DisplayName = "UEF T2 Destroyer",
+    UnitId = "ues0201",
+    Weapon = {
+        {
+            BlueprintId = "/projectiles/ues0201/front_gun",
+            Label = "FrontGun",
+            Damage = 100,
+            DamageRadius = 0,
+            RateOfFire = 0.5,
+            MaxRadius = 60,
+            MuzzleVelocity = 40,
+            RackSalvoChargeTime = 1.5,
+            Turreted = true,
+            TurretBoneYaw = "Turret_Front",
+            TurretDualManipulators = false,
+            TargetCategories = { "NAVAL", "GROUND" }
+        },
+        {
+            BlueprintId = "/projectiles/ues0201/front_gun_secondary",
+            Label = "FrontGunSecondary",
+            Damage = 60,
+            DamageRadius = 0,
+            RateOfFire = 0.5,
+            MaxRadius = 60,
+            MuzzleVelocity = 40,
+            RackSalvoChargeTime = 1.5,
+            Turreted = true,
+            TurretBoneYaw = "Turret_Front",
+            TurretDualManipulators = false,
+            TargetCategories = { "NAVAL", "GROUND" }
+        },
+        {
+            BlueprintId = "/projectiles/ues0201/rear_gun",
+            Label = "RearGun",
+            Damage = 40,
+            DamageRadius = 0,
+            RateOfFire = 1,
+            MaxRadius = 40,
+            MuzzleVelocity = 40,
+            Turreted = true,
+            TurretBoneYaw = "Turret_Front",
+            PrefersPrimaryWeaponTarget = true,
+            TargetCategories = { "NAVAL", "GROUND" }
+        }
+    }
+}
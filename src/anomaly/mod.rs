@@ -1,5 +1,6 @@
 //! Anomaly detection: severity, explanation, technical note.
 
+use crate::config::{FriendlyFireConfig, InterceptThreat};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -22,16 +23,46 @@ pub struct Anomaly {
 }
 
 impl Anomaly {
-    pub fn cadence_interference(unit_id: &str, weapon_ids: &[String], technical: String) -> Self {
+    /// `weapon_id` loses `lost_dps` to sharing; `blocked_by` is the weapon whose turret or unpack
+    /// lock it waited on longest. Without one, `energy_user` tells whether the weapon draws from
+    /// the unit's shared energy pool; otherwise the loss is left unattributed.
+    pub fn cadence_interference(
+        unit_id: &str,
+        weapon_id: &str,
+        blocked_by: Option<&str>,
+        energy_user: bool,
+        lost_dps: f64,
+        technical: String,
+    ) -> Self {
+        let (summary, weapon_ids) = match blocked_by {
+            Some(blocker) => (
+                format!(
+                    "Unit {}: {} loses {:.1} DPS waiting for {} on a shared turret or unpack lock (cadence interference).",
+                    unit_id, weapon_id, lost_dps, blocker
+                ),
+                vec![weapon_id.to_string(), blocker.to_string()],
+            ),
+            None if energy_user => (
+                format!(
+                    "Unit {}: {} loses {:.1} DPS waiting for a shared energy pool (cadence interference).",
+                    unit_id, weapon_id, lost_dps
+                ),
+                vec![weapon_id.to_string()],
+            ),
+            None => (
+                format!(
+                    "Unit {}: {} loses {:.1} DPS firing alongside the other weapons; no shared resource accounts for it (cadence interference).",
+                    unit_id, weapon_id, lost_dps
+                ),
+                vec![weapon_id.to_string()],
+            ),
+        };
         Self {
             code: "CADENCE_INTERFERENCE".to_string(),
             severity: AnomalySeverity::Warn,
-            summary: format!(
-                "Unit {}: multi-weapon firing may reduce effective ROF (cadence interference suspected).",
-                unit_id
-            ),
+            summary,
             technical,
-            weapon_ids: weapon_ids.to_vec(),
+            weapon_ids,
             unit_id: Some(unit_id.to_string()),
         }
     }
@@ -67,6 +98,191 @@ impl Anomaly {
         }
     }
 
+    /// No weapon can fire between `start` and `end`. Inside the weapon envelope (a gap between
+    /// weapon bands) this is a warning; below every MinRadius it is the expected artillery blind spot.
+    pub fn range_dead_zone(unit_id: &str, start: f64, end: f64, interior: bool) -> Self {
+        Self {
+            code: "RANGE_DEAD_ZONE".to_string(),
+            severity: if interior {
+                AnomalySeverity::Warn
+            } else {
+                AnomalySeverity::Info
+            },
+            summary: format!(
+                "Unit {}: no weapon can fire between {:.0} and {:.0} range.",
+                unit_id, start, end
+            ),
+            technical: if interior {
+                format!(
+                    "Gap between weapon range bands: [{}, {}] is outside every MinRadius..MaxRadius.",
+                    start, end
+                )
+            } else {
+                format!(
+                    "Every weapon has MinRadius >= {}; targets closer than that cannot be engaged.",
+                    end
+                )
+            },
+            weapon_ids: vec![],
+            unit_id: Some(unit_id.to_string()),
+        }
+    }
+
+    /// A FragmentId (or the weapon's ProjectileId) points at a projectile blueprint that was not loaded.
+    pub fn projectile_missing(unit_id: &str, weapon_id: &str, projectile_id: &str) -> Self {
+        Self {
+            code: "PROJECTILE_MISSING".to_string(),
+            severity: AnomalySeverity::Warn,
+            summary: format!(
+                "Unit {} weapon {}: projectile {} not found; its damage is not counted.",
+                unit_id, weapon_id, projectile_id
+            ),
+            technical: "Referenced by ProjectileId or Physics.FragmentId but absent from the scanned projectiles.".to_string(),
+            weapon_ids: vec![weapon_id.to_string()],
+            unit_id: Some(unit_id.to_string()),
+        }
+    }
+
+    /// A projectile's fragment chain leads back to itself.
+    pub fn projectile_cycle(unit_id: &str, weapon_id: &str, projectile_id: &str) -> Self {
+        Self {
+            code: "PROJECTILE_CYCLE".to_string(),
+            severity: AnomalySeverity::Crit,
+            summary: format!(
+                "Unit {} weapon {}: fragment chain loops back to {}.",
+                unit_id, weapon_id, projectile_id
+            ),
+            technical: "Physics.FragmentId references a projectile already on the chain; the loop was cut and its damage not counted.".to_string(),
+            weapon_ids: vec![weapon_id.to_string()],
+            unit_id: Some(unit_id.to_string()),
+        }
+    }
+
+    /// The projectile expires (Lifetime) before it can travel the weapon's MaxRadius.
+    pub fn projectile_lifetime_short(
+        unit_id: &str,
+        weapon_id: &str,
+        time_of_flight_sec: f64,
+        lifetime_sec: f64,
+        range: f64,
+    ) -> Self {
+        Self {
+            code: "PROJECTILE_LIFETIME_SHORT".to_string(),
+            severity: AnomalySeverity::Warn,
+            summary: format!(
+                "Unit {} weapon {}: projectile expires after {:.1}s but needs {:.1}s to reach max range {:.0}.",
+                unit_id, weapon_id, lifetime_sec, time_of_flight_sec, range
+            ),
+            technical: format!(
+                "Time of flight to MaxRadius from InitialSpeed/MuzzleVelocity, Acceleration and MaxSpeed is {}s; Physics.Lifetime is {}s.",
+                time_of_flight_sec, lifetime_sec
+            ),
+            weapon_ids: vec![weapon_id.to_string()],
+            unit_id: Some(unit_id.to_string()),
+        }
+    }
+
+    /// The turret's yaw arc (or speed) leaves targets the weapon should engage out of reach.
+    pub fn turret_yaw_limited(unit_id: &str, weapon_id: &str, issue: String) -> Self {
+        Self {
+            code: "TURRET_YAW_LIMITED".to_string(),
+            severity: AnomalySeverity::Warn,
+            summary: format!(
+                "Unit {} weapon {}: turret cannot cover all its targets.",
+                unit_id, weapon_id
+            ),
+            technical: format!(
+                "From TurretYaw, TurretYawRange and TurretYawSpeed: {}.",
+                issue
+            ),
+            weapon_ids: vec![weapon_id.to_string()],
+            unit_id: Some(unit_id.to_string()),
+        }
+    }
+
+    /// The engine's 0.1 s ticks make the weapon fire noticeably slower or faster than declared.
+    pub fn rof_tick_rounding(
+        unit_id: &str,
+        weapon_id: &str,
+        declared_cycle_sec: f64,
+        tick_cycle_sec: f64,
+        threshold: f64,
+    ) -> Self {
+        let change = declared_cycle_sec / tick_cycle_sec - 1.0;
+        Self {
+            code: "ROF_TICK_ROUNDING".to_string(),
+            severity: AnomalySeverity::Warn,
+            summary: format!(
+                "Unit {} weapon {}: fires {:.0}% {} than declared because reloads run on 0.1s ticks.",
+                unit_id,
+                weapon_id,
+                change.abs() * 100.0,
+                if change < 0.0 { "slower" } else { "faster" }
+            ),
+            technical: format!(
+                "Declared cycle {:.3}s rounds to {:.1}s ({} ticks); threshold {:.0}%. Write RateOfFire as 10/ticks to avoid this.",
+                declared_cycle_sec,
+                tick_cycle_sec,
+                (tick_cycle_sec / crate::config::SIM_TICK_SECS).round(),
+                threshold * 100.0
+            ),
+            weapon_ids: vec![weapon_id.to_string()],
+            unit_id: Some(unit_id.to_string()),
+        }
+    }
+
+    /// Splash or collisions put more damage on own units than the configured share of enemy damage.
+    pub fn friendly_fire_risk(
+        unit_id: &str,
+        weapon_ids: &[String],
+        friendly_per_min: f64,
+        enemy_per_min: f64,
+        cfg: &FriendlyFireConfig,
+    ) -> Self {
+        Self {
+            code: "FRIENDLY_FIRE_RISK".to_string(),
+            severity: AnomalySeverity::Warn,
+            summary: format!(
+                "Unit {}: about {:.0} friendly damage/min against {:.0} enemy damage/min in a formation.",
+                unit_id, friendly_per_min, enemy_per_min
+            ),
+            technical: format!(
+                "DamageFriendly splash landing within {} ogrids of the shooter and CollideFriendly hits at {} friendly units per square ogrid exceed {:.0}% of enemy damage. Weapons: {}.",
+                cfg.line_depth,
+                cfg.density,
+                cfg.max_fraction * 100.0,
+                weapon_ids.join(", ")
+            ),
+            weapon_ids: weapon_ids.to_vec(),
+            unit_id: Some(unit_id.to_string()),
+        }
+    }
+
+    /// A defensive weapon fires slower than the reference incoming salvo arrives.
+    pub fn interceptor_saturated(
+        unit_id: &str,
+        weapon_id: &str,
+        target: &str,
+        per_min: f64,
+        threat: &InterceptThreat,
+    ) -> Self {
+        Self {
+            code: "INTERCEPTOR_SATURATED".to_string(),
+            severity: AnomalySeverity::Warn,
+            summary: format!(
+                "Unit {} weapon {}: intercepts {:.1} {}s/min, slower than a salvo of {} in {:.0}s.",
+                unit_id, weapon_id, per_min, target, threat.salvo_size, threat.window_secs
+            ),
+            technical: format!(
+                "Interceptions per minute from ROF, reload and salvo is {:.2}; the salvo needs {:.2}. Set --threat-salvo/--threat-window to change the reference salvo.",
+                per_min,
+                threat.required_per_min()
+            ),
+            weapon_ids: vec![weapon_id.to_string()],
+            unit_id: Some(unit_id.to_string()),
+        }
+    }
+
     pub fn salvo_cooldown_suspicion(unit_id: &str, weapon_id: &str, note: String) -> Self {
         Self {
             code: "SALVO_COOLDOWN_PATTERN".to_string(),
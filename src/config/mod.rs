@@ -1,7 +1,8 @@
 //! Configuration loading and validation.
 
-use serde::Deserialize;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 /// Maximum number of blueprint files to process in one scan (bound input size).
 pub const MAX_BLUEPRINT_FILES: usize = 50_000;
@@ -12,39 +13,464 @@ pub const MAX_BLUEPRINT_FILE_BYTES: usize = 2 * 1024 * 1024;
 /// Default simulation window in seconds for cadence analysis.
 pub const DEFAULT_SIMULATION_SECONDS: f64 = 30.0;
 
+/// Longest hyperperiod (seconds) the cadence check analyses exactly; longer patterns fall back
+/// to `simulation_seconds`.
+pub const DEFAULT_MAX_HYPERPERIOD_SECS: f64 = 300.0;
+
 /// Default tolerance (seconds) for cadence gap detection.
 pub const DEFAULT_CADENCE_GAP_TOLERANCE_SECS: f64 = 0.05;
 
+/// Default time limit (seconds) for time-to-kill; pairs that take longer are reported as no kill.
+pub const DEFAULT_TTK_HORIZON_SECS: f64 = 600.0;
+
+/// Highest veterancy level in FAF.
+pub const MAX_VET_LEVEL: u8 = 5;
+
+/// Default seconds between target switches for the switching-DPS estimate.
+pub const DEFAULT_TARGET_SWITCH_INTERVAL_SECS: f64 = 10.0;
+
+/// Default windows (seconds) for the max-damage-in-window burst metric.
+pub const DEFAULT_BURST_WINDOWS_SECS: [f64; 3] = [3.0, 5.0, 10.0];
+
+/// Default damage totals for the time-to-deal-X-damage burst metric.
+pub const DEFAULT_DAMAGE_MILESTONES: [f64; 4] = [500.0, 1000.0, 2500.0, 5000.0];
+
+/// Default friendly units per square ogrid around the shooter.
+pub const DEFAULT_FORMATION_DENSITY: f64 = 0.02;
+
+/// Default depth (ogrids) of own formation around the shooter; direct-fire shots cross it on their way out.
+pub const DEFAULT_FORMATION_LINE_DEPTH: f64 = 10.0;
+
+/// Default limit on friendly damage as a fraction of enemy damage.
+pub const DEFAULT_FRIENDLY_FIRE_MAX_FRACTION: f64 = 0.5;
+
+/// Engine simulation tick in seconds: weapons act on 10 Hz ticks.
+pub const SIM_TICK_SECS: f64 = 0.1;
+
+/// Default fraction of declared rate of fire that tick rounding may change before it is flagged.
+pub const DEFAULT_ROF_ROUNDING_THRESHOLD: f64 = 0.05;
+
+/// Default missile salvo an interceptor must keep up with: this many missiles...
+pub const DEFAULT_THREAT_SALVO_SIZE: u32 = 4;
+
+/// ...arriving within this many seconds.
+pub const DEFAULT_THREAT_SALVO_WINDOW_SECS: f64 = 10.0;
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct ScanConfig {
     /// Path to FAF blueprint/weapon data directory.
     pub data_dir: PathBuf,
     /// Output directory for reports and SQLite DB.
     pub out_dir: PathBuf,
-    /// Simulation duration in seconds for multi-weapon cadence analysis.
+    /// Model and simulation settings.
+    #[serde(flatten)]
+    pub analysis: AnalysisConfig,
+}
+
+/// Model and simulation settings used when building unit summaries.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnalysisConfig {
+    /// Simulation duration in seconds for multi-weapon cadence analysis, when the weapons have
+    /// no exact hyperperiod within `max_hyperperiod_secs`.
     #[serde(default = "default_simulation_seconds")]
     pub simulation_seconds: f64,
+    /// Longest hyperperiod analysed exactly.
+    #[serde(default = "default_max_hyperperiod")]
+    pub max_hyperperiod_secs: f64,
     /// Gap tolerance in seconds; gaps larger than this may be flagged.
     #[serde(default = "default_cadence_gap_tolerance")]
     pub cadence_gap_tolerance_secs: f64,
+    /// Health and regen buffs per veterancy level.
+    #[serde(default)]
+    pub veterancy: VeterancyBuffs,
+    /// Declarative effects for enhancements whose behaviour lives in script code.
+    #[serde(default)]
+    pub enhancements: EnhancementEffects,
+    /// Salvo that defensive weapons are checked against.
+    #[serde(default)]
+    pub intercept_threat: InterceptThreat,
+    /// Seconds between target switches; each switch costs turret acquisition time.
+    #[serde(default = "default_target_switch_interval")]
+    pub target_switch_interval_secs: f64,
+    /// Window lengths for the max-damage-in-window burst metric.
+    #[serde(default = "default_burst_windows")]
+    pub burst_windows_secs: Vec<f64>,
+    /// Damage totals for the time-to-deal-X-damage burst metric.
+    #[serde(default = "default_damage_milestones")]
+    pub damage_milestones: Vec<f64>,
+    /// Formation density and threshold for the friendly-fire check.
+    #[serde(default)]
+    pub friendly_fire: FriendlyFireConfig,
+    /// Continuous time, or intervals rounded to whole engine ticks.
+    #[serde(default)]
+    pub timing: TimingMode,
+    /// Flag weapons whose rate of fire changes by more than this fraction when rounded to ticks.
+    #[serde(default = "default_rof_rounding_threshold")]
+    pub rof_rounding_threshold: f64,
+    /// Seeded Monte Carlo runs per unit; off when unset.
+    #[serde(default)]
+    pub monte_carlo: Option<MonteCarloRuns>,
 }
 
 fn default_simulation_seconds() -> f64 {
     DEFAULT_SIMULATION_SECONDS
 }
 
+fn default_max_hyperperiod() -> f64 {
+    DEFAULT_MAX_HYPERPERIOD_SECS
+}
+
 fn default_cadence_gap_tolerance() -> f64 {
     DEFAULT_CADENCE_GAP_TOLERANCE_SECS
 }
 
+fn default_target_switch_interval() -> f64 {
+    DEFAULT_TARGET_SWITCH_INTERVAL_SECS
+}
+
+fn default_burst_windows() -> Vec<f64> {
+    DEFAULT_BURST_WINDOWS_SECS.to_vec()
+}
+
+fn default_damage_milestones() -> Vec<f64> {
+    DEFAULT_DAMAGE_MILESTONES.to_vec()
+}
+
+fn default_rof_rounding_threshold() -> f64 {
+    DEFAULT_ROF_ROUNDING_THRESHOLD
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        Self {
+            simulation_seconds: DEFAULT_SIMULATION_SECONDS,
+            max_hyperperiod_secs: DEFAULT_MAX_HYPERPERIOD_SECS,
+            cadence_gap_tolerance_secs: DEFAULT_CADENCE_GAP_TOLERANCE_SECS,
+            veterancy: VeterancyBuffs::default(),
+            enhancements: EnhancementEffects::default(),
+            intercept_threat: InterceptThreat::default(),
+            target_switch_interval_secs: DEFAULT_TARGET_SWITCH_INTERVAL_SECS,
+            burst_windows_secs: default_burst_windows(),
+            damage_milestones: default_damage_milestones(),
+            friendly_fire: FriendlyFireConfig::default(),
+            timing: TimingMode::default(),
+            rof_rounding_threshold: DEFAULT_ROF_ROUNDING_THRESHOLD,
+            monte_carlo: None,
+        }
+    }
+}
+
+/// How weapon intervals are timed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimingMode {
+    /// Intervals exactly as declared (1 / RateOfFire, salvo delays).
+    #[default]
+    Continuous,
+    /// Intervals rounded to whole engine ticks (`SIM_TICK_SECS`), as the game runs them.
+    Ticks,
+}
+
+/// Monte Carlo mode: `runs` replays per unit from generators seeded with `seed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MonteCarloRuns {
+    pub runs: u32,
+    #[serde(default)]
+    pub seed: u64,
+}
+
+/// Formation assumed by the friendly-fire model and the FRIENDLY_FIRE_RISK threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FriendlyFireConfig {
+    /// Friendly units per square ogrid around the shooter.
+    pub density: f64,
+    /// Ogrids the own formation extends from the shooter; splash landing within it and direct-fire
+    /// shots crossing it can hit friendlies.
+    pub line_depth: f64,
+    /// Flag units whose friendly damage per minute exceeds this fraction of enemy damage.
+    pub max_fraction: f64,
+}
+
+impl Default for FriendlyFireConfig {
+    fn default() -> Self {
+        Self {
+            density: DEFAULT_FORMATION_DENSITY,
+            line_depth: DEFAULT_FORMATION_LINE_DEPTH,
+            max_fraction: DEFAULT_FRIENDLY_FIRE_MAX_FRACTION,
+        }
+    }
+}
+
+/// Incoming salvo for the interceptor saturation check: `salvo_size` projectiles within `window_secs`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InterceptThreat {
+    pub salvo_size: u32,
+    pub window_secs: f64,
+}
+
+impl Default for InterceptThreat {
+    fn default() -> Self {
+        Self {
+            salvo_size: DEFAULT_THREAT_SALVO_SIZE,
+            window_secs: DEFAULT_THREAT_SALVO_WINDOW_SECS,
+        }
+    }
+}
+
+impl InterceptThreat {
+    /// Interceptions per minute needed to stop every projectile of the salvo.
+    pub fn required_per_min(&self) -> f64 {
+        if self.window_secs <= 0.0 {
+            return f64::INFINITY;
+        }
+        self.salvo_size as f64 * 60.0 / self.window_secs
+    }
+}
+
 impl ScanConfig {
     pub fn new(data_dir: PathBuf, out_dir: PathBuf) -> Self {
         Self {
             data_dir,
             out_dir,
-            simulation_seconds: DEFAULT_SIMULATION_SECONDS,
-            cadence_gap_tolerance_secs: DEFAULT_CADENCE_GAP_TOLERANCE_SECS,
+            analysis: AnalysisConfig::default(),
+        }
+    }
+}
+
+/// Veterancy buff table. Defaults follow FAF's buff definitions (+10% max health per level,
+/// regen by tech class); load a TOML file to follow balance patches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VeterancyBuffs {
+    /// Fraction of base max health added per level (additive: level 3 = 1 + 3 × this).
+    pub max_health_per_level: f64,
+    /// Regen (HP/s) granted at levels 1..=5, keyed by tech class category
+    /// (COMMAND, SUBCOMMANDER, EXPERIMENTAL, TECH3, TECH2, TECH1).
+    pub regen_per_level: BTreeMap<String, Vec<f64>>,
+}
+
+impl Default for VeterancyBuffs {
+    fn default() -> Self {
+        let regen = [
+            ("TECH1", [1.0, 2.0, 3.0, 4.0, 5.0]),
+            ("TECH2", [3.0, 6.0, 9.0, 12.0, 15.0]),
+            ("TECH3", [6.0, 12.0, 18.0, 24.0, 30.0]),
+            ("EXPERIMENTAL", [25.0, 50.0, 75.0, 100.0, 125.0]),
+            ("SUBCOMMANDER", [10.0, 15.0, 20.0, 25.0, 30.0]),
+            ("COMMAND", [10.0, 20.0, 30.0, 40.0, 50.0]),
+        ];
+        Self {
+            max_health_per_level: 0.1,
+            regen_per_level: regen
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_vec()))
+                .collect(),
+        }
+    }
+}
+
+/// Tech classes in lookup order: the first one a unit carries selects its regen row.
+const VET_CLASS_ORDER: [&str; 6] = [
+    "COMMAND",
+    "SUBCOMMANDER",
+    "EXPERIMENTAL",
+    "TECH3",
+    "TECH2",
+    "TECH1",
+];
+
+impl VeterancyBuffs {
+    /// Load a buff table from TOML; keys left out keep their defaults.
+    pub fn from_toml_file(path: &Path) -> Result<Self, String> {
+        let s = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        toml::from_str(&s).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Regen bonus at `level` for a unit with these categories (0 at level 0 or for unknown classes).
+    pub fn regen_bonus(&self, categories: &[String], level: u8) -> f64 {
+        if level == 0 {
+            return 0.0;
+        }
+        VET_CLASS_ORDER
+            .iter()
+            .find(|class| categories.iter().any(|c| c.eq_ignore_ascii_case(class)))
+            .and_then(|class| self.regen_per_level.get(*class))
+            .and_then(|row| row.get(level as usize - 1))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Max health multiplier at `level`.
+    pub fn max_health_mult(&self, level: u8) -> f64 {
+        1.0 + self.max_health_per_level * level as f64
+    }
+}
+
+/// Effect of one enhancement (ACU/SCU upgrade). Built from the blueprint's New* fields, or supplied
+/// in a TOML file (`[[enhancement]]` entries) when the effect only exists in script code.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnhancementEffect {
+    /// Unit blueprint ID the entry applies to (data file only; case-insensitive).
+    pub unit: String,
+    /// Enhancement name as in the blueprint's Enhancements table.
+    pub name: String,
+    /// Label of the weapon altered by the weapon fields; defaults to the unit's first combat weapon.
+    pub weapon: Option<String>,
+    /// Damage added to the weapon (NewDamageMod / …DamageMod).
+    pub damage_add: Option<f64>,
+    /// New MaxRadius (NewMaxRadius).
+    pub max_radius: Option<f64>,
+    /// New RateOfFire (NewRateOfFire).
+    pub rate_of_fire: Option<f64>,
+    /// New DamageRadius (NewDamageRadius).
+    pub damage_radius: Option<f64>,
+    /// Max health added to the unit (NewHealth).
+    pub health_add: Option<f64>,
+    /// Regen added to the unit (NewRegenRate).
+    pub regen_add: Option<f64>,
+    /// Labels of weapons switched on by this enhancement (besides EnabledByEnhancement in the blueprint).
+    pub enable_weapons: Vec<String>,
+}
+
+impl EnhancementEffect {
+    /// Fields set in `over` replace ours; weapon enables are merged.
+    pub fn merged_with(&self, over: &EnhancementEffect) -> EnhancementEffect {
+        let mut enable_weapons = self.enable_weapons.clone();
+        for w in &over.enable_weapons {
+            if !enable_weapons.iter().any(|e| e.eq_ignore_ascii_case(w)) {
+                enable_weapons.push(w.clone());
+            }
+        }
+        EnhancementEffect {
+            unit: self.unit.clone(),
+            name: self.name.clone(),
+            weapon: over.weapon.clone().or_else(|| self.weapon.clone()),
+            damage_add: over.damage_add.or(self.damage_add),
+            max_radius: over.max_radius.or(self.max_radius),
+            rate_of_fire: over.rate_of_fire.or(self.rate_of_fire),
+            damage_radius: over.damage_radius.or(self.damage_radius),
+            health_add: over.health_add.or(self.health_add),
+            regen_add: over.regen_add.or(self.regen_add),
+            enable_weapons,
+        }
+    }
+}
+
+/// Declarative enhancement data file: a list of `[[enhancement]]` entries.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnhancementEffects {
+    pub enhancement: Vec<EnhancementEffect>,
+}
+
+impl EnhancementEffects {
+    pub fn from_toml_file(path: &Path) -> Result<Self, String> {
+        let s = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        toml::from_str(&s).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Entry for `unit_id` / `name`, if the data file has one.
+    pub fn find(&self, unit_id: &str, name: &str) -> Option<&EnhancementEffect> {
+        self.enhancement
+            .iter()
+            .find(|e| e.unit.eq_ignore_ascii_case(unit_id) && e.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Which enemy a shooter picks among those in range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetPolicy {
+    /// Every shooter on the side fires at the same enemy until it dies.
+    #[default]
+    FocusFire,
+    /// Shooters rotate through the enemies in range.
+    Spread,
+    /// Each shot goes to the enemy with the least health plus shield.
+    LowestHp,
+}
+
+impl TargetPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TargetPolicy::FocusFire => "focus_fire",
+            TargetPolicy::Spread => "spread",
+            TargetPolicy::LowestHp => "lowest_hp",
+        }
+    }
+}
+
+/// `count` units of one blueprint in a battle scenario.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioGroup {
+    /// Unit ID or name.
+    pub unit: String,
+    pub count: u32,
+}
+
+/// One army of a battle scenario.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioSide {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub policy: TargetPolicy,
+    pub units: Vec<ScenarioGroup>,
+}
+
+/// Group battle scenario: armies `a` and `b`, loaded from TOML.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BattleScenario {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Starting distance between the armies; default: the longest weapon range in the battle.
+    #[serde(default)]
+    pub start_range: Option<f64>,
+    /// Battle ends as a timeout after this many seconds.
+    #[serde(default = "default_battle_horizon")]
+    pub horizon_sec: f64,
+    /// Damage multiplier by Defense.ArmorType (case-insensitive).
+    #[serde(default)]
+    pub armor: BTreeMap<String, f64>,
+    pub a: ScenarioSide,
+    pub b: ScenarioSide,
+}
+
+fn default_battle_horizon() -> f64 {
+    DEFAULT_TTK_HORIZON_SECS
+}
+
+impl BattleScenario {
+    /// Load and validate a scenario: both armies need units, counts and multipliers must be sane.
+    pub fn from_toml_file(path: &Path) -> Result<Self, String> {
+        let s = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let scenario: Self =
+            toml::from_str(&s).map_err(|e| format!("{}: {}", path.display(), e))?;
+        scenario
+            .validate()
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(scenario)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (label, side) in [("a", &self.a), ("b", &self.b)] {
+            if side.units.iter().all(|g| g.count == 0) {
+                return Err(format!("army {} has no units", label));
+            }
+        }
+        if let Some(r) = self.start_range.filter(|r| r.is_nan() || *r < 0.0) {
+            return Err(format!("invalid start_range: {}", r));
+        }
+        if self.horizon_sec.is_nan() || self.horizon_sec <= 0.0 {
+            return Err(format!("invalid horizon_sec: {}", self.horizon_sec));
+        }
+        if let Some((t, m)) = self.armor.iter().find(|(_, m)| !m.is_finite() || **m < 0.0) {
+            return Err(format!("invalid armor multiplier for {}: {}", t, m));
         }
+        Ok(())
     }
 }
 
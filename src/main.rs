@@ -2,17 +2,27 @@
 
 use clap::{Parser, Subcommand};
 use faf_simlint::config::{
-    ScanConfig, DEFAULT_CADENCE_GAP_TOLERANCE_SECS, DEFAULT_SIMULATION_SECONDS,
+    AnalysisConfig, BattleScenario, EnhancementEffects, FriendlyFireConfig, InterceptThreat,
+    MonteCarloRuns, ScanConfig, TimingMode, VeterancyBuffs, DEFAULT_CADENCE_GAP_TOLERANCE_SECS,
+    DEFAULT_FORMATION_DENSITY, DEFAULT_FRIENDLY_FIRE_MAX_FRACTION, DEFAULT_MAX_HYPERPERIOD_SECS,
+    DEFAULT_ROF_ROUNDING_THRESHOLD, DEFAULT_SIMULATION_SECONDS,
+    DEFAULT_TARGET_SWITCH_INTERVAL_SECS, DEFAULT_THREAT_SALVO_SIZE,
+    DEFAULT_THREAT_SALVO_WINDOW_SECS, DEFAULT_TTK_HORIZON_SECS, MAX_VET_LEVEL,
 };
 use faf_simlint::config::{MAX_BLUEPRINT_FILES, MAX_BLUEPRINT_FILE_BYTES};
 use faf_simlint::gamedata;
 use faf_simlint::model::{
-    normalize_projectile_path, projectile_from_lua, unit_summary_from_file, ProjectileData,
+    battle, duel, expected_dps_vs_target, normalize_projectile_path, projectile_from_lua,
+    ttk_matrix, unit_summary_from_file, unit_timeline, BattleOptions, BattleSide, DuelOptions,
+    DuelOutcome, DuelSide, HitMode, ProjectileData, TargetProfile, UnitSummary, UnitTimeline,
+};
+use faf_simlint::report::{
+    write_battle_html, write_battle_json, write_chrome_trace, write_duel_json, write_html_report,
+    write_json_report, write_ttk_csv, write_ttk_html,
 };
-use faf_simlint::report::{write_html_report, write_json_report};
 use faf_simlint::store::Store;
 use faf_simlint::util::{check_file_bounds, init_logging, normalize_id};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -23,6 +33,18 @@ struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
+    /// TOML file overriding the veterancy buff table (max health per level, regen per tech class).
+    #[arg(long, global = true, value_name = "TOML")]
+    vet_buffs: Option<PathBuf>,
+
+    /// TOML file with declarative enhancement effects (`[[enhancement]]` entries) for upgrades implemented in script code.
+    #[arg(long, global = true, value_name = "TOML")]
+    enhancements: Option<PathBuf>,
+
+    /// Round reload and salvo intervals to 0.1 s engine ticks, as the game does.
+    #[arg(long, global = true)]
+    tick_timing: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -52,10 +74,44 @@ enum Commands {
             help = "Optional: JSON file { \"unit_id\": dps } to use as declared DPS instead of blueprint"
         )]
         declared_dps: Option<PathBuf>,
+        /// Cadence window when the weapons have no exact hyperperiod within --max-hyperperiod.
         #[arg(long, default_value_t = DEFAULT_SIMULATION_SECONDS)]
         simulation_seconds: f64,
+        /// Longest weapon hyperperiod (seconds) the cadence check analyses exactly.
+        #[arg(long, default_value_t = DEFAULT_MAX_HYPERPERIOD_SECS)]
+        max_hyperperiod: f64,
         #[arg(long, default_value_t = DEFAULT_CADENCE_GAP_TOLERANCE_SECS)]
         cadence_gap_tolerance: f64,
+        /// Missiles per incoming salvo for the interceptor saturation check.
+        #[arg(long, default_value_t = DEFAULT_THREAT_SALVO_SIZE)]
+        threat_salvo: u32,
+        /// Seconds over which the incoming salvo arrives.
+        #[arg(long, default_value_t = DEFAULT_THREAT_SALVO_WINDOW_SECS)]
+        threat_window: f64,
+        /// Seconds between target switches for the turret switching-DPS estimate.
+        #[arg(long, default_value_t = DEFAULT_TARGET_SWITCH_INTERVAL_SECS)]
+        target_switch_interval: f64,
+        /// Window (seconds) for the max-damage-in-window burst metric; repeatable. Default: 3, 5, 10.
+        #[arg(long = "burst-window", value_name = "SECS")]
+        burst_windows: Vec<f64>,
+        /// Damage total for the time-to-deal-X-damage burst metric; repeatable. Default: 500, 1000, 2500, 5000.
+        #[arg(long = "damage-milestone", value_name = "DAMAGE")]
+        damage_milestones: Vec<f64>,
+        /// Friendly units per square ogrid around the shooter, for the friendly-fire model.
+        #[arg(long, default_value_t = DEFAULT_FORMATION_DENSITY)]
+        formation_density: f64,
+        /// Flag units whose friendly damage exceeds this fraction of their enemy damage.
+        #[arg(long, default_value_t = DEFAULT_FRIENDLY_FIRE_MAX_FRACTION)]
+        friendly_fire_threshold: f64,
+        /// Flag weapons whose rate of fire changes by more than this fraction on ticks.
+        #[arg(long, default_value_t = DEFAULT_ROF_ROUNDING_THRESHOLD)]
+        rof_rounding_threshold: f64,
+        /// Seeded Monte Carlo replays per unit: mean, p5 and p95 effective DPS and TTK.
+        #[arg(long, value_name = "N")]
+        runs: Option<u32>,
+        /// Seed for --runs; stored with the scan so results can be reproduced.
+        #[arg(long, default_value_t = 0, requires = "runs")]
+        seed: u64,
     },
     /// Print readable summary for one unit (by ID or name).
     Unit {
@@ -65,6 +121,123 @@ enum Commands {
         scan_db: Option<PathBuf>,
         unit_id_or_name: String,
     },
+    /// Estimate hit chance and expected DPS of one unit against a target unit at a given range.
+    Hit {
+        #[arg(long, value_name = "PATH")]
+        data_dir: Option<PathBuf>,
+        #[arg(long, value_name = "DB")]
+        scan_db: Option<PathBuf>,
+        /// Attacking unit (ID or name).
+        attacker: String,
+        /// Target unit (ID or name); its footprint and speed drive the miss chance.
+        target: String,
+        #[arg(long, help = "Engagement range in ogrids")]
+        range: f64,
+        #[arg(
+            long,
+            value_name = "SPEED",
+            help = "Override target speed (default: target max speed; 0 for a stationary target)"
+        )]
+        target_speed: Option<f64>,
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Use a seeded Monte Carlo run of N shots per weapon instead of the analytical estimate"
+        )]
+        monte_carlo: Option<u32>,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Time-to-kill matrix between unit pairs; writes ttk.csv and ttk.html.
+    Ttk {
+        #[arg(long, value_name = "PATH")]
+        data_dir: Option<PathBuf>,
+        #[arg(long, value_name = "DB")]
+        scan_db: Option<PathBuf>,
+        #[arg(
+            long = "unit",
+            value_name = "ID",
+            help = "Unit to include (ID or name); repeat for several"
+        )]
+        units: Vec<String>,
+        #[arg(
+            long = "category",
+            value_name = "CATEGORY",
+            help = "Include units carrying this blueprint category; repeat to require several (e.g. TECH1 and LAND)"
+        )]
+        categories: Vec<String>,
+        #[arg(long, default_value_t = 1, help = "Number of attackers (N of A)")]
+        count: u32,
+        #[arg(
+            long,
+            default_value_t = 0,
+            value_parser = clap::value_parser!(u8).range(0..=MAX_VET_LEVEL as i64),
+            help = "Veterancy level of every target (0-5)"
+        )]
+        target_vet: u8,
+        #[arg(
+            long,
+            value_name = "ID",
+            help = "Put every target under this shield unit's shield (vs shielded target)"
+        )]
+        shield: Option<String>,
+        #[arg(long, default_value_t = DEFAULT_TTK_HORIZON_SECS)]
+        horizon: f64,
+        #[arg(long, value_name = "DIR", default_value = "out")]
+        out: PathBuf,
+    },
+    /// Simulate N of unit A against M of unit B closing from range; writes duel.json with a per-second trace.
+    Duel {
+        #[arg(long, value_name = "PATH")]
+        data_dir: Option<PathBuf>,
+        #[arg(long, value_name = "DB")]
+        scan_db: Option<PathBuf>,
+        /// Side A unit (ID or name).
+        unit_a: String,
+        /// Side B unit (ID or name).
+        unit_b: String,
+        #[arg(long, default_value_t = 1, help = "Number of side A units")]
+        count_a: u32,
+        #[arg(long, default_value_t = 1, help = "Number of side B units")]
+        count_b: u32,
+        #[arg(
+            long,
+            value_name = "RANGE",
+            help = "Starting distance in ogrids (default: the longer of the two sides' weapon range)"
+        )]
+        start_range: Option<f64>,
+        #[arg(long, default_value_t = DEFAULT_TTK_HORIZON_SECS)]
+        horizon: f64,
+        #[arg(
+            long = "armor",
+            value_name = "TYPE=MULT",
+            help = "Damage multiplier for a Defense.ArmorType (e.g. Commander=0.9); repeatable"
+        )]
+        armor: Vec<String>,
+        #[arg(long, value_name = "DIR", default_value = "out")]
+        out: PathBuf,
+    },
+    /// Simulate two mixed armies from a TOML scenario; writes battle.json and battle.html.
+    Battle {
+        #[arg(long, value_name = "PATH")]
+        data_dir: Option<PathBuf>,
+        #[arg(long, value_name = "DB")]
+        scan_db: Option<PathBuf>,
+        /// Scenario TOML: [a] and [b] armies with units = [{ unit, count }] and a target policy.
+        scenario: PathBuf,
+        #[arg(long, value_name = "DIR", default_value = "out")]
+        out: PathBuf,
+    },
+    /// Export one unit's scheduler timeline as Chrome trace JSON (chrome://tracing, Perfetto).
+    Timeline {
+        #[arg(long, value_name = "PATH")]
+        data_dir: Option<PathBuf>,
+        #[arg(long, value_name = "DB")]
+        scan_db: Option<PathBuf>,
+        unit_id_or_name: String,
+        #[arg(long, value_name = "DIR", default_value = "out")]
+        out: PathBuf,
+    },
     /// Compare two scans (e.g. before/after patch).
     Diff {
         #[arg(long)]
@@ -92,9 +265,36 @@ fn load_declared_dps(path: &PathBuf) -> Result<HashMap<String, f64>, String> {
     Ok(out)
 }
 
+/// Default analysis settings, with the veterancy buff table and enhancement data loaded when given
+/// and engine-tick timing when requested.
+fn analysis_config(
+    vet_buffs: Option<&Path>,
+    enhancements: Option<&Path>,
+    tick_timing: bool,
+) -> Result<AnalysisConfig, String> {
+    let mut cfg = AnalysisConfig::default();
+    if tick_timing {
+        cfg.timing = TimingMode::Ticks;
+    }
+    if let Some(path) = vet_buffs {
+        cfg.veterancy = VeterancyBuffs::from_toml_file(path)?;
+        tracing::info!("using veterancy buffs from {}", path.display());
+    }
+    if let Some(path) = enhancements {
+        cfg.enhancements = EnhancementEffects::from_toml_file(path)?;
+        tracing::info!("using enhancement effects from {}", path.display());
+    }
+    Ok(cfg)
+}
+
 fn main() -> Result<(), String> {
     let cli = Cli::parse();
     init_logging(cli.verbose);
+    let analysis = analysis_config(
+        cli.vet_buffs.as_deref(),
+        cli.enhancements.as_deref(),
+        cli.tick_timing,
+    )?;
 
     match cli.command {
         Commands::Extract { gamedata, out } => run_extract(gamedata, out),
@@ -103,13 +303,50 @@ fn main() -> Result<(), String> {
             out,
             declared_dps,
             simulation_seconds,
+            max_hyperperiod,
             cadence_gap_tolerance,
+            threat_salvo,
+            threat_window,
+            target_switch_interval,
+            burst_windows,
+            damage_milestones,
+            formation_density,
+            friendly_fire_threshold,
+            rof_rounding_threshold,
+            runs,
+            seed,
         } => run_scan(
             ScanConfig {
                 data_dir,
                 out_dir: out,
-                simulation_seconds,
-                cadence_gap_tolerance_secs: cadence_gap_tolerance,
+                analysis: AnalysisConfig {
+                    simulation_seconds,
+                    max_hyperperiod_secs: max_hyperperiod,
+                    cadence_gap_tolerance_secs: cadence_gap_tolerance,
+                    intercept_threat: InterceptThreat {
+                        salvo_size: threat_salvo,
+                        window_secs: threat_window,
+                    },
+                    target_switch_interval_secs: target_switch_interval,
+                    burst_windows_secs: if burst_windows.is_empty() {
+                        analysis.burst_windows_secs.clone()
+                    } else {
+                        burst_windows
+                    },
+                    damage_milestones: if damage_milestones.is_empty() {
+                        analysis.damage_milestones.clone()
+                    } else {
+                        damage_milestones
+                    },
+                    friendly_fire: FriendlyFireConfig {
+                        density: formation_density,
+                        max_fraction: friendly_fire_threshold,
+                        ..analysis.friendly_fire
+                    },
+                    rof_rounding_threshold,
+                    monte_carlo: runs.map(|runs| MonteCarloRuns { runs, seed }),
+                    ..analysis
+                },
             },
             declared_dps,
         ),
@@ -117,7 +354,91 @@ fn main() -> Result<(), String> {
             data_dir,
             scan_db,
             unit_id_or_name,
-        } => run_unit(data_dir, scan_db, unit_id_or_name),
+        } => run_unit(data_dir, scan_db, &analysis, unit_id_or_name),
+        Commands::Hit {
+            data_dir,
+            scan_db,
+            attacker,
+            target,
+            range,
+            target_speed,
+            monte_carlo,
+            seed,
+        } => run_hit(
+            data_dir,
+            scan_db,
+            &analysis,
+            HitQuery {
+                attacker,
+                target,
+                range,
+                target_speed,
+                monte_carlo,
+                seed,
+            },
+        ),
+        Commands::Ttk {
+            data_dir,
+            scan_db,
+            units,
+            categories,
+            count,
+            target_vet,
+            shield,
+            horizon,
+            out,
+        } => run_ttk(
+            data_dir,
+            scan_db,
+            &analysis,
+            TtkQuery {
+                units,
+                categories,
+                count,
+                target_vet,
+                shield,
+                horizon,
+                out,
+            },
+        ),
+        Commands::Duel {
+            data_dir,
+            scan_db,
+            unit_a,
+            unit_b,
+            count_a,
+            count_b,
+            start_range,
+            horizon,
+            armor,
+            out,
+        } => run_duel(
+            data_dir,
+            scan_db,
+            &analysis,
+            DuelQuery {
+                unit_a,
+                unit_b,
+                count_a,
+                count_b,
+                start_range,
+                horizon,
+                armor,
+                out,
+            },
+        ),
+        Commands::Battle {
+            data_dir,
+            scan_db,
+            scenario,
+            out,
+        } => run_battle(data_dir, scan_db, &analysis, scenario, out),
+        Commands::Timeline {
+            data_dir,
+            scan_db,
+            unit_id_or_name,
+            out,
+        } => run_timeline(data_dir, scan_db, &analysis, unit_id_or_name, out),
         Commands::Diff { a, b, out } => run_diff(a, b, out),
     }
 }
@@ -188,6 +509,35 @@ fn projectile_file_to_key(path: &Path) -> String {
     }
 }
 
+/// Parse every projectile blueprint under `dir` into a map keyed by normalized projectile path.
+/// Unreadable, oversized or unparsable projectile files are skipped.
+fn load_projectile_map(dir: &Path) -> Result<HashMap<String, ProjectileData>, String> {
+    let mut proj_files = Vec::new();
+    collect_projectile_files(dir, &mut proj_files)?;
+    tracing::info!(
+        "loaded {} projectile blueprint(s) for fragment data",
+        proj_files.len()
+    );
+    let mut map = HashMap::new();
+    for path in &proj_files {
+        let content = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+        if content.len() > MAX_BLUEPRINT_FILE_BYTES {
+            continue;
+        }
+        let root = match faf_simlint::parser::parse_blueprint(&content) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        let key = normalize_projectile_path(&projectile_file_to_key(path));
+        let data = projectile_from_lua(&root).unwrap_or_default();
+        map.insert(key, data);
+    }
+    Ok(map)
+}
+
 fn run_scan(cfg: ScanConfig, declared_dps_path: Option<PathBuf>) -> Result<(), String> {
     if !cfg.data_dir.is_dir() {
         return Err(format!(
@@ -208,31 +558,10 @@ fn run_scan(cfg: ScanConfig, declared_dps_path: Option<PathBuf>) -> Result<(), S
     let mut unit_files = Vec::new();
     collect_lua_files(&units_root, &units_root, &mut unit_files)?;
 
-    let mut projectile_map = HashMap::<String, ProjectileData>::new();
-    if let Some(ref proj_dir) = projectiles_root {
-        let mut proj_files = Vec::new();
-        collect_projectile_files(proj_dir, &mut proj_files)?;
-        tracing::info!(
-            "loaded {} projectile blueprint(s) for fragment data",
-            proj_files.len()
-        );
-        for path in &proj_files {
-            let content = match fs::read_to_string(path) {
-                Ok(c) => c,
-                Err(_) => continue,
-            };
-            if content.len() > MAX_BLUEPRINT_FILE_BYTES {
-                continue;
-            }
-            let root = match faf_simlint::parser::parse_blueprint(&content) {
-                Ok(r) => r,
-                Err(_) => continue,
-            };
-            let key = normalize_projectile_path(&projectile_file_to_key(path));
-            let data = projectile_from_lua(&root).unwrap_or_default();
-            projectile_map.insert(key, data);
-        }
-    }
+    let projectile_map = match projectiles_root {
+        Some(ref proj_dir) => load_projectile_map(proj_dir)?,
+        None => HashMap::new(),
+    };
 
     let mut units = Vec::new();
     let projectile_map_ref = if projectile_map.is_empty() {
@@ -246,8 +575,7 @@ fn run_scan(cfg: ScanConfig, declared_dps_path: Option<PathBuf>) -> Result<(), S
         if let Some(summary) = unit_summary_from_file(
             path,
             &content,
-            cfg.simulation_seconds,
-            cfg.cadence_gap_tolerance_secs,
+            &cfg.analysis,
             declared_dps_map.as_ref(),
             projectile_map_ref,
         )? {
@@ -258,13 +586,30 @@ fn run_scan(cfg: ScanConfig, declared_dps_path: Option<PathBuf>) -> Result<(), S
     fs::create_dir_all(&cfg.out_dir).map_err(|e| e.to_string())?;
     let db_path = cfg.out_dir.join("scan.sqlite");
     let store = Store::open(&db_path)?;
-    store.insert_scan(data_dir_canon.to_string_lossy().as_ref(), &units)?;
+    let scan_id = store.insert_scan(
+        data_dir_canon.to_string_lossy().as_ref(),
+        &units,
+        cfg.analysis.monte_carlo,
+    )?;
+    let timelines: Vec<UnitTimeline> = units
+        .iter()
+        .filter_map(|u| unit_timeline(u, &cfg.analysis))
+        .collect();
+    store.insert_timelines(scan_id, &timelines)?;
     tracing::info!("stored scan with {} units", units.len());
+    if let Some(mc) = cfg.analysis.monte_carlo {
+        tracing::info!(
+            "scan {}: monte carlo {} runs per unit, seed {}",
+            scan_id,
+            mc.runs,
+            mc.seed
+        );
+    }
 
     let json_path = cfg.out_dir.join("report.json");
     write_json_report(&units, &json_path)?;
     let html_dir = cfg.out_dir.join("html");
-    write_html_report(&units, &html_dir)?;
+    write_html_report(&units, &timelines, &html_dir, cfg.analysis.timing)?;
     tracing::info!("wrote {} and {}", json_path.display(), html_dir.display());
     Ok(())
 }
@@ -304,86 +649,455 @@ fn collect_lua_files(
     Ok(())
 }
 
-fn run_unit(
+/// Load unit summaries from the latest scan in `scan_db`, or by parsing `data_dir` with default settings.
+fn load_units(
     data_dir: Option<PathBuf>,
     scan_db: Option<PathBuf>,
-    unit_id_or_name: String,
-) -> Result<(), String> {
-    let key = normalize_id(&unit_id_or_name);
+    cfg: &AnalysisConfig,
+) -> Result<Vec<UnitSummary>, String> {
     if let Some(db_path) = scan_db {
         let store = Store::open(&db_path)?;
         let scans = store.list_scans()?;
-        if let Some((scan_id, _data_dir, _created)) = scans.first() {
-            let units = store.get_scan_units(*scan_id)?;
-            for u in units {
-                if normalize_id(&u.unit_id.id) == key
-                    || u.unit_id
-                        .name
-                        .as_ref()
-                        .map(|n| normalize_id(n) == key)
-                        .unwrap_or(false)
-                {
-                    print_unit_summary(&u);
-                    return Ok(());
-                }
-            }
-        }
-        return Err(format!("unit not found in scan: {}", unit_id_or_name));
+        return match scans.first() {
+            Some((scan_id, _data_dir, _created)) => store.get_scan_units(*scan_id),
+            None => Ok(Vec::new()),
+        };
     }
     if let Some(dir) = data_dir {
         let dir_canon = dir.canonicalize().map_err(|e| e.to_string())?;
         let (units_root, projectiles_root) = resolve_scan_dirs(&dir_canon);
         let mut lua_files = Vec::new();
         collect_lua_files(&units_root, &units_root, &mut lua_files)?;
-        let mut projectile_map = HashMap::<String, ProjectileData>::new();
-        if let Some(ref proj_dir) = projectiles_root {
-            let mut proj_files = Vec::new();
-            let _ = collect_projectile_files(proj_dir, &mut proj_files);
-            for path in &proj_files {
-                if let Ok(content) = fs::read_to_string(path) {
-                    if content.len() <= MAX_BLUEPRINT_FILE_BYTES {
-                        if let Ok(root) = faf_simlint::parser::parse_blueprint(&content) {
-                            let key = normalize_projectile_path(&projectile_file_to_key(path));
-                            let data = projectile_from_lua(&root).unwrap_or_default();
-                            projectile_map.insert(key, data);
-                        }
-                    }
-                }
-            }
-        }
+        let projectile_map = match projectiles_root {
+            Some(ref proj_dir) => load_projectile_map(proj_dir).unwrap_or_default(),
+            None => HashMap::new(),
+        };
         let projectile_map_ref = if projectile_map.is_empty() {
             None
         } else {
             Some(&projectile_map)
         };
+        let mut units = Vec::new();
         for path in &lua_files {
             let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
-            if let Some(summary) = unit_summary_from_file(
-                path,
-                &content,
-                DEFAULT_SIMULATION_SECONDS,
-                DEFAULT_CADENCE_GAP_TOLERANCE_SECS,
-                None,
-                projectile_map_ref,
-            )? {
-                if normalize_id(&summary.unit_id.id) == key
-                    || summary
-                        .unit_id
-                        .name
-                        .as_ref()
-                        .map(|n| normalize_id(n) == key)
-                        .unwrap_or(false)
-                {
-                    print_unit_summary(&summary);
-                    return Ok(());
-                }
+            if let Some(summary) =
+                unit_summary_from_file(path, &content, cfg, None, projectile_map_ref)?
+            {
+                units.push(summary);
             }
         }
-        return Err(format!("unit not found in data dir: {}", unit_id_or_name));
+        return Ok(units);
     }
     Err("provide --data-dir or --scan-db".to_string())
 }
 
+/// Find a unit by blueprint ID or display name (case-insensitive).
+fn find_unit<'a>(units: &'a [UnitSummary], unit_id_or_name: &str) -> Option<&'a UnitSummary> {
+    let key = normalize_id(unit_id_or_name);
+    units.iter().find(|u| {
+        normalize_id(&u.unit_id.id) == key
+            || u.unit_id
+                .name
+                .as_ref()
+                .map(|n| normalize_id(n) == key)
+                .unwrap_or(false)
+    })
+}
+
+fn run_unit(
+    data_dir: Option<PathBuf>,
+    scan_db: Option<PathBuf>,
+    cfg: &AnalysisConfig,
+    unit_id_or_name: String,
+) -> Result<(), String> {
+    let units = load_units(data_dir, scan_db, cfg)?;
+    let u = find_unit(&units, &unit_id_or_name)
+        .ok_or_else(|| format!("unit not found: {}", unit_id_or_name))?;
+    print_unit_summary(u);
+    Ok(())
+}
+
+/// Arguments of the `hit` subcommand.
+struct HitQuery {
+    attacker: String,
+    target: String,
+    range: f64,
+    target_speed: Option<f64>,
+    monte_carlo: Option<u32>,
+    seed: u64,
+}
+
+fn run_hit(
+    data_dir: Option<PathBuf>,
+    scan_db: Option<PathBuf>,
+    cfg: &AnalysisConfig,
+    q: HitQuery,
+) -> Result<(), String> {
+    let range = q.range;
+    if range.is_nan() || range < 0.0 {
+        return Err(format!("invalid range: {}", range));
+    }
+    let units = load_units(data_dir, scan_db, cfg)?;
+    let a =
+        find_unit(&units, &q.attacker).ok_or_else(|| format!("unit not found: {}", q.attacker))?;
+    let t = find_unit(&units, &q.target).ok_or_else(|| format!("unit not found: {}", q.target))?;
+    let mode = match q.monte_carlo {
+        Some(runs) => HitMode::MonteCarlo { runs, seed: q.seed },
+        None => HitMode::Analytical,
+    };
+    let mut profile = TargetProfile::from_stats(&t.stats);
+    if let Some(speed) = q.target_speed {
+        profile.speed = speed.max(0.0);
+    }
+    let estimates = expected_dps_vs_target(a, &profile, range, mode);
+    let mode_label = match mode {
+        HitMode::Analytical => "analytical".to_string(),
+        HitMode::MonteCarlo { runs, seed } => format!("monte carlo, {} runs, seed {}", runs, seed),
+    };
+    println!(
+        "Hit estimate: {} -> {} at range {} ({})",
+        a.unit_id.id, t.unit_id.id, range, mode_label
+    );
+    println!(
+        "Target: radius {:.2}  speed {:.2}",
+        profile.radius, profile.speed
+    );
+    for e in &estimates {
+        if !e.in_range {
+            println!("  {}  out of range", e.weapon_bp_id);
+            continue;
+        }
+        println!(
+            "  {}  flight={:.2}s  spread={:.2}  movement={:.2}  hit={:.1}%  expected_dps={:.2}",
+            e.weapon_bp_id,
+            e.flight_time_sec,
+            e.spread_radius,
+            e.movement_radius,
+            e.hit_chance * 100.0,
+            e.expected_dps
+        );
+    }
+    let expected: f64 = estimates.iter().map(|e| e.expected_dps).sum();
+    let effective = a.total_dps();
+    println!(
+        "Total expected DPS: {:.2} (effective {:.2})",
+        expected, effective
+    );
+    Ok(())
+}
+
+/// Arguments of the `ttk` subcommand.
+struct TtkQuery {
+    units: Vec<String>,
+    categories: Vec<String>,
+    count: u32,
+    target_vet: u8,
+    shield: Option<String>,
+    horizon: f64,
+    out: PathBuf,
+}
+
+fn run_ttk(
+    data_dir: Option<PathBuf>,
+    scan_db: Option<PathBuf>,
+    cfg: &AnalysisConfig,
+    q: TtkQuery,
+) -> Result<(), String> {
+    let TtkQuery {
+        units: unit_keys,
+        categories,
+        count,
+        target_vet,
+        shield,
+        horizon,
+        out,
+    } = q;
+    if count == 0 {
+        return Err("--count must be at least 1".to_string());
+    }
+    let all = load_units(data_dir, scan_db, cfg)?;
+    let shield_unit = match &shield {
+        Some(key) => {
+            let u = find_unit(&all, key).ok_or_else(|| format!("unit not found: {}", key))?;
+            if u.stats.shield.is_none() {
+                return Err(format!("{} has no Defense.Shield", u.unit_id.id));
+            }
+            Some(u.clone())
+        }
+        None => None,
+    };
+    let mut selected: Vec<UnitSummary> = Vec::new();
+    for key in &unit_keys {
+        let u = find_unit(&all, key).ok_or_else(|| format!("unit not found: {}", key))?;
+        selected.push(u.clone());
+    }
+    if !categories.is_empty() {
+        for u in &all {
+            if u.stats.has_categories(&categories)
+                && !selected.iter().any(|s| s.unit_id.id == u.unit_id.id)
+            {
+                selected.push(u.clone());
+            }
+        }
+    }
+    if unit_keys.is_empty() && categories.is_empty() {
+        selected = all;
+    }
+    // Without Defense.MaxHealth a unit is no target: its column would read as an instant kill.
+    selected.retain(|u| {
+        let keep = u.stats.max_health.is_some();
+        if !keep {
+            tracing::warn!("skipping {}: no Defense.MaxHealth", u.unit_id.id);
+        }
+        keep
+    });
+    if selected.is_empty() {
+        return Err("no units match the selection".to_string());
+    }
+    let matrix = ttk_matrix(
+        &selected,
+        count,
+        target_vet,
+        horizon,
+        shield_unit.as_ref(),
+        cfg.timing,
+    );
+    fs::create_dir_all(&out).map_err(|e| e.to_string())?;
+    let csv_path = out.join("ttk.csv");
+    let html_path = out.join("ttk.html");
+    write_ttk_csv(&matrix, &csv_path)?;
+    write_ttk_html(&matrix, &html_path)?;
+    tracing::info!(
+        "wrote {}x{} TTK matrix to {} and {}",
+        selected.len(),
+        selected.len(),
+        csv_path.display(),
+        html_path.display()
+    );
+    Ok(())
+}
+
+/// Timeline stored with the latest scan in `scan_db`, else simulated from the unit summary.
+fn run_timeline(
+    data_dir: Option<PathBuf>,
+    scan_db: Option<PathBuf>,
+    cfg: &AnalysisConfig,
+    unit_id_or_name: String,
+    out: PathBuf,
+) -> Result<(), String> {
+    let units = load_units(data_dir, scan_db.clone(), cfg)?;
+    let u = find_unit(&units, &unit_id_or_name)
+        .ok_or_else(|| format!("unit not found: {}", unit_id_or_name))?;
+    let stored = match &scan_db {
+        Some(db_path) => {
+            let store = Store::open(db_path)?;
+            match store.list_scans()?.first() {
+                Some((scan_id, _, _)) => store.get_timeline(*scan_id, &u.unit_id.id)?,
+                None => None,
+            }
+        }
+        None => None,
+    };
+    let timeline = match stored {
+        Some(t) => t,
+        None => unit_timeline(u, cfg)
+            .ok_or_else(|| format!("{} has no combat weapons", u.unit_id.id))?,
+    };
+    fs::create_dir_all(&out).map_err(|e| e.to_string())?;
+    let path = out.join(format!(
+        "timeline_{}.json",
+        normalize_id(&u.unit_id.id).replace(' ', "_")
+    ));
+    write_chrome_trace(&timeline, &path)?;
+    println!(
+        "Timeline {}: {} shots, {} gaps over {}s -> {}",
+        timeline.unit_id,
+        timeline.events.len(),
+        timeline.gaps.len(),
+        timeline.window_sec,
+        path.display()
+    );
+    Ok(())
+}
+
+/// Arguments of the `duel` subcommand.
+struct DuelQuery {
+    unit_a: String,
+    unit_b: String,
+    count_a: u32,
+    count_b: u32,
+    start_range: Option<f64>,
+    horizon: f64,
+    armor: Vec<String>,
+    out: PathBuf,
+}
+
+/// Parse `TYPE=MULT` armor multipliers; types are matched case-insensitively.
+fn parse_armor(entries: &[String]) -> Result<BTreeMap<String, f64>, String> {
+    entries
+        .iter()
+        .map(|entry| {
+            let (armor_type, mult) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("invalid --armor (expected TYPE=MULT): {}", entry))?;
+            let mult: f64 = mult
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid armor multiplier: {}", entry))?;
+            if !mult.is_finite() || mult < 0.0 {
+                return Err(format!("invalid armor multiplier: {}", entry));
+            }
+            Ok((armor_type.trim().to_lowercase(), mult))
+        })
+        .collect()
+}
+
+fn run_duel(
+    data_dir: Option<PathBuf>,
+    scan_db: Option<PathBuf>,
+    cfg: &AnalysisConfig,
+    q: DuelQuery,
+) -> Result<(), String> {
+    if q.count_a == 0 || q.count_b == 0 {
+        return Err("--count-a and --count-b must be at least 1".to_string());
+    }
+    if let Some(r) = q.start_range.filter(|r| r.is_nan() || *r < 0.0) {
+        return Err(format!("invalid start range: {}", r));
+    }
+    let armor = parse_armor(&q.armor)?;
+    let units = load_units(data_dir, scan_db, cfg)?;
+    let a = find_unit(&units, &q.unit_a).ok_or_else(|| format!("unit not found: {}", q.unit_a))?;
+    let b = find_unit(&units, &q.unit_b).ok_or_else(|| format!("unit not found: {}", q.unit_b))?;
+    let result = duel(
+        DuelSide {
+            unit: a,
+            count: q.count_a,
+        },
+        DuelSide {
+            unit: b,
+            count: q.count_b,
+        },
+        &DuelOptions {
+            start_range: q.start_range,
+            horizon_sec: q.horizon,
+            armor,
+            timing: cfg.timing,
+        },
+    );
+    let winner = match result.outcome {
+        DuelOutcome::A => format!("{} x{} (A)", result.unit_a, result.count_a),
+        DuelOutcome::B => format!("{} x{} (B)", result.unit_b, result.count_b),
+        DuelOutcome::Draw => "draw (both destroyed)".to_string(),
+        DuelOutcome::Timeout => format!("none within {}s", q.horizon),
+    };
+    println!(
+        "Duel: {} x{} vs {} x{} from range {:.1}",
+        result.unit_a, result.count_a, result.unit_b, result.count_b, result.start_range
+    );
+    println!("Winner: {}  time={:.2}s", winner, result.end_time_sec);
+    let first = |t: Option<f64>| {
+        t.map(|t| format!("{:.2}s", t))
+            .unwrap_or_else(|| "—".to_string())
+    };
+    for (label, id, side, first_shot) in [
+        ("A", &result.unit_a, &result.a, result.first_shot_a_sec),
+        ("B", &result.unit_b, &result.b, result.first_shot_b_sec),
+    ] {
+        println!(
+            "  {} {}: alive={}  health={:.0}  shield={:.0}  first_shot={}",
+            label,
+            id,
+            side.alive,
+            side.health,
+            side.shield_health,
+            first(first_shot)
+        );
+    }
+    fs::create_dir_all(&q.out).map_err(|e| e.to_string())?;
+    let json_path = q.out.join("duel.json");
+    write_duel_json(&result, &json_path)?;
+    tracing::info!("wrote {}", json_path.display());
+    Ok(())
+}
+
+fn run_battle(
+    data_dir: Option<PathBuf>,
+    scan_db: Option<PathBuf>,
+    cfg: &AnalysisConfig,
+    scenario_path: PathBuf,
+    out: PathBuf,
+) -> Result<(), String> {
+    let scenario = BattleScenario::from_toml_file(&scenario_path)?;
+    let units = load_units(data_dir, scan_db, cfg)?;
+    let mut sides = Vec::new();
+    for (label, side) in [("A", &scenario.a), ("B", &scenario.b)] {
+        let mut groups = Vec::new();
+        for g in side.units.iter().filter(|g| g.count > 0) {
+            let u =
+                find_unit(&units, &g.unit).ok_or_else(|| format!("unit not found: {}", g.unit))?;
+            groups.push((u, g.count));
+        }
+        sides.push(BattleSide {
+            name: side.name.clone().unwrap_or_else(|| label.to_string()),
+            policy: side.policy,
+            groups,
+        });
+    }
+    let result = battle(
+        &sides[0],
+        &sides[1],
+        &BattleOptions {
+            start_range: scenario.start_range,
+            horizon_sec: scenario.horizon_sec,
+            armor: scenario
+                .armor
+                .iter()
+                .map(|(t, m)| (t.to_lowercase(), *m))
+                .collect(),
+            timing: cfg.timing,
+        },
+    );
+    let name = scenario.name.clone().unwrap_or_else(|| {
+        scenario_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    });
+    let winner = match result.outcome {
+        DuelOutcome::A => format!("{} (A)", result.a.name),
+        DuelOutcome::B => format!("{} (B)", result.b.name),
+        DuelOutcome::Draw => "draw (both destroyed)".to_string(),
+        DuelOutcome::Timeout => format!("none within {}s", scenario.horizon_sec),
+    };
+    println!("Battle: {} from range {:.1}", name, result.start_range);
+    println!("Winner: {}  time={:.2}s", winner, result.end_time_sec);
+    for (label, army) in [("A", &result.a), ("B", &result.b)] {
+        println!(
+            "  {} {} ({}): survivors={}/{}  dealt={:.0}  overkill={:.0}",
+            label,
+            army.name,
+            army.policy.as_str(),
+            army.survivors(),
+            army.units(),
+            army.damage_dealt(),
+            army.damage_wasted()
+        );
+        for g in &army.groups {
+            println!(
+                "    {} x{}: survivors={}  dealt={:.0}  overkill={:.0}",
+                g.unit_id, g.count, g.survivors, g.damage_dealt, g.damage_wasted
+            );
+        }
+    }
+    fs::create_dir_all(&out).map_err(|e| e.to_string())?;
+    let json_path = out.join("battle.json");
+    write_battle_json(&result, &json_path)?;
+    let html_path = out.join("battle.html");
+    write_battle_html(&name, &result, &html_path)?;
+    tracing::info!("wrote {} and {}", json_path.display(), html_path.display());
+    Ok(())
+}
+
 fn print_unit_summary(u: &faf_simlint::model::UnitSummary) {
     println!(
         "Unit: {} ({})",
@@ -391,20 +1105,198 @@ fn print_unit_summary(u: &faf_simlint::model::UnitSummary) {
         u.unit_id.name.as_deref().unwrap_or("—")
     );
     println!("Blueprint: {}", u.blueprint_path);
+    if let Some(hp) = u.stats.max_health {
+        println!(
+            "Health: {}  regen={}/s",
+            hp,
+            u.stats.regen_rate.unwrap_or(0.0)
+        );
+    }
+    if let Some(s) = u.stats.shield {
+        println!(
+            "Shield: {}  regen={}/s  recharge={}s  size={}",
+            s.max_health, s.regen_rate, s.recharge_time, s.size
+        );
+    }
+    for i in &u.interceptors {
+        println!(
+            "Interceptor: {}  intercepts={}  per_min={:.1}  range={}",
+            i.weapon_bp_id,
+            i.target.as_str(),
+            i.interceptions_per_min,
+            i.range
+        );
+    }
+    if let Some(mass) = u.stats.build_cost_mass {
+        let c = &u.cost_efficiency;
+        let opt = |v: Option<f64>| {
+            v.map(|v| format!("{:.4}", v))
+                .unwrap_or_else(|| "—".to_string())
+        };
+        println!(
+            "Cost: mass={}  energy={}  build_time={}  dps/mass={}  dps/energy={}  ehp/mass={}  dps/build-s={}",
+            mass,
+            u.stats.build_cost_energy.unwrap_or(0.0),
+            u.stats.build_time.unwrap_or(0.0),
+            opt(c.dps_per_mass),
+            opt(c.dps_per_energy),
+            opt(c.ehp_per_mass),
+            opt(c.dps_per_build_second)
+        );
+    }
     println!("\nDeclared weapons:");
-    for w in &u.weapons {
+    for (i, w) in u.weapons.iter().enumerate() {
         println!(
-            "  {}  damage={}  projectiles={}  ROF={}  range={}",
-            w.weapon_bp_id, w.damage, w.projectiles_per_fire, w.rate_of_fire, w.range
+            "  {}  role={}  damage={}  projectiles={}  ROF={}  range={}-{}",
+            w.display_name(i),
+            w.role.as_str(),
+            w.damage,
+            w.projectiles_per_fire,
+            w.rate_of_fire,
+            w.min_range,
+            w.range
         );
     }
+    for w in &u.weapons {
+        if let Some(tree) = w
+            .projectile_tree
+            .as_ref()
+            .filter(|t| !t.fragments.is_empty())
+        {
+            println!("\nProjectile tree ({}):", w.weapon_bp_id);
+            print_projectile_node(tree, 1);
+        }
+    }
     println!("\nEffective (computed):");
+    for (i, (e, w)) in u.effective.iter().zip(&u.weapons).enumerate() {
+        println!(
+            "  {}  nominal_dps={:.2}  effective_dps={:.2}  cycle_sec={:.3}  flight_sec={}  acquisition_sec={}  first_shot_sec={:.2}  switching_dps={:.2}",
+            w.display_name(i),
+            e.nominal_dps,
+            e.effective_dps,
+            e.cycle_time_sec,
+            e.time_of_flight_sec
+                .map(|t| format!("{:.3}", t))
+                .unwrap_or_else(|| "—".to_string()),
+            e.acquisition
+                .map(|a| format!("{:.2}/{:.2}", a.avg_sec, a.worst_sec))
+                .unwrap_or_else(|| "—".to_string()),
+            e.first_shot_latency_sec,
+            e.switching_dps
+        );
+    }
     for e in &u.effective {
+        if let Some(r) = e.attack_run {
+            println!(
+                "Attack run ({}): interval={:.2}s  damage/pass={:.0}  sustained_dps={:.2}  rof_dps={:.2}  carpet={:.1}",
+                e.weapon_bp_id,
+                r.reattack_interval_sec,
+                r.damage_per_pass,
+                r.sustained_dps,
+                r.rof_dps,
+                r.carpet_length
+            );
+        }
+    }
+    for f in &u.friendly_fire {
         println!(
-            "  {}  nominal_dps={:.2}  effective_dps={:.2}  cycle_sec={:.3}",
-            e.weapon_bp_id, e.nominal_dps, e.effective_dps, e.cycle_time_sec
+            "Friendly fire ({}): per_shot={:.1}  collide_chance={:.2}  friendly/min={:.0}  enemy/min={:.0}{}",
+            f.weapon_bp_id,
+            f.friendly_damage_per_shot,
+            f.collide_chance,
+            f.friendly_damage_per_min,
+            f.enemy_damage_per_min,
+            if f.self_splash { "  self-splash" } else { "" }
         );
     }
+    if let Some(mc) = &u.monte_carlo {
+        println!("\nMonte Carlo ({} runs, seed {}):", mc.runs, mc.seed);
+        let d = &mc.effective_dps;
+        println!(
+            "  effective_dps mean={:.2}  p5={:.2}  p95={:.2}",
+            d.mean, d.p5, d.p95
+        );
+        match &mc.ttk_sec {
+            Some(t) => println!(
+                "  ttk_vs_self mean={:.2}s  p5={:.2}s  p95={:.2}s  kill_fraction={:.2}",
+                t.mean, t.p5, t.p95, mc.kill_fraction
+            ),
+            None => println!("  ttk_vs_self —"),
+        }
+    }
+    if u.burst.alpha_damage > 0.0 {
+        let b = &u.burst;
+        println!("\nBurst (over {}s):", b.horizon_sec);
+        println!("  alpha={:.0}", b.alpha_damage);
+        for w in &b.windows {
+            println!("  max in {}s={:.0}", w.window_sec, w.max_damage);
+        }
+        for m in &b.milestones {
+            println!(
+                "  time to {:.0}={}",
+                m.damage,
+                m.time_sec
+                    .map(|t| format!("{:.2}s", t))
+                    .unwrap_or_else(|| "—".to_string())
+            );
+        }
+    }
+    if !u.range_bands.is_empty() {
+        println!("\nDPS by range:");
+        for b in &u.range_bands {
+            if b.is_dead_zone() {
+                println!("  {:>6.1}–{:<6.1}  dead zone", b.start, b.end);
+            } else {
+                println!(
+                    "  {:>6.1}–{:<6.1}  dps={:.2}  {}",
+                    b.start,
+                    b.end,
+                    b.dps,
+                    b.weapon_ids.join(",")
+                );
+            }
+        }
+    }
+    if !u.variants.is_empty() {
+        println!("\nEnhancements:");
+        for v in &u.variants {
+            let dps = v.summary.total_dps();
+            println!(
+                "  {}  path={}  slot={}  dps={:.2}  max_health={}  cost={:.0}M/{:.0}E/{:.0}s{}",
+                v.enhancement,
+                v.path.join(">"),
+                v.slot.as_deref().unwrap_or("—"),
+                dps,
+                v.summary.stats.max_health.unwrap_or(0.0),
+                v.build_cost_mass,
+                v.build_cost_energy,
+                v.build_time,
+                if v.uses_declared_data {
+                    "  (data file)"
+                } else {
+                    ""
+                }
+            );
+        }
+    }
+    if !u.veterancy.is_empty() {
+        println!("\nVeterancy:");
+        for v in &u.veterancy {
+            println!(
+                "  vet{}  threshold={}  max_health={:.0}  regen={:.1}/s  ehp={:.0}  mirror_ttk={}",
+                v.level,
+                v.threshold
+                    .map(|t| t.to_string())
+                    .unwrap_or_else(|| "—".to_string()),
+                v.max_health,
+                v.regen_rate,
+                v.effective_hp,
+                v.mirror_ttk_sec
+                    .map(|t| format!("{:.1}s", t))
+                    .unwrap_or_else(|| "no kill".to_string())
+            );
+        }
+    }
     println!("\nAnomalies:");
     for a in &u.anomalies {
         let sev = match a.severity {
@@ -419,6 +1311,43 @@ fn print_unit_summary(u: &faf_simlint::model::UnitSummary) {
     }
 }
 
+fn print_projectile_node(n: &faf_simlint::model::ProjectileNode, depth: usize) {
+    println!(
+        "{}x{} {}  damage={}{}{}",
+        "  ".repeat(depth),
+        n.count,
+        n.projectile_id,
+        n.damage,
+        if n.inherited_damage {
+            " (inherited)"
+        } else {
+            ""
+        },
+        match n.status {
+            faf_simlint::model::ProjectileStatus::Resolved => "",
+            faf_simlint::model::ProjectileStatus::Missing => "  MISSING",
+            faf_simlint::model::ProjectileStatus::Cycle => "  CYCLE",
+        }
+    );
+    for f in &n.fragments {
+        print_projectile_node(f, depth + 1);
+    }
+}
+
+/// Total effective DPS per diff entry: each unit, plus each enhancement variant as "unit/enhancement".
+fn diff_entries(
+    units: &[faf_simlint::model::UnitSummary],
+) -> std::collections::BTreeMap<String, f64> {
+    let mut out = std::collections::BTreeMap::new();
+    for u in units {
+        out.insert(u.unit_id.id.clone(), u.total_dps());
+        for v in &u.variants {
+            out.insert(v.summary.unit_id.id.clone(), v.summary.total_dps());
+        }
+    }
+    out
+}
+
 fn run_diff(a: PathBuf, b: PathBuf, out: Option<PathBuf>) -> Result<(), String> {
     let store_a = Store::open(&a)?;
     let store_b = Store::open(&b)?;
@@ -430,13 +1359,11 @@ fn run_diff(a: PathBuf, b: PathBuf, out: Option<PathBuf>) -> Result<(), String>
     );
     let units_a = store_a.get_scan_units(id_a)?;
     let units_b = store_b.get_scan_units(id_b)?;
-    let ids_a: std::collections::HashSet<_> =
-        units_a.iter().map(|u| u.unit_id.id.as_str()).collect();
-    let ids_b: std::collections::HashSet<_> =
-        units_b.iter().map(|u| u.unit_id.id.as_str()).collect();
-    let added: Vec<_> = ids_b.difference(&ids_a).collect();
-    let removed: Vec<_> = ids_a.difference(&ids_b).collect();
-    let common: Vec<_> = ids_a.intersection(&ids_b).copied().collect();
+    let dps_a = diff_entries(&units_a);
+    let dps_b = diff_entries(&units_b);
+    let added: Vec<_> = dps_b.keys().filter(|k| !dps_a.contains_key(*k)).collect();
+    let removed: Vec<_> = dps_a.keys().filter(|k| !dps_b.contains_key(*k)).collect();
+    let common: Vec<_> = dps_a.keys().filter(|k| dps_b.contains_key(*k)).collect();
 
     println!("Diff: {} vs {}", a.display(), b.display());
     println!("Units added: {}", added.len());
@@ -451,14 +1378,9 @@ fn run_diff(a: PathBuf, b: PathBuf, out: Option<PathBuf>) -> Result<(), String>
 
     let mut regressions = Vec::new();
     for id in &common {
-        let ua = units_a.iter().find(|u| u.unit_id.id.as_str() == *id);
-        let ub = units_b.iter().find(|u| u.unit_id.id.as_str() == *id);
-        if let (Some(ua), Some(ub)) = (ua, ub) {
-            let dps_a: f64 = ua.effective.iter().map(|e| e.effective_dps).sum();
-            let dps_b: f64 = ub.effective.iter().map(|e| e.effective_dps).sum();
-            if dps_b < dps_a * 0.95 {
-                regressions.push((id.to_string(), dps_a, dps_b));
-            }
+        let (before, after) = (dps_a[*id], dps_b[*id]);
+        if after < before * 0.95 {
+            regressions.push((id.to_string(), before, after));
         }
     }
     if !regressions.is_empty() {
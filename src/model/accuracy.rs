@@ -0,0 +1,209 @@
+//! Ballistic accuracy: analytical hit-chance estimate plus an optional seeded Monte Carlo check.
+//!
+//! Landing error is modelled as two independent uniform discs around the aim point:
+//! FiringRandomness × flight time (muzzle spread) and target speed × flight time (the target
+//! leaving the predicted impact point). A shot counts as a hit when it lands within the
+//! target footprint radius plus the weapon's DamageRadius.
+
+use super::{UnitStats, UnitSummary, WeaponDeclared};
+use crate::util::SeededRng;
+use serde::{Deserialize, Serialize};
+
+/// Engine gravity in ogrids/s² (used for ballistic flight time).
+pub const GRAVITY: f64 = 4.9;
+
+/// Footprint used when the target blueprint has no SizeX/SizeZ.
+const DEFAULT_TARGET_SIZE: f64 = 1.0;
+
+/// What the shooter is aiming at: footprint radius and movement speed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TargetProfile {
+    pub radius: f64,
+    pub speed: f64,
+}
+
+impl TargetProfile {
+    /// Radius is half the mean of SizeX and SizeZ; speed is the unit's max speed (0 for structures).
+    pub fn from_stats(stats: &UnitStats) -> Self {
+        let x = stats.size_x.unwrap_or(DEFAULT_TARGET_SIZE);
+        let z = stats.size_z.unwrap_or(x);
+        Self {
+            radius: 0.25 * (x + z),
+            speed: stats.max_speed.unwrap_or(0.0).max(0.0),
+        }
+    }
+}
+
+/// How the hit chance is evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitMode {
+    /// Closed-form estimate; deterministic and cheap.
+    Analytical,
+    /// Sample `runs` shots per weapon from a generator seeded with `seed`.
+    MonteCarlo { runs: u32, seed: u64 },
+}
+
+/// Hit estimate for one weapon against one target at one range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HitEstimate {
+    pub weapon_bp_id: String,
+    pub range: f64,
+    pub in_range: bool,
+    pub flight_time_sec: f64,
+    /// Radius of the muzzle spread disc at impact (FiringRandomness × flight time).
+    pub spread_radius: f64,
+    /// Radius the target can move during flight (speed × flight time).
+    pub movement_radius: f64,
+    pub hit_chance: f64,
+    /// Weapon effective DPS × hit chance (0 when out of range).
+    pub expected_dps: f64,
+}
+
+/// Flight time to `range` from MuzzleVelocity and BallisticArc.
+/// RULEUBA_None flies straight; low/high arc use the ballistic range equation and fall back to the
+/// 45° solution when the target is beyond the weapon's ballistic reach. Zero when MuzzleVelocity is unset.
+pub fn flight_time_sec(w: &WeaponDeclared, range: f64) -> f64 {
+    let Some(v) = w.muzzle_velocity.filter(|v| *v > 0.0) else {
+        return 0.0;
+    };
+    if range <= 0.0 {
+        return 0.0;
+    }
+    let arc = w.ballistic_arc.as_deref().unwrap_or("RULEUBA_None");
+    if arc == "RULEUBA_None" {
+        return range / v;
+    }
+    let s = (GRAVITY * range / (v * v)).min(1.0);
+    let low = 0.5 * s.asin();
+    let angle = if arc == "RULEUBA_HighArc" {
+        std::f64::consts::FRAC_PI_2 - low
+    } else {
+        low
+    };
+    2.0 * v * angle.sin() / GRAVITY
+}
+
+/// Radius of a single uniform disc with the same spread as the two error discs combined.
+pub fn combined_miss_radius(spread_radius: f64, movement_radius: f64) -> f64 {
+    (spread_radius * spread_radius + movement_radius * movement_radius).sqrt()
+}
+
+/// Analytical hit chance: fraction of the combined error disc covered by the hit radius.
+/// Exact when either error source is zero; otherwise a variance-matched approximation.
+pub fn hit_chance(spread_radius: f64, movement_radius: f64, hit_radius: f64) -> f64 {
+    let miss = combined_miss_radius(spread_radius, movement_radius);
+    if miss <= hit_radius {
+        return 1.0;
+    }
+    (hit_radius / miss).powi(2)
+}
+
+/// Monte Carlo hit chance: sample both error discs independently and count landings inside the hit radius.
+pub fn hit_chance_monte_carlo(
+    spread_radius: f64,
+    movement_radius: f64,
+    hit_radius: f64,
+    runs: u32,
+    rng: &mut SeededRng,
+) -> f64 {
+    if runs == 0 {
+        return hit_chance(spread_radius, movement_radius, hit_radius);
+    }
+    let mut hits = 0u32;
+    for _ in 0..runs {
+        let (sx, sy) = rng.point_in_disc(spread_radius);
+        let (mx, my) = rng.point_in_disc(movement_radius);
+        let (dx, dy) = (sx + mx, sy + my);
+        if dx * dx + dy * dy <= hit_radius * hit_radius {
+            hits += 1;
+        }
+    }
+    hits as f64 / runs as f64
+}
+
+/// Expected DPS of each attacker combat weapon against `target` at `range`.
+/// Weapons outside their MinRadius..MaxRadius band get hit chance 0. Monte Carlo uses one
+/// generator for the whole unit, so results depend only on the seed and the weapon order.
+pub fn expected_dps_vs_target(
+    attacker: &UnitSummary,
+    target: &TargetProfile,
+    range: f64,
+    mode: HitMode,
+) -> Vec<HitEstimate> {
+    let mut rng = match mode {
+        HitMode::MonteCarlo { seed, .. } => Some(SeededRng::new(seed)),
+        HitMode::Analytical => None,
+    };
+    attacker
+        .combat()
+        .map(|(w, e)| {
+            let in_range = range >= w.min_range && range <= w.range;
+            let flight = flight_time_sec(w, range);
+            let spread = w.firing_randomness.unwrap_or(0.0).max(0.0) * flight;
+            let movement = target.speed * flight;
+            let hit_radius = target.radius + w.damage_radius.max(0.0);
+            let chance = if !in_range {
+                0.0
+            } else {
+                match (mode, rng.as_mut()) {
+                    (HitMode::MonteCarlo { runs, .. }, Some(rng)) => {
+                        hit_chance_monte_carlo(spread, movement, hit_radius, runs, rng)
+                    }
+                    _ => hit_chance(spread, movement, hit_radius),
+                }
+            };
+            HitEstimate {
+                weapon_bp_id: w.weapon_bp_id.clone(),
+                range,
+                in_range,
+                flight_time_sec: flight,
+                spread_radius: spread,
+                movement_radius: movement,
+                hit_chance: chance,
+                expected_dps: e.effective_dps * chance,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artillery(arc: &str) -> WeaponDeclared {
+        WeaponDeclared {
+            weapon_bp_id: "Arty".to_string(),
+            muzzle_velocity: Some(14.0),
+            ballistic_arc: Some(arc.to_string()),
+            firing_randomness: Some(0.5),
+            range: 30.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn high_arc_flies_longer_than_low_arc() {
+        let low = flight_time_sec(&artillery("RULEUBA_LowArc"), 25.0);
+        let high = flight_time_sec(&artillery("RULEUBA_HighArc"), 25.0);
+        assert!(low > 0.0 && high > low);
+    }
+
+    #[test]
+    fn straight_flight_time_is_range_over_velocity() {
+        let t = flight_time_sec(&artillery("RULEUBA_None"), 28.0);
+        assert!((t - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn no_spread_always_hits() {
+        assert_eq!(hit_chance(0.0, 0.0, 0.5), 1.0);
+        assert!(hit_chance(4.0, 0.0, 1.0) < 0.1);
+    }
+
+    #[test]
+    fn monte_carlo_matches_analytical_for_single_disc() {
+        let mut rng = SeededRng::new(1);
+        let mc = hit_chance_monte_carlo(4.0, 0.0, 2.0, 20_000, &mut rng);
+        assert!((mc - hit_chance(4.0, 0.0, 2.0)).abs() < 0.02);
+    }
+}
@@ -0,0 +1,582 @@
+//! Group battle: two mixed armies on a line, stepped on engine ticks.
+//!
+//! Each army is a list of groups (N copies of one blueprint). Army A starts at 0 and army B at
+//! `start_range`; every group advances at its max speed until the nearest enemy group is within
+//! its longest weapon range, then holds. A weapon starts its scheduler timeline when an enemy
+//! first comes within its MaxRadius, and the group's units fire it in sync. As in the TTK model,
+//! every shot hits.
+//!
+//! Each volley is aimed when fired, by the army's `TargetPolicy`, at one enemy unit in range,
+//! and lands at impact time. Damage beyond what kills the target, and whole volleys landing on
+//! a unit that is already dead, are counted as overkill waste. A unit's own shield absorbs
+//! shield-blocked damage first; the part of a hit beyond a collapsing shield's health is waste
+//! too (it does not spill onto health). Health regenerates between ticks.
+
+use super::ttk::{armor_multiplier, reach, unit_schedule, Member};
+use super::{volley_damage, DuelOutcome, UnitSummary};
+use crate::config::{TargetPolicy, TimingMode, SIM_TICK_SECS};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// One army: groups of `count` copies of a unit.
+#[derive(Debug, Clone)]
+pub struct BattleSide<'a> {
+    pub name: String,
+    pub policy: TargetPolicy,
+    pub groups: Vec<(&'a UnitSummary, u32)>,
+}
+
+/// Battle settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BattleOptions {
+    /// Starting distance; None starts at the longest weapon range in the battle.
+    pub start_range: Option<f64>,
+    pub horizon_sec: f64,
+    /// Damage multiplier by Defense.ArmorType (lowercase); unlisted types take full damage.
+    pub armor: BTreeMap<String, f64>,
+    /// Scheduler timing for every group's fire timeline.
+    pub timing: TimingMode,
+}
+
+/// Outcome of one group.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupReport {
+    pub unit_id: String,
+    pub count: u32,
+    pub survivors: u32,
+    /// Health left on the survivors.
+    pub health_left: f64,
+    /// Damage the group's volleys removed from enemy health and shields.
+    pub damage_dealt: f64,
+    /// Damage the group's volleys wasted on overkill.
+    pub damage_wasted: f64,
+    pub volleys_fired: u32,
+    /// When the group lost its first unit (None when it lost none).
+    pub first_loss_sec: Option<f64>,
+}
+
+/// Outcome of one army.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArmyReport {
+    pub name: String,
+    pub policy: TargetPolicy,
+    pub groups: Vec<GroupReport>,
+}
+
+impl ArmyReport {
+    pub fn units(&self) -> u32 {
+        self.groups.iter().map(|g| g.count).sum()
+    }
+
+    pub fn survivors(&self) -> u32 {
+        self.groups.iter().map(|g| g.survivors).sum()
+    }
+
+    pub fn damage_dealt(&self) -> f64 {
+        self.groups.iter().fold(0.0, |sum, g| sum + g.damage_dealt)
+    }
+
+    pub fn damage_wasted(&self) -> f64 {
+        self.groups.iter().fold(0.0, |sum, g| sum + g.damage_wasted)
+    }
+}
+
+/// Units alive per group (in scenario order) at one second of the battle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BattleSample {
+    pub time_sec: f64,
+    pub a_alive: Vec<u32>,
+    pub b_alive: Vec<u32>,
+}
+
+/// Result of a battle, with losses over time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BattleResult {
+    pub start_range: f64,
+    pub outcome: DuelOutcome,
+    /// Time the last unit of the losing army died, or the horizon on timeout.
+    pub end_time_sec: f64,
+    pub a: ArmyReport,
+    pub b: ArmyReport,
+    pub trace: Vec<BattleSample>,
+}
+
+/// One weapon of a group: its scheduled volleys relative to engagement.
+struct GroupWeapon {
+    min_range: f64,
+    range: f64,
+    damage: f64,
+    shield_blocked: bool,
+    /// (fire time, flight time) from the scheduler, in fire order.
+    volleys: Vec<(f64, f64)>,
+    engaged_at: Option<f64>,
+    next: usize,
+}
+
+struct Group {
+    side: usize,
+    unit_id: String,
+    position: f64,
+    speed: f64,
+    reach: f64,
+    max_health: f64,
+    regen_rate: f64,
+    armor_multiplier: f64,
+    weapons: Vec<GroupWeapon>,
+    members: Vec<Member>,
+    damage_dealt: f64,
+    damage_wasted: f64,
+    volleys_fired: u32,
+    first_loss_sec: Option<f64>,
+}
+
+impl Group {
+    fn new(
+        side: usize,
+        u: &UnitSummary,
+        count: u32,
+        position: f64,
+        horizon: f64,
+        timing: TimingMode,
+        armor: &BTreeMap<String, f64>,
+    ) -> Self {
+        let (weapons, schedule) = unit_schedule(u, horizon, timing);
+        Self {
+            side,
+            unit_id: u.unit_id.id.clone(),
+            position,
+            speed: u.stats.max_speed.unwrap_or(0.0).max(0.0),
+            reach: reach(u),
+            max_health: u.stats.max_health.unwrap_or(0.0).max(0.0),
+            regen_rate: u.stats.regen_rate.unwrap_or(0.0).max(0.0),
+            armor_multiplier: armor_multiplier(u, armor),
+            weapons: weapons
+                .iter()
+                .enumerate()
+                .map(|(i, w)| GroupWeapon {
+                    min_range: w.min_range,
+                    range: w.range,
+                    damage: volley_damage(w),
+                    shield_blocked: w.blocked_by_shields(),
+                    volleys: schedule
+                        .events
+                        .iter()
+                        .filter(|e| e.weapon_index == i)
+                        .map(|e| (e.time_sec, e.impact_time_sec - e.time_sec))
+                        .collect(),
+                    engaged_at: None,
+                    next: 0,
+                })
+                .collect(),
+            members: (0..count).map(|_| Member::new(u)).collect(),
+            damage_dealt: 0.0,
+            damage_wasted: 0.0,
+            volleys_fired: 0,
+            first_loss_sec: None,
+        }
+    }
+
+    fn alive(&self) -> u32 {
+        self.members.iter().filter(|m| m.alive()).count() as u32
+    }
+}
+
+/// A volley in flight at one enemy unit.
+struct Impact {
+    time_sec: f64,
+    shooter: usize,
+    target: (usize, usize),
+    damage: f64,
+    shield_blocked: bool,
+}
+
+/// Living enemy units within the band of weapon `w` of group `from`, in scenario order.
+fn candidates(groups: &[Group], from: usize, w: &GroupWeapon) -> Vec<(usize, usize)> {
+    let origin = groups[from].position;
+    groups
+        .iter()
+        .enumerate()
+        .filter(|(_, g)| g.side != groups[from].side)
+        .filter(|(_, g)| {
+            let d = (g.position - origin).abs();
+            d <= w.range + 1e-9 && d >= w.min_range - 1e-9
+        })
+        .flat_map(|(gi, g)| {
+            g.members
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| m.alive())
+                .map(move |(mi, _)| (gi, mi))
+        })
+        .collect()
+}
+
+/// Simulate army `a` against army `b`.
+pub fn battle(a: &BattleSide, b: &BattleSide, opts: &BattleOptions) -> BattleResult {
+    let horizon = opts.horizon_sec.max(0.0);
+    let start_range = opts.start_range.unwrap_or_else(|| {
+        a.groups
+            .iter()
+            .chain(b.groups.iter())
+            .map(|(u, _)| reach(u))
+            .fold(0.0, f64::max)
+    });
+    let mut groups: Vec<Group> = Vec::new();
+    for (side, army, position) in [(0, a, 0.0), (1, b, start_range)] {
+        for (u, count) in &army.groups {
+            groups.push(Group::new(
+                side,
+                u,
+                *count,
+                position,
+                horizon,
+                opts.timing,
+                &opts.armor,
+            ));
+        }
+    }
+    let policies = [a.policy, b.policy];
+    let mut rotation = [0usize; 2];
+    let mut in_flight: Vec<Impact> = Vec::new();
+    let mut trace = Vec::new();
+    let side_alive = |groups: &[Group], side: usize| -> u32 {
+        groups
+            .iter()
+            .filter(|g| g.side == side)
+            .map(Group::alive)
+            .sum()
+    };
+    let snapshot = |groups: &[Group], time_sec: f64| BattleSample {
+        time_sec,
+        a_alive: groups
+            .iter()
+            .filter(|g| g.side == 0)
+            .map(Group::alive)
+            .collect(),
+        b_alive: groups
+            .iter()
+            .filter(|g| g.side == 1)
+            .map(Group::alive)
+            .collect(),
+    };
+
+    let ticks_per_sec = (1.0 / SIM_TICK_SECS).round() as u64;
+    let mut tick: u64 = 0;
+    let mut end: Option<f64> = None;
+    if side_alive(&groups, 0) == 0 || side_alive(&groups, 1) == 0 {
+        end = Some(0.0);
+    }
+    while end.is_none() {
+        let now = tick as f64 * SIM_TICK_SECS;
+        if now > horizon {
+            break;
+        }
+        if tick.is_multiple_of(ticks_per_sec) {
+            trace.push(snapshot(&groups, now));
+        }
+
+        // Movement toward the nearest living enemy group, stopping at weapon reach.
+        let positions: Vec<(usize, f64, bool)> = groups
+            .iter()
+            .map(|g| (g.side, g.position, g.alive() > 0))
+            .collect();
+        for g in groups.iter_mut().filter(|g| g.alive() > 0 && g.speed > 0.0) {
+            let nearest = positions
+                .iter()
+                .filter(|(side, _, alive)| *side != g.side && *alive)
+                .map(|(_, p, _)| (p - g.position).abs())
+                .fold(f64::INFINITY, f64::min);
+            if nearest.is_finite() && nearest > g.reach {
+                let step = (g.speed * SIM_TICK_SECS).min(nearest - g.reach);
+                g.position += if g.side == 0 { step } else { -step };
+            }
+        }
+
+        // Regeneration since the previous tick.
+        if tick > 0 {
+            for g in groups.iter_mut() {
+                let (regen, max) = (g.regen_rate, g.max_health);
+                for m in g.members.iter_mut().filter(|m| m.alive()) {
+                    m.health = (m.health + regen * SIM_TICK_SECS).min(max);
+                    if let Some(s) = m.shield.as_mut() {
+                        s.advance(now, SIM_TICK_SECS);
+                    }
+                }
+            }
+        }
+
+        // Fires due by this tick; every living member of the group fires the volley.
+        for gi in 0..groups.len() {
+            let shooters = groups[gi].alive();
+            if shooters == 0 {
+                continue;
+            }
+            let side = groups[gi].side;
+            for wi in 0..groups[gi].weapons.len() {
+                let in_range = candidates(&groups, gi, &groups[gi].weapons[wi]);
+                let weakest = in_range
+                    .iter()
+                    .min_by(|x, y| {
+                        let d = |(g, m): (usize, usize)| groups[g].members[m].durability();
+                        d(**x).total_cmp(&d(**y))
+                    })
+                    .copied();
+                let w = &mut groups[gi].weapons[wi];
+                if w.engaged_at.is_none() && !in_range.is_empty() {
+                    w.engaged_at = Some(now);
+                }
+                let Some(t0) = w.engaged_at else {
+                    continue;
+                };
+                let mut fired = 0;
+                while let Some(&(t, flight)) = w.volleys.get(w.next) {
+                    if t0 + t > now + 1e-9 {
+                        break;
+                    }
+                    w.next += 1;
+                    let Some(weakest) = weakest else {
+                        continue;
+                    };
+                    for _ in 0..shooters {
+                        let target = match policies[side] {
+                            TargetPolicy::FocusFire => in_range[0],
+                            TargetPolicy::Spread => {
+                                rotation[side] += 1;
+                                in_range[(rotation[side] - 1) % in_range.len()]
+                            }
+                            TargetPolicy::LowestHp => weakest,
+                        };
+                        in_flight.push(Impact {
+                            time_sec: t0 + t + flight,
+                            shooter: gi,
+                            target,
+                            damage: w.damage,
+                            shield_blocked: w.shield_blocked,
+                        });
+                        fired += 1;
+                    }
+                }
+                groups[gi].volleys_fired += fired;
+            }
+        }
+
+        // Impacts due by this tick, in time order.
+        in_flight.sort_by(|x, y| x.time_sec.total_cmp(&y.time_sec));
+        let due = in_flight.partition_point(|i| i.time_sec <= now + 1e-9);
+        for hit in in_flight.drain(..due).collect::<Vec<_>>() {
+            let (tg, tm) = hit.target;
+            let mult = groups[tg].armor_multiplier;
+            let m = &mut groups[tg].members[tm];
+            let absorbed = if m.alive() && hit.shield_blocked {
+                m.shield.as_mut().and_then(|s| s.absorb(hit.damage, now))
+            } else {
+                None
+            };
+            let (dealt, wasted) = if !m.alive() {
+                (0.0, hit.damage)
+            } else if let Some(absorbed) = absorbed {
+                // A collapsing shield takes the whole hit; what it had no health left for is waste.
+                (absorbed, hit.damage - absorbed)
+            } else {
+                let damage = hit.damage * mult;
+                let dealt = damage.min(m.health);
+                m.health -= dealt;
+                (dealt, damage - dealt)
+            };
+            if !groups[tg].members[tm].alive() && dealt > 0.0 {
+                groups[tg].first_loss_sec.get_or_insert(now);
+            }
+            groups[hit.shooter].damage_dealt += dealt;
+            groups[hit.shooter].damage_wasted += wasted;
+        }
+
+        if side_alive(&groups, 0) == 0 || side_alive(&groups, 1) == 0 {
+            end = Some(now);
+        }
+        tick += 1;
+    }
+
+    let end_time_sec = end.unwrap_or(horizon);
+    // A sample at the end time was taken before that tick's impacts; replace it.
+    trace.retain(|s| s.time_sec < end_time_sec);
+    trace.push(snapshot(&groups, end_time_sec));
+    let outcome = match (side_alive(&groups, 0), side_alive(&groups, 1)) {
+        (0, 0) => DuelOutcome::Draw,
+        (_, 0) => DuelOutcome::A,
+        (0, _) => DuelOutcome::B,
+        _ => DuelOutcome::Timeout,
+    };
+    let report = |side: usize, army: &BattleSide| ArmyReport {
+        name: army.name.clone(),
+        policy: army.policy,
+        groups: groups
+            .iter()
+            .filter(|g| g.side == side)
+            .map(|g| GroupReport {
+                unit_id: g.unit_id.clone(),
+                count: g.members.len() as u32,
+                survivors: g.alive(),
+                health_left: g
+                    .members
+                    .iter()
+                    .filter(|m| m.alive())
+                    .fold(0.0, |sum, m| sum + m.health),
+                damage_dealt: g.damage_dealt,
+                damage_wasted: g.damage_wasted,
+                volleys_fired: g.volleys_fired,
+                first_loss_sec: g.first_loss_sec,
+            })
+            .collect(),
+    };
+    BattleResult {
+        start_range,
+        outcome,
+        end_time_sec,
+        a: report(0, a),
+        b: report(1, b),
+        trace,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ShieldStats, UnitStats, WeaponDeclared, WeaponEffective};
+
+    /// Unit with `damage` per second at `range` (no weapon when zero), instant flight.
+    fn unit(id: &str, damage: f64, range: f64, speed: f64, health: f64) -> UnitSummary {
+        let gun = (
+            WeaponDeclared {
+                weapon_bp_id: "Gun".to_string(),
+                damage,
+                projectiles_per_fire: 1,
+                rate_of_fire: 1.0,
+                range,
+                ..Default::default()
+            },
+            WeaponEffective {
+                weapon_bp_id: "Gun".to_string(),
+                effective_dps: damage,
+                cycle_time_sec: 1.0,
+                shots_per_cycle: 1,
+                ..Default::default()
+            },
+        );
+        let stats = UnitStats {
+            max_speed: Some(speed),
+            max_health: Some(health),
+            ..Default::default()
+        };
+        UnitSummary::for_test(
+            id,
+            (damage > 0.0).then_some(gun).into_iter().collect(),
+            stats,
+        )
+    }
+
+    fn opts() -> BattleOptions {
+        BattleOptions {
+            start_range: Some(20.0),
+            horizon_sec: 600.0,
+            armor: BTreeMap::new(),
+            timing: TimingMode::Continuous,
+        }
+    }
+
+    fn side<'a>(policy: TargetPolicy, groups: Vec<(&'a UnitSummary, u32)>) -> BattleSide<'a> {
+        BattleSide {
+            name: String::new(),
+            policy,
+            groups,
+        }
+    }
+
+    #[test]
+    fn focus_fire_wastes_overkill_that_spread_avoids() {
+        let gun = unit("gun", 10.0, 20.0, 0.0, 100.0);
+        let target = unit("target", 0.0, 0.0, 0.0, 15.0);
+        let run = |policy| {
+            battle(
+                &side(policy, vec![(&gun, 3)]),
+                &side(TargetPolicy::FocusFire, vec![(&target, 3)]),
+                &opts(),
+            )
+        };
+        // Three 10-damage volleys a second into one 15 HP unit: 15 of 30 wasted each second.
+        let focus = run(TargetPolicy::FocusFire);
+        assert_eq!(focus.outcome, DuelOutcome::A);
+        assert_eq!(focus.end_time_sec, 2.0);
+        assert_eq!(focus.a.damage_dealt(), 45.0);
+        assert_eq!(focus.a.damage_wasted(), 45.0);
+        assert_eq!(focus.b.groups[0].first_loss_sec, Some(0.0));
+        // One volley per target each second: only the second round overkills, by 5 each.
+        let spread = run(TargetPolicy::Spread);
+        assert_eq!(spread.end_time_sec, 1.0);
+        assert_eq!(spread.a.damage_wasted(), 15.0);
+        assert_eq!(spread.a.groups[0].volleys_fired, 6);
+    }
+
+    #[test]
+    fn lowest_hp_picks_off_the_weak_group_first() {
+        let gun = unit("gun", 10.0, 20.0, 0.0, 100.0);
+        let big = unit("big", 0.0, 0.0, 0.0, 100.0);
+        let small = unit("small", 0.0, 0.0, 0.0, 10.0);
+        let run = |policy| {
+            battle(
+                &side(policy, vec![(&gun, 1)]),
+                &side(TargetPolicy::FocusFire, vec![(&big, 1), (&small, 1)]),
+                &opts(),
+            )
+        };
+        let focus = run(TargetPolicy::FocusFire);
+        assert_eq!(focus.b.groups[0].first_loss_sec, Some(9.0));
+        assert_eq!(focus.b.groups[1].first_loss_sec, Some(10.0));
+        let lowest = run(TargetPolicy::LowestHp);
+        assert_eq!(lowest.b.groups[1].first_loss_sec, Some(0.0));
+        assert_eq!(lowest.b.groups[0].first_loss_sec, Some(10.0));
+        assert_eq!(lowest.trace[1].b_alive, vec![1, 0]);
+    }
+
+    #[test]
+    fn damage_past_a_collapsing_shield_is_wasted() {
+        let gun = unit("gun", 10.0, 20.0, 0.0, 100.0);
+        let mut shielded = unit("shielded", 0.0, 0.0, 0.0, 15.0);
+        shielded.stats.shield = Some(ShieldStats {
+            max_health: 12.0,
+            regen_rate: 0.0,
+            recharge_time: 100.0,
+            size: 10.0,
+        });
+        let r = battle(
+            &side(TargetPolicy::FocusFire, vec![(&gun, 1)]),
+            &side(TargetPolicy::FocusFire, vec![(&shielded, 1)]),
+            &opts(),
+        );
+        // 10 into the shield, 2 collapsing it (8 lost), then 10 and 5 (5 lost) into health.
+        assert_eq!(r.outcome, DuelOutcome::A);
+        assert_eq!(r.end_time_sec, 3.0);
+        assert_eq!(r.a.damage_dealt(), 27.0);
+        assert_eq!(r.a.damage_wasted(), 13.0);
+    }
+
+    #[test]
+    fn single_units_match_the_duel_model() {
+        let turret = unit("turret", 10.0, 30.0, 0.0, 100.0);
+        let raider = unit("raider", 10.0, 10.0, 5.0, 100.0);
+        let r = battle(
+            &side(TargetPolicy::FocusFire, vec![(&turret, 1)]),
+            &side(TargetPolicy::FocusFire, vec![(&raider, 1)]),
+            &BattleOptions {
+                start_range: None,
+                ..opts()
+            },
+        );
+        // Same as the duel: the raider closes 20 ogrids at 5/s and lands 6 shots before dying.
+        assert_eq!(r.start_range, 30.0);
+        assert_eq!(r.outcome, DuelOutcome::A);
+        assert_eq!(r.end_time_sec, 9.0);
+        assert_eq!(r.a.groups[0].health_left, 40.0);
+        assert_eq!(r.b.groups[0].volleys_fired, 6);
+        assert_eq!(r.trace.last().unwrap().b_alive, vec![0]);
+    }
+}
@@ -0,0 +1,142 @@
+//! Attack runs: sustained DPS of bombers, which drop a salvo and then have to come round again.
+//!
+//! After the drop the bomber flies on for its break-off distance, turns through 180° and flies
+//! back, so the re-attack interval is set by airspeed and turn rate rather than RateOfFire.
+//! Air.CombatTurnSpeed (else Air.TurnSpeed) is taken as radians per second.
+
+use super::{total_damage_per_shot, WeaponDeclared, WeaponEffective};
+use crate::parser::LuaValue;
+use serde::{Deserialize, Serialize};
+
+/// Flight fields from the unit's Air table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct AirStats {
+    /// Air.MaxAirspeed (ogrids/second).
+    pub max_airspeed: f64,
+    /// Air.TurnSpeed.
+    pub turn_speed: f64,
+    /// Air.CombatTurnSpeed: turn rate while attacking (falls back to TurnSpeed).
+    pub combat_turn_speed: Option<f64>,
+    /// Air.BreakOffDistance: how far past the target the bomber flies before turning.
+    pub break_off_distance: f64,
+    /// Air.RandomBreakOffDistanceMult: break-off distance is scaled by a random factor in 1..=mult.
+    pub random_break_off_mult: Option<f64>,
+}
+
+impl AirStats {
+    fn attack_turn_speed(&self) -> f64 {
+        self.combat_turn_speed.unwrap_or(self.turn_speed)
+    }
+
+    /// Average break-off distance (the random multiplier is uniform between 1 and its maximum).
+    fn mean_break_off(&self) -> f64 {
+        let mult = self.random_break_off_mult.unwrap_or(1.0).max(1.0);
+        self.break_off_distance.max(0.0) * (1.0 + mult) / 2.0
+    }
+}
+
+/// One bombing pass and the sustained DPS it implies.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AttackRun {
+    /// Time between drops: salvo, break-off leg out, 180° turn, leg back (or the weapon's own cycle if longer).
+    pub reattack_interval_sec: f64,
+    pub damage_per_pass: f64,
+    pub sustained_dps: f64,
+    /// DPS from RateOfFire alone, for comparison.
+    pub rof_dps: f64,
+    /// Length of ground covered by one salvo (airspeed × salvo duration).
+    pub carpet_length: f64,
+}
+
+/// Air table of a flying unit; None for units that do not fly or have no airspeed.
+pub fn air_from_lua(root: &LuaValue) -> Option<AirStats> {
+    let air = root.get_table("Air")?;
+    if !air.get_bool("CanFly").unwrap_or(true) {
+        return None;
+    }
+    let max_airspeed = air.get_num("MaxAirspeed").filter(|v| *v > 0.0)?;
+    Some(AirStats {
+        max_airspeed,
+        turn_speed: air.get_num("TurnSpeed").unwrap_or(0.0),
+        combat_turn_speed: air.get_num("CombatTurnSpeed"),
+        break_off_distance: air.get_num("BreakOffDistance").unwrap_or(0.0),
+        random_break_off_mult: air.get_num("RandomBreakOffDistanceMult"),
+    })
+}
+
+/// Attack run of bomb weapon `w` (with ROF-based stats `e`) on an aircraft with `air`.
+/// None for weapons that are not bombs or aircraft that cannot turn.
+pub fn attack_run(w: &WeaponDeclared, e: &WeaponEffective, air: &AirStats) -> Option<AttackRun> {
+    if !w.is_bomb() || air.attack_turn_speed() <= 0.0 {
+        return None;
+    }
+    let legs = 2.0 * air.mean_break_off() / air.max_airspeed;
+    let turn = std::f64::consts::PI / air.attack_turn_speed();
+    let weapon_cycle = e.cycle_time_sec + e.salvo_duration_sec;
+    let interval = (e.salvo_duration_sec + legs + turn).max(weapon_cycle);
+    // Bombs per pass are the muzzle salvo. `projectiles_per_fire` falls back to that same
+    // MuzzleSalvoSize, so only an explicit ProjectilesPerOnFire multiplies each bomb.
+    let per_bomb = total_damage_per_shot(w) * w.projectiles_per_on_fire.unwrap_or(1).max(1) as f64;
+    let damage_per_pass = per_bomb * e.shots_per_cycle.max(1) as f64;
+    Some(AttackRun {
+        reattack_interval_sec: interval,
+        damage_per_pass,
+        sustained_dps: if interval > 0.0 {
+            damage_per_pass / interval
+        } else {
+            0.0
+        },
+        rof_dps: if weapon_cycle > 0.0 {
+            damage_per_pass / weapon_cycle
+        } else {
+            0.0
+        },
+        carpet_length: air.max_airspeed * e.salvo_duration_sec,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pass_interval_from_break_off_and_turn() {
+        let w = WeaponDeclared {
+            damage: 50.0,
+            projectiles_per_fire: 1,
+            weapon_category: Some("Bomb".to_string()),
+            ..Default::default()
+        };
+        let e = WeaponEffective {
+            cycle_time_sec: 5.0,
+            shots_per_cycle: 4,
+            salvo_duration_sec: 0.8,
+            ..Default::default()
+        };
+        let air = AirStats {
+            max_airspeed: 10.0,
+            turn_speed: 1.0,
+            combat_turn_speed: Some(std::f64::consts::PI / 2.0),
+            break_off_distance: 30.0,
+            random_break_off_mult: Some(1.0),
+        };
+        let run = attack_run(&w, &e, &air).unwrap();
+        // 0.8 s salvo + 2 × 3 s legs + 2 s turn.
+        assert!((run.reattack_interval_sec - 8.8).abs() < 1e-9);
+        assert_eq!(run.damage_per_pass, 200.0);
+        assert!((run.rof_dps - 200.0 / 5.8).abs() < 1e-9);
+        // MuzzleSalvoSize without ProjectilesPerOnFire: the salvo is not counted twice.
+        let salvo = WeaponDeclared {
+            projectiles_per_fire: 4,
+            muzzle_salvo_size: Some(4),
+            ..w.clone()
+        };
+        assert_eq!(attack_run(&salvo, &e, &air).unwrap().damage_per_pass, 200.0);
+        assert!((run.carpet_length - 8.0).abs() < 1e-9);
+        let gun = WeaponDeclared {
+            weapon_category: Some("Direct Fire".to_string()),
+            ..w
+        };
+        assert!(attack_run(&gun, &e, &air).is_none());
+    }
+}
@@ -0,0 +1,196 @@
+//! Burst profile: cumulative damage over time from the scheduler's fire timeline.
+//!
+//! One effective-DPS figure averages a heavy opening volley over the whole reload; the curve,
+//! the best N-second windows and the alpha strike show how front-loaded a unit's damage is.
+
+use super::ttk::unit_schedule;
+use super::{volley_damage, UnitSummary};
+use crate::config::TimingMode;
+use serde::{Deserialize, Serialize};
+
+/// Cumulative damage landed by `time_sec`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CurvePoint {
+    pub time_sec: f64,
+    pub damage: f64,
+}
+
+/// Most damage landed within any window of `window_sec` seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowDamage {
+    pub window_sec: f64,
+    pub max_damage: f64,
+}
+
+/// Time until cumulative damage reaches `damage` (None: not within the horizon).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DamageMilestone {
+    pub damage: f64,
+    pub time_sec: Option<f64>,
+}
+
+/// Burst metrics of one unit's combat weapons over `horizon_sec`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BurstProfile {
+    pub horizon_sec: f64,
+    /// Damage of every combat weapon's first cycle (the opening salvo), wherever it lands.
+    pub alpha_damage: f64,
+    /// Cumulative damage at each impact time, starting at (0, 0).
+    pub curve: Vec<CurvePoint>,
+    pub windows: Vec<WindowDamage>,
+    pub milestones: Vec<DamageMilestone>,
+}
+
+/// Burst profile of `u`'s combat weapons. Damage lands at impact time; windows are half-open
+/// (an event exactly `window_sec` after the first is not counted with it).
+pub fn burst_profile(
+    u: &UnitSummary,
+    horizon_sec: f64,
+    windows_sec: &[f64],
+    thresholds: &[f64],
+    timing: TimingMode,
+) -> BurstProfile {
+    if horizon_sec <= 0.0 {
+        return BurstProfile::default();
+    }
+    let (weapons, result) = unit_schedule(u, horizon_sec, timing);
+    if weapons.is_empty() {
+        return BurstProfile::default();
+    }
+
+    let mut cycles_started = vec![0u32; weapons.len()];
+    let mut alpha_damage = 0.0;
+    let mut impacts: Vec<(f64, f64)> = Vec::with_capacity(result.events.len());
+    for e in &result.events {
+        let damage = volley_damage(&weapons[e.weapon_index]);
+        if e.shot_index == 0 {
+            cycles_started[e.weapon_index] += 1;
+        }
+        if cycles_started[e.weapon_index] == 1 {
+            alpha_damage += damage;
+        }
+        if e.impact_time_sec <= horizon_sec {
+            impacts.push((e.impact_time_sec, damage));
+        }
+    }
+    impacts.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut curve = vec![CurvePoint {
+        time_sec: 0.0,
+        damage: 0.0,
+    }];
+    let mut total = 0.0;
+    for &(t, d) in &impacts {
+        total += d;
+        match curve.last_mut() {
+            Some(last) if (last.time_sec - t).abs() < 1e-9 => last.damage = total,
+            _ => curve.push(CurvePoint {
+                time_sec: t,
+                damage: total,
+            }),
+        }
+    }
+
+    let windows = windows_sec
+        .iter()
+        .filter(|w| **w > 0.0)
+        .map(|&window_sec| WindowDamage {
+            window_sec,
+            max_damage: max_window_damage(&impacts, window_sec),
+        })
+        .collect();
+    let milestones = thresholds
+        .iter()
+        .map(|&damage| DamageMilestone {
+            damage,
+            time_sec: curve
+                .iter()
+                .find(|p| p.damage >= damage)
+                .map(|p| p.time_sec),
+        })
+        .collect();
+    BurstProfile {
+        horizon_sec,
+        alpha_damage,
+        curve,
+        windows,
+        milestones,
+    }
+}
+
+/// Two-pointer sweep over impacts sorted by time.
+fn max_window_damage(impacts: &[(f64, f64)], window_sec: f64) -> f64 {
+    let mut best = 0.0f64;
+    let mut sum = 0.0;
+    let mut start = 0;
+    for &(t, d) in impacts {
+        sum += d;
+        while impacts[start].0 <= t - window_sec {
+            sum -= impacts[start].1;
+            start += 1;
+        }
+        best = best.max(sum);
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{WeaponDeclared, WeaponEffective};
+
+    fn salvo_unit() -> UnitSummary {
+        let rack = (
+            WeaponDeclared {
+                weapon_bp_id: "Rack".to_string(),
+                damage: 100.0,
+                projectiles_per_fire: 1,
+                rate_of_fire: 0.1,
+                ..Default::default()
+            },
+            WeaponEffective {
+                weapon_bp_id: "Rack".to_string(),
+                cycle_time_sec: 10.0,
+                shots_per_cycle: 4,
+                salvo_duration_sec: 2.0,
+                ..Default::default()
+            },
+        );
+        UnitSummary::for_test("burst", vec![rack], Default::default())
+    }
+
+    #[test]
+    fn alpha_windows_and_milestones() {
+        let b = burst_profile(
+            &salvo_unit(),
+            30.0,
+            &[3.0, 15.0],
+            &[400.0, 2000.0],
+            TimingMode::Continuous,
+        );
+        // Four shots 0.5 s apart, then the next salvo at 11.5 s.
+        assert_eq!(b.alpha_damage, 400.0);
+        assert_eq!(b.curve[0].damage, 100.0);
+        assert_eq!(b.windows[0].max_damage, 400.0);
+        assert_eq!(b.windows[1].max_damage, 800.0);
+        assert_eq!(b.milestones[0].time_sec, Some(1.5));
+        assert_eq!(b.milestones[1].time_sec, None);
+        assert_eq!(b.curve.last().unwrap().damage, 1200.0);
+    }
+
+    #[test]
+    fn tick_timing_reaches_the_scheduler() {
+        let mut u = salvo_unit();
+        u.effective[0].cycle_time_sec = 1.0 / 15.0;
+        u.effective[0].shots_per_cycle = 1;
+        u.effective[0].salvo_duration_sec = 0.0;
+        let damage = |timing| {
+            let b = burst_profile(&u, 1.0, &[1.0], &[], timing);
+            b.windows[0].max_damage
+        };
+        // RateOfFire 15 runs as one shot per 0.1 s tick on ticks.
+        assert!(damage(TimingMode::Ticks) < damage(TimingMode::Continuous));
+        assert_eq!(damage(TimingMode::Ticks), 1000.0);
+    }
+}
@@ -0,0 +1,92 @@
+//! Defensive weapons: anti-missile, TMD and anti-torpedo launchers rated by interceptions per minute.
+//!
+//! Each projectile fired can stop one incoming missile or torpedo, so the rate is the weapon's
+//! projectiles per cycle over its cycle time (ROF or reload, plus salvo duration).
+
+use super::{effective_dps, InterceptTarget, WeaponDeclared, WeaponRole};
+use crate::config::{InterceptThreat, TimingMode};
+use serde::{Deserialize, Serialize};
+
+/// One defensive weapon of a unit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interceptor {
+    pub weapon_bp_id: String,
+    pub target: InterceptTarget,
+    /// Projectiles the weapon can shoot down per minute of sustained fire.
+    pub interceptions_per_min: f64,
+    /// MaxRadius: how far from the unit incoming projectiles are engaged.
+    pub range: f64,
+}
+
+impl Interceptor {
+    /// Whether the weapon fires fast enough to stop every projectile of `threat`.
+    pub fn keeps_up_with(&self, threat: &InterceptThreat) -> bool {
+        self.interceptions_per_min >= threat.required_per_min()
+    }
+}
+
+/// Interceptions per minute of one weapon: effective DPS at one damage per projectile, times 60.
+pub fn interceptions_per_min(w: &WeaponDeclared, timing: TimingMode) -> f64 {
+    60.0 * effective_dps(
+        1.0,
+        w.projectiles_per_fire,
+        w.rate_of_fire,
+        w.reload_time,
+        w.salvo_size,
+        w.salvo_delay,
+        timing,
+    )
+}
+
+/// The defensive weapons among `weapons`, in blueprint order.
+pub fn interceptors(weapons: &[WeaponDeclared], timing: TimingMode) -> Vec<Interceptor> {
+    weapons
+        .iter()
+        .filter(|w| w.role == WeaponRole::Defensive)
+        .map(|w| Interceptor {
+            weapon_bp_id: w.weapon_bp_id.clone(),
+            target: w.intercepts.unwrap_or(InterceptTarget::Projectile),
+            interceptions_per_min: interceptions_per_min(w, timing),
+            range: w.range,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tmd(rate_of_fire: f64) -> WeaponDeclared {
+        WeaponDeclared {
+            weapon_bp_id: "AntiMissile".to_string(),
+            rate_of_fire,
+            projectiles_per_fire: 1,
+            range: 30.0,
+            role: WeaponRole::Defensive,
+            intercepts: Some(InterceptTarget::Missile),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rate_and_saturation_against_threat() {
+        let weapons = vec![
+            tmd(0.5),
+            WeaponDeclared {
+                weapon_bp_id: "MainGun".to_string(),
+                rate_of_fire: 1.0,
+                projectiles_per_fire: 1,
+                ..Default::default()
+            },
+        ];
+        let found = interceptors(&weapons, TimingMode::Continuous);
+        assert_eq!(found.len(), 1);
+        assert!((found[0].interceptions_per_min - 30.0).abs() < 1e-9);
+        let threat = InterceptThreat {
+            salvo_size: 4,
+            window_secs: 10.0,
+        };
+        assert!(found[0].keeps_up_with(&threat));
+        assert!(!interceptors(&[tmd(0.2)], TimingMode::Continuous)[0].keeps_up_with(&threat));
+    }
+}
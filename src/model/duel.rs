@@ -0,0 +1,449 @@
+//! Duel: two groups close to range and fire on each other until one side is destroyed.
+//!
+//! Both sides start `start_range` apart and advance at their max speed until the distance is
+//! within their longest combat weapon's MaxRadius, then hold. Each weapon starts its scheduler
+//! timeline when the distance first falls within its MaxRadius, and skips shots fired inside its
+//! MinRadius. As in the TTK model, every shot hits; damage lands at impact time.
+//!
+//! Each side focuses fire: volleys go to its first surviving unit, and damage beyond what kills
+//! it is lost. A unit's own shield absorbs shield-blocked damage first. Units regenerate health
+//! between events. Dead units stop firing; their shots already in flight still land, as do
+//! shots fired at the instant of death.
+
+use super::ttk::{armor_multiplier, reach, unit_schedule, Member};
+use super::{volley_damage, UnitSummary};
+use crate::config::TimingMode;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// One side of a duel: `count` copies of `unit`.
+#[derive(Debug, Clone, Copy)]
+pub struct DuelSide<'a> {
+    pub unit: &'a UnitSummary,
+    pub count: u32,
+}
+
+/// Duel settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuelOptions {
+    /// Starting distance; None starts at the longer of the two sides' reach.
+    pub start_range: Option<f64>,
+    /// The duel is a timeout when neither side is destroyed by then.
+    pub horizon_sec: f64,
+    /// Damage multiplier by Defense.ArmorType (lowercase). Armor definitions live in game
+    /// script, not blueprints; unlisted types take full damage.
+    pub armor: BTreeMap<String, f64>,
+    /// Scheduler timing for both sides' fire timelines.
+    pub timing: TimingMode,
+}
+
+/// How a duel ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuelOutcome {
+    /// Side A destroyed side B.
+    A,
+    /// Side B destroyed side A.
+    B,
+    /// Both sides lost their last unit at the same instant.
+    Draw,
+    /// Both sides still stand at the horizon.
+    Timeout,
+}
+
+/// A side's state at one instant.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SideSnapshot {
+    pub alive: u32,
+    /// Health of the surviving units.
+    pub health: f64,
+    /// Shield health of the surviving units (zero while collapsed).
+    pub shield_health: f64,
+}
+
+/// One entry of the per-second trace (plus a final entry at the end of the duel).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DuelSample {
+    pub time_sec: f64,
+    pub distance: f64,
+    pub a: SideSnapshot,
+    pub b: SideSnapshot,
+}
+
+/// Result of a duel, with the per-second trace.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuelResult {
+    pub unit_a: String,
+    pub unit_b: String,
+    pub count_a: u32,
+    pub count_b: u32,
+    pub start_range: f64,
+    pub outcome: DuelOutcome,
+    /// Time of the last kill, or the horizon on timeout.
+    pub end_time_sec: f64,
+    /// When each side's first shot was fired (None if it never fired).
+    pub first_shot_a_sec: Option<f64>,
+    pub first_shot_b_sec: Option<f64>,
+    pub a: SideSnapshot,
+    pub b: SideSnapshot,
+    pub trace: Vec<DuelSample>,
+}
+
+/// Distance over time: piecewise linear segments `(start_sec, start_distance, closing_speed)`.
+struct Approach {
+    segments: Vec<(f64, f64, f64)>,
+}
+
+impl Approach {
+    /// Each side closes at `speed` while the distance exceeds its `reach`.
+    fn new(start: f64, sides: [(f64, f64); 2]) -> Self {
+        let mut segments = Vec::new();
+        let (mut t, mut d) = (0.0, start.max(0.0));
+        loop {
+            let advancing: Vec<(f64, f64)> = sides
+                .iter()
+                .copied()
+                .filter(|&(speed, reach)| speed > 0.0 && d > reach)
+                .collect();
+            let v: f64 = advancing.iter().map(|(speed, _)| speed).sum();
+            segments.push((t, d, v));
+            if v <= 0.0 {
+                return Self { segments };
+            }
+            let stop = advancing
+                .iter()
+                .map(|(_, r)| r.max(0.0))
+                .fold(0.0, f64::max);
+            t += (d - stop) / v;
+            d = stop;
+        }
+    }
+
+    fn distance_at(&self, t: f64) -> f64 {
+        let &(t0, d0, v) = self
+            .segments
+            .iter()
+            .rev()
+            .find(|(t0, _, _)| *t0 <= t)
+            .unwrap_or(&self.segments[0]);
+        d0 - v * (t - t0)
+    }
+
+    /// First time the distance is at most `range`, or None if it never closes that far.
+    fn time_within(&self, range: f64) -> Option<f64> {
+        for (i, &(t0, d0, v)) in self.segments.iter().enumerate() {
+            if d0 <= range {
+                return Some(t0);
+            }
+            let end = self.segments.get(i + 1).map(|s| s.1);
+            match end {
+                Some(d1) if d1 <= range => return Some(t0 + (d0 - range) / v),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// A volley in the merged timeline of both sides.
+struct Shot {
+    fire_sec: f64,
+    impact_sec: f64,
+    side: usize,
+    damage: f64,
+    shield_blocked: bool,
+}
+
+/// Shots of one side: its scheduler timeline, each weapon offset to when it comes into range.
+fn side_shots(
+    side: usize,
+    u: &UnitSummary,
+    approach: &Approach,
+    horizon: f64,
+    timing: TimingMode,
+) -> Vec<Shot> {
+    let (weapons, schedule) = unit_schedule(u, horizon, timing);
+    let engage: Vec<Option<f64>> = weapons
+        .iter()
+        .map(|w| approach.time_within(w.range))
+        .collect();
+    schedule
+        .events
+        .iter()
+        .filter_map(|e| {
+            let w = &weapons[e.weapon_index];
+            let t0 = engage[e.weapon_index]?;
+            let fire_sec = t0 + e.time_sec;
+            (fire_sec <= horizon && approach.distance_at(fire_sec) >= w.min_range).then(|| Shot {
+                fire_sec,
+                impact_sec: t0 + e.impact_time_sec,
+                side,
+                damage: volley_damage(w),
+                shield_blocked: w.blocked_by_shields(),
+            })
+        })
+        .collect()
+}
+
+/// Health of one side's units, advanced lazily between events.
+struct Fleet {
+    members: Vec<Member>,
+    max_health: f64,
+    regen_rate: f64,
+    armor_multiplier: f64,
+    at_sec: f64,
+}
+
+impl Fleet {
+    fn new(u: &UnitSummary, count: u32, armor: &BTreeMap<String, f64>) -> Self {
+        Self {
+            members: (0..count).map(|_| Member::new(u)).collect(),
+            max_health: u.stats.max_health.unwrap_or(0.0).max(0.0),
+            regen_rate: u.stats.regen_rate.unwrap_or(0.0).max(0.0),
+            armor_multiplier: armor_multiplier(u, armor),
+            at_sec: 0.0,
+        }
+    }
+
+    fn alive(&self) -> u32 {
+        self.members.iter().filter(|m| m.alive()).count() as u32
+    }
+
+    fn advance(&mut self, now: f64) {
+        let dt = now - self.at_sec;
+        if dt <= 0.0 {
+            return;
+        }
+        self.at_sec = now;
+        for m in self.members.iter_mut().filter(|m| m.alive()) {
+            m.health = (m.health + self.regen_rate * dt).min(self.max_health);
+            if let Some(s) = m.shield.as_mut() {
+                s.advance(now, dt);
+            }
+        }
+    }
+
+    /// One volley on the first surviving unit.
+    fn hit(&mut self, damage: f64, shield_blocked: bool, now: f64) {
+        let mult = self.armor_multiplier;
+        let Some(m) = self.members.iter_mut().find(|m| m.alive()) else {
+            return;
+        };
+        if let Some(s) = m.shield.as_mut() {
+            if shield_blocked && s.absorb(damage, now).is_some() {
+                return;
+            }
+        }
+        m.health = (m.health - damage * mult).max(0.0);
+    }
+
+    fn snapshot(&self) -> SideSnapshot {
+        let alive = self.members.iter().filter(|m| m.alive());
+        SideSnapshot {
+            alive: self.alive(),
+            health: alive.clone().fold(0.0, |sum, m| sum + m.health),
+            shield_health: alive
+                .filter_map(|m| m.shield.as_ref())
+                .fold(0.0, |sum, s| sum + s.health()),
+        }
+    }
+}
+
+/// Trace entry at `t` (no events between the fleets' last update and `t`).
+fn sample(t: f64, fleets: &mut [Fleet; 2], approach: &Approach) -> DuelSample {
+    fleets.iter_mut().for_each(|f| f.advance(t));
+    DuelSample {
+        time_sec: t,
+        distance: approach.distance_at(t),
+        a: fleets[0].snapshot(),
+        b: fleets[1].snapshot(),
+    }
+}
+
+/// Simulate side `a` against side `b`.
+pub fn duel(a: DuelSide, b: DuelSide, opts: &DuelOptions) -> DuelResult {
+    let horizon = opts.horizon_sec.max(0.0);
+    let start_range = opts
+        .start_range
+        .unwrap_or_else(|| reach(a.unit).max(reach(b.unit)));
+    let approach = Approach::new(
+        start_range,
+        [a, b].map(|s| (s.unit.stats.max_speed.unwrap_or(0.0), reach(s.unit))),
+    );
+    let mut shots = side_shots(0, a.unit, &approach, horizon, opts.timing);
+    shots.extend(side_shots(1, b.unit, &approach, horizon, opts.timing));
+    shots.sort_by(|x, y| x.fire_sec.total_cmp(&y.fire_sec).then(x.side.cmp(&y.side)));
+    let first_shot = |side: usize| shots.iter().find(|s| s.side == side).map(|s| s.fire_sec);
+    let (first_shot_a_sec, first_shot_b_sec) = (first_shot(0), first_shot(1));
+
+    let mut fleets = [
+        Fleet::new(a.unit, a.count, &opts.armor),
+        Fleet::new(b.unit, b.count, &opts.armor),
+    ];
+    let mut trace = Vec::new();
+    let mut next_sample = 0.0;
+    // Impacts in flight, ordered by time: (impact_sec, target side, damage, shield_blocked).
+    let mut in_flight: Vec<(f64, usize, f64, bool)> = Vec::new();
+    let mut fires = shots.iter().peekable();
+    // A side without health (or without units) is destroyed before the first shot.
+    let mut end = fleets.iter().any(|f| f.alive() == 0).then_some(0.0);
+    loop {
+        let next_impact = in_flight.first().map(|i| i.0);
+        let next_fire = fires.peek().map(|s| s.fire_sec);
+        // Fires at an instant go out before its impacts land, so neither side shoots first on ties.
+        let (now, is_impact) = match (next_impact, next_fire) {
+            (Some(i), Some(f)) if i < f => (i, true),
+            (Some(i), None) => (i, true),
+            (_, Some(f)) => (f, false),
+            (None, None) => break,
+        };
+        if now > horizon || end.is_some_and(|e| now > e) {
+            break;
+        }
+        while next_sample < now {
+            trace.push(sample(next_sample, &mut fleets, &approach));
+            next_sample += 1.0;
+        }
+        fleets.iter_mut().for_each(|f| f.advance(now));
+        if is_impact {
+            let (_, target, damage, blocked) = in_flight.remove(0);
+            fleets[target].hit(damage, blocked, now);
+            if fleets.iter().any(|f| f.alive() == 0) {
+                end = Some(now);
+            }
+        } else {
+            let s = fires.next().expect("peeked");
+            // Every surviving unit fires the volley in sync; each lands separately.
+            for _ in 0..fleets[s.side].alive() {
+                let pos = in_flight.partition_point(|i| i.0 <= s.impact_sec);
+                in_flight.insert(pos, (s.impact_sec, 1 - s.side, s.damage, s.shield_blocked));
+            }
+        }
+    }
+
+    let end_time_sec = end.unwrap_or(horizon);
+    while next_sample <= end_time_sec {
+        trace.push(sample(next_sample, &mut fleets, &approach));
+        next_sample += 1.0;
+    }
+    if trace.last().is_some_and(|s| s.time_sec < end_time_sec) {
+        trace.push(sample(end_time_sec, &mut fleets, &approach));
+    }
+    let outcome = match (fleets[0].alive(), fleets[1].alive()) {
+        (0, 0) => DuelOutcome::Draw,
+        (_, 0) => DuelOutcome::A,
+        (0, _) => DuelOutcome::B,
+        _ => DuelOutcome::Timeout,
+    };
+    DuelResult {
+        unit_a: a.unit.unit_id.id.clone(),
+        unit_b: b.unit.unit_id.id.clone(),
+        count_a: a.count,
+        count_b: b.count,
+        start_range,
+        outcome,
+        end_time_sec,
+        first_shot_a_sec,
+        first_shot_b_sec,
+        a: fleets[0].snapshot(),
+        b: fleets[1].snapshot(),
+        trace,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ShieldStats, UnitStats, WeaponDeclared, WeaponEffective};
+
+    /// Unit with one gun: 10 damage per second at `range`, instant flight.
+    fn unit(id: &str, range: f64, speed: f64, health: f64) -> UnitSummary {
+        let gun = (
+            WeaponDeclared {
+                weapon_bp_id: "Gun".to_string(),
+                damage: 10.0,
+                projectiles_per_fire: 1,
+                rate_of_fire: 1.0,
+                range,
+                ..Default::default()
+            },
+            WeaponEffective {
+                weapon_bp_id: "Gun".to_string(),
+                effective_dps: 10.0,
+                cycle_time_sec: 1.0,
+                shots_per_cycle: 1,
+                ..Default::default()
+            },
+        );
+        let stats = UnitStats {
+            max_speed: Some(speed),
+            max_health: Some(health),
+            ..Default::default()
+        };
+        UnitSummary::for_test(id, vec![gun], stats)
+    }
+
+    fn opts() -> DuelOptions {
+        DuelOptions {
+            start_range: None,
+            horizon_sec: 600.0,
+            armor: BTreeMap::new(),
+            timing: TimingMode::Continuous,
+        }
+    }
+
+    fn side(unit: &UnitSummary, count: u32) -> DuelSide<'_> {
+        DuelSide { unit, count }
+    }
+
+    #[test]
+    fn outranged_side_closes_before_firing() {
+        let turret = unit("turret", 30.0, 0.0, 100.0);
+        let raider = unit("raider", 10.0, 5.0, 100.0);
+        let r = duel(side(&turret, 1), side(&raider, 1), &opts());
+        assert_eq!(r.start_range, 30.0);
+        assert_eq!(r.first_shot_a_sec, Some(0.0));
+        // 20 ogrids to close at 5/s.
+        assert_eq!(r.first_shot_b_sec, Some(4.0));
+        // Shots at 0..=9 kill the raider at 9 s; it has landed 6 shots by then.
+        assert_eq!(r.outcome, DuelOutcome::A);
+        assert_eq!(r.end_time_sec, 9.0);
+        assert_eq!(r.a.health, 40.0);
+        assert_eq!(r.trace[2].distance, 20.0);
+        assert_eq!(r.trace.last().unwrap().time_sec, 9.0);
+    }
+
+    #[test]
+    fn mirror_is_a_draw_and_numbers_win() {
+        let tank = unit("tank", 20.0, 3.0, 50.0);
+        let r = duel(side(&tank, 1), side(&tank, 1), &opts());
+        assert_eq!(r.outcome, DuelOutcome::Draw);
+        assert_eq!(r.end_time_sec, 4.0);
+
+        let r = duel(side(&tank, 2), side(&tank, 3), &opts());
+        assert_eq!(r.outcome, DuelOutcome::B);
+        assert_eq!(r.b.alive, 2);
+    }
+
+    #[test]
+    fn shield_and_armor_reduce_damage_taken() {
+        let gun = unit("gun", 20.0, 0.0, 50.0);
+        let mut shielded = gun.clone();
+        shielded.unit_id.id = "shielded".to_string();
+        shielded.stats.armor_type = Some("Heavy".to_string());
+        shielded.stats.shield = Some(ShieldStats {
+            max_health: 30.0,
+            regen_rate: 0.0,
+            recharge_time: 100.0,
+            size: 10.0,
+        });
+        let mut o = opts();
+        o.armor.insert("heavy".to_string(), 0.5);
+        let r = duel(side(&gun, 1), side(&shielded, 1), &o);
+        // Shots at 0-2 collapse the shield; those at 3 and 4 do half damage before the gun dies.
+        assert_eq!(r.outcome, DuelOutcome::B);
+        assert_eq!(r.end_time_sec, 4.0);
+        assert_eq!(r.b.health, 40.0);
+        assert_eq!(r.b.shield_health, 0.0);
+    }
+}
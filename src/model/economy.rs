@@ -0,0 +1,68 @@
+//! Cost efficiency: DPS and effective HP per unit of Economy cost.
+
+use super::{TtkTarget, UnitStats};
+use serde::{Deserialize, Serialize};
+
+/// Output per unit of build cost. A field is None when the matching cost is missing or zero.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CostEfficiency {
+    /// Summed effective DPS per BuildCostMass.
+    pub dps_per_mass: Option<f64>,
+    /// Summed effective DPS per BuildCostEnergy.
+    pub dps_per_energy: Option<f64>,
+    /// Effective HP (health plus own shield) per BuildCostMass.
+    pub ehp_per_mass: Option<f64>,
+    /// Summed effective DPS per BuildTime (build points; seconds at 1 build power).
+    pub dps_per_build_second: Option<f64>,
+}
+
+fn per(value: f64, cost: Option<f64>) -> Option<f64> {
+    cost.filter(|c| *c > 0.0).map(|c| value / c)
+}
+
+/// Cost efficiency of a unit with `dps` total effective DPS.
+pub fn cost_efficiency(stats: &UnitStats, dps: f64) -> CostEfficiency {
+    let ehp = TtkTarget::from_stats(stats).effective_hp();
+    CostEfficiency {
+        dps_per_mass: per(dps, stats.build_cost_mass),
+        dps_per_energy: per(dps, stats.build_cost_energy),
+        ehp_per_mass: if ehp > 0.0 {
+            per(ehp, stats.build_cost_mass)
+        } else {
+            None
+        },
+        dps_per_build_second: per(dps, stats.build_time),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratios_divide_by_cost() {
+        let stats = UnitStats {
+            max_health: Some(3600.0),
+            build_cost_mass: Some(1900.0),
+            build_cost_energy: Some(13585.0),
+            build_time: Some(1600.0),
+            ..Default::default()
+        };
+        let c = cost_efficiency(&stats, 95.0);
+        assert_eq!(c.dps_per_mass, Some(0.05));
+        assert!((c.dps_per_energy.unwrap() - 95.0 / 13585.0).abs() < 1e-12);
+        assert!((c.ehp_per_mass.unwrap() - 3600.0 / 1900.0).abs() < 1e-12);
+        assert!((c.dps_per_build_second.unwrap() - 95.0 / 1600.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn missing_or_zero_cost_gives_none() {
+        let stats = UnitStats {
+            max_health: Some(100.0),
+            build_cost_mass: Some(0.0),
+            ..Default::default()
+        };
+        assert_eq!(cost_efficiency(&stats, 10.0), CostEfficiency::default());
+    }
+}
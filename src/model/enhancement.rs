@@ -0,0 +1,365 @@
+//! ACU/SCU enhancements: parse the Enhancements table and build one unit variant per upgrade path.
+
+use super::{build_unit_summary, UnitId, UnitStats, UnitSummary, WeaponDeclared};
+use crate::config::{AnalysisConfig, EnhancementEffect};
+use crate::parser::{LuaKey, LuaValue};
+use serde::{Deserialize, Serialize};
+
+/// One named entry of the blueprint's Enhancements table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enhancement {
+    pub name: String,
+    pub slot: Option<String>,
+    pub prerequisite: Option<String>,
+    pub build_cost_mass: f64,
+    pub build_cost_energy: f64,
+    pub build_time: f64,
+    /// Effect read from the blueprint's New* / …DamageMod fields.
+    pub effect: EnhancementEffect,
+}
+
+/// A commander variant with one upgrade path installed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnhancementVariant {
+    /// Enhancement the path ends in.
+    pub enhancement: String,
+    /// Enhancements installed, prerequisites first.
+    pub path: Vec<String>,
+    pub slot: Option<String>,
+    /// Summed build cost of every enhancement on the path.
+    pub build_cost_mass: f64,
+    pub build_cost_energy: f64,
+    pub build_time: f64,
+    /// True when part of the effect came from the declarative data file rather than the blueprint.
+    pub uses_declared_data: bool,
+    pub summary: UnitSummary,
+}
+
+/// Parse the Enhancements table. Skips `Slots` and removal entries (RemoveEnhancements / …Remove).
+pub fn enhancements_from_lua(root: &LuaValue) -> Vec<Enhancement> {
+    let Some(table) = root.get_table("Enhancements").and_then(LuaValue::as_table) else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for (key, value) in table {
+        let LuaKey::String(name) = key else { continue };
+        if name == "Slots"
+            || name.ends_with("Remove")
+            || value.get_table("RemoveEnhancements").is_some()
+            || value.as_table().is_none()
+        {
+            continue;
+        }
+        out.push(Enhancement {
+            name: name.clone(),
+            slot: value.get_str("Slot").map(str::to_string),
+            prerequisite: value.get_str("Prerequisite").map(str::to_string),
+            build_cost_mass: value.get_num("BuildCostMass").unwrap_or(0.0),
+            build_cost_energy: value.get_num("BuildCostEnergy").unwrap_or(0.0),
+            build_time: value.get_num("BuildTime").unwrap_or(0.0),
+            effect: effect_from_lua(name, value),
+        });
+    }
+    out
+}
+
+fn effect_from_lua(name: &str, value: &LuaValue) -> EnhancementEffect {
+    let damage_mods: Vec<f64> = value
+        .as_table()
+        .map(|t| {
+            t.iter()
+                .filter_map(|(k, v)| match k {
+                    LuaKey::String(k) if k.ends_with("DamageMod") => v.as_number(),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    EnhancementEffect {
+        name: name.to_string(),
+        damage_add: if damage_mods.is_empty() {
+            None
+        } else {
+            Some(damage_mods.iter().sum())
+        },
+        max_radius: value.get_num("NewMaxRadius"),
+        rate_of_fire: value.get_num("NewRateOfFire"),
+        damage_radius: value.get_num("NewDamageRadius"),
+        health_add: value.get_num("NewHealth"),
+        regen_add: value.get_num("NewRegenRate"),
+        ..Default::default()
+    }
+}
+
+/// Enhancement names from the root prerequisite down to `name`. Stops at missing or repeated names.
+pub fn upgrade_path(enhancements: &[Enhancement], name: &str) -> Vec<String> {
+    let mut path = vec![name.to_string()];
+    let mut current = name.to_string();
+    while let Some(prereq) = enhancements
+        .iter()
+        .find(|e| e.name == current)
+        .and_then(|e| e.prerequisite.clone())
+    {
+        if path.contains(&prereq) || !enhancements.iter().any(|e| e.name == prereq) {
+            break;
+        }
+        path.push(prereq.clone());
+        current = prereq;
+    }
+    path.reverse();
+    path
+}
+
+/// True when the weapon only exists with an enhancement: EnabledByEnhancement in the blueprint,
+/// or listed under `enable_weapons` for this unit in the enhancement data file.
+fn is_gated(unit_id: &str, w: &WeaponDeclared, cfg: &AnalysisConfig) -> bool {
+    w.enabled_by_enhancement.is_some()
+        || cfg
+            .enhancements
+            .enhancement
+            .iter()
+            .filter(|e| e.unit.eq_ignore_ascii_case(unit_id))
+            .any(|e| label_in(w, &e.enable_weapons))
+}
+
+fn label_in(w: &WeaponDeclared, labels: &[String]) -> bool {
+    w.label
+        .as_deref()
+        .map(|l| labels.iter().any(|x| x.eq_ignore_ascii_case(l)))
+        .unwrap_or(false)
+}
+
+/// Weapons present on the unit with no enhancement installed.
+pub fn base_weapons(
+    unit_id: &str,
+    all_weapons: &[WeaponDeclared],
+    cfg: &AnalysisConfig,
+) -> Vec<WeaponDeclared> {
+    all_weapons
+        .iter()
+        .filter(|w| !is_gated(unit_id, w, cfg))
+        .cloned()
+        .collect()
+}
+
+/// Apply an upgrade path to a copy of the weapons and stats. Weapon changes apply in path order;
+/// health and regen take the last value set along the path (FAF swaps the prerequisite's buff out).
+fn apply_path(
+    unit_id: &str,
+    all_weapons: &[WeaponDeclared],
+    stats: &UnitStats,
+    effects: &[EnhancementEffect],
+    cfg: &AnalysisConfig,
+) -> (Vec<WeaponDeclared>, UnitStats) {
+    let enabled: Vec<String> = effects
+        .iter()
+        .flat_map(|e| e.enable_weapons.iter().cloned())
+        .collect();
+    let mut weapons: Vec<WeaponDeclared> = all_weapons
+        .iter()
+        .filter(|w| {
+            let by_blueprint = w
+                .enabled_by_enhancement
+                .as_deref()
+                .map(|by| effects.iter().any(|e| e.name.eq_ignore_ascii_case(by)))
+                .unwrap_or(false);
+            !is_gated(unit_id, w, cfg) || by_blueprint || label_in(w, &enabled)
+        })
+        .cloned()
+        .collect();
+    for effect in effects {
+        let target = match &effect.weapon {
+            Some(label) => weapons
+                .iter()
+                .position(|w| label_in(w, std::slice::from_ref(label))),
+            None => weapons.iter().position(|w| w.role.is_combat()),
+        };
+        if let Some(w) = target.map(|i| &mut weapons[i]) {
+            if let Some(d) = effect.damage_add {
+                w.damage += d;
+            }
+            if let Some(r) = effect.max_radius {
+                w.range = r;
+            }
+            if let Some(rof) = effect.rate_of_fire {
+                w.rate_of_fire = rof.max(0.001);
+            }
+            if let Some(r) = effect.damage_radius {
+                w.damage_radius = r;
+            }
+        }
+    }
+    let mut stats = stats.clone();
+    if let Some(h) = effects.iter().rev().find_map(|e| e.health_add) {
+        stats.max_health = Some(stats.max_health.unwrap_or(0.0) + h);
+    }
+    if let Some(r) = effects.iter().rev().find_map(|e| e.regen_add) {
+        stats.regen_rate = Some(stats.regen_rate.unwrap_or(0.0) + r);
+    }
+    (weapons, stats)
+}
+
+/// Build one variant per enhancement: its upgrade path applied to the unit, summarised like a unit.
+/// `all_weapons` includes weapons that only exist with an enhancement (EnabledByEnhancement).
+pub fn enhancement_variants(
+    unit_id: &UnitId,
+    blueprint_path: &str,
+    all_weapons: &[WeaponDeclared],
+    stats: &UnitStats,
+    enhancements: &[Enhancement],
+    cfg: &AnalysisConfig,
+) -> Vec<EnhancementVariant> {
+    enhancements
+        .iter()
+        .map(|enh| {
+            let path = upgrade_path(enhancements, &enh.name);
+            let mut uses_declared_data = false;
+            let steps: Vec<&Enhancement> = path
+                .iter()
+                .filter_map(|n| enhancements.iter().find(|e| &e.name == n))
+                .collect();
+            let effects: Vec<EnhancementEffect> = steps
+                .iter()
+                .map(|e| match cfg.enhancements.find(&unit_id.id, &e.name) {
+                    Some(data) => {
+                        uses_declared_data = true;
+                        e.effect.merged_with(data)
+                    }
+                    None => e.effect.clone(),
+                })
+                .collect();
+            let (weapons, mut variant_stats) =
+                apply_path(&unit_id.id, all_weapons, stats, &effects, cfg);
+            let build_cost_mass: f64 = steps.iter().map(|e| e.build_cost_mass).sum();
+            let build_cost_energy: f64 = steps.iter().map(|e| e.build_cost_energy).sum();
+            let build_time: f64 = steps.iter().map(|e| e.build_time).sum();
+            // A variant's cost efficiency counts the unit plus its upgrades.
+            let plus = |base: Option<f64>, extra: f64| {
+                Some(base.unwrap_or(0.0) + extra).filter(|c| *c > 0.0)
+            };
+            variant_stats.build_cost_mass = plus(stats.build_cost_mass, build_cost_mass);
+            variant_stats.build_cost_energy = plus(stats.build_cost_energy, build_cost_energy);
+            variant_stats.build_time = plus(stats.build_time, build_time);
+            let summary = build_unit_summary(
+                UnitId {
+                    id: format!("{}/{}", unit_id.id, enh.name),
+                    name: unit_id.name.clone(),
+                },
+                blueprint_path.to_string(),
+                weapons,
+                variant_stats,
+                cfg,
+                None,
+            );
+            EnhancementVariant {
+                enhancement: enh.name.clone(),
+                path,
+                slot: enh.slot.clone(),
+                build_cost_mass,
+                build_cost_energy,
+                build_time,
+                uses_declared_data,
+                summary,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::EnhancementEffects;
+    use crate::model::{unit_stats_from_lua, weapons_from_unit_lua};
+
+    const ACU: &str = r#"{
+        BlueprintId = "uel0001",
+        Defense = { MaxHealth = 12000, RegenRate = 10 },
+        Enhancements = {
+            Slots = { RCH = { x = 0 } },
+            Gun = { BuildCostMass = 100, NewMaxRadius = 30, ZephyrDamageMod = 50, Slot = "RCH" },
+            GunRemove = { Prerequisite = "Gun", RemoveEnhancements = { "Gun" }, Slot = "RCH" },
+            Shield = { BuildCostMass = 200, NewHealth = 1000, Slot = "LCH" },
+            ShieldAdvanced = { BuildCostMass = 300, NewHealth = 4000, Prerequisite = "Shield", Slot = "LCH" },
+            Missile = { BuildCostMass = 400, Slot = "Back" },
+        },
+        Weapon = {
+            { Label = "RightZephyr", Damage = 100, RateOfFire = 1, MaxRadius = 22 },
+            { Label = "TacMissile", EnabledByEnhancement = "Missile", Damage = 1000, RateOfFire = 0.1, MaxRadius = 256 },
+        },
+    }"#;
+
+    fn parse() -> (Vec<WeaponDeclared>, UnitStats, Vec<Enhancement>) {
+        let root = crate::parser::parse_blueprint(ACU).unwrap();
+        (
+            weapons_from_unit_lua(&root),
+            unit_stats_from_lua(&root),
+            enhancements_from_lua(&root),
+        )
+    }
+
+    fn variants(cfg: &AnalysisConfig) -> Vec<EnhancementVariant> {
+        let (weapons, stats, enh) = parse();
+        let id = UnitId {
+            id: "uel0001".to_string(),
+            name: None,
+        };
+        enhancement_variants(&id, "acu.bp", &weapons, &stats, &enh, cfg)
+    }
+
+    #[test]
+    fn parses_enhancements_and_skips_slots_and_removals() {
+        let (_, _, enh) = parse();
+        let names: Vec<&str> = enh.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["Gun", "Missile", "Shield", "ShieldAdvanced"]);
+        let gun = &enh[0];
+        assert_eq!(gun.effect.max_radius, Some(30.0));
+        assert_eq!(gun.effect.damage_add, Some(50.0));
+        assert_eq!(
+            upgrade_path(&enh, "ShieldAdvanced"),
+            ["Shield", "ShieldAdvanced"]
+        );
+    }
+
+    #[test]
+    fn variants_apply_weapon_health_and_enabled_weapons() {
+        let cfg = AnalysisConfig::default();
+        let (weapons, _, _) = parse();
+        assert_eq!(base_weapons("uel0001", &weapons, &cfg).len(), 1);
+        let v = variants(&cfg);
+        let gun = v.iter().find(|v| v.enhancement == "Gun").unwrap();
+        assert_eq!(gun.summary.weapons[0].range, 30.0);
+        assert_eq!(gun.summary.weapons[0].damage, 150.0);
+        let missile = v.iter().find(|v| v.enhancement == "Missile").unwrap();
+        assert_eq!(missile.summary.weapons.len(), 2);
+        let adv = v
+            .iter()
+            .find(|v| v.enhancement == "ShieldAdvanced")
+            .unwrap();
+        assert_eq!(adv.summary.stats.max_health, Some(16000.0));
+        assert_eq!(adv.build_cost_mass, 500.0);
+        assert_eq!(adv.summary.unit_id.id, "uel0001/ShieldAdvanced");
+    }
+
+    #[test]
+    fn data_file_overrides_blueprint_effect() {
+        let cfg = AnalysisConfig {
+            enhancements: EnhancementEffects {
+                enhancement: vec![EnhancementEffect {
+                    unit: "UEL0001".to_string(),
+                    name: "Gun".to_string(),
+                    weapon: Some("RightZephyr".to_string()),
+                    damage_add: Some(200.0),
+                    ..Default::default()
+                }],
+            },
+            ..Default::default()
+        };
+        let gun = variants(&cfg)
+            .into_iter()
+            .find(|v| v.enhancement == "Gun")
+            .unwrap();
+        assert!(gun.uses_declared_data);
+        assert_eq!(gun.summary.weapons[0].damage, 300.0);
+        assert_eq!(gun.summary.weapons[0].range, 30.0);
+    }
+}
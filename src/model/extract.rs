@@ -1,17 +1,24 @@
 //! Extract unit and weapon data from parsed blueprint LuaValue.
 
 use super::{
-    cycle_time_sec, effective_dps, nominal_dps, normalize_projectile_path, salvo_duration_sec,
-    total_damage_per_shot, ProjectileData, TargetClassDps, UnitId, UnitSummary, WeaponDeclared,
-    WeaponEffective,
+    acquisition_time, air_from_lua, attack_run, base_weapons, burst_profile, cost_efficiency,
+    cycle_time_sec, cycle_time_with, damage_timeline, dps_by_range, effective_dps,
+    enhancement_variants, enhancements_from_lua, friendly_fire, intercept_target_from_lua,
+    interceptors, monte_carlo, nominal_dps, normalize_projectile_path, reaches_before_lifetime,
+    resolve_projectile_tree, salvo_duration_with, switching_dps, tick_rate_ratio,
+    time_of_flight_sec, total_damage_per_shot, turret_from_lua, veterancy_table, volley_damage,
+    weapon_role_from_lua, yaw_coverage_issue, FriendlyFire, ProjectileData, ProjectileStatus,
+    ShieldStats, TargetClassDps, UnitId, UnitStats, UnitSummary, WeaponDeclared, WeaponEffective,
+    WeaponRole,
 };
 use crate::anomaly::Anomaly;
+use crate::config::{AnalysisConfig, DEFAULT_TTK_HORIZON_SECS, MAX_VET_LEVEL};
 use crate::parser::LuaValue;
 use crate::scheduler;
 use std::collections::HashMap;
 use std::path::Path;
 
-/// Enrich weapons with fragment count (and optionally fragment damage) from projectile map.
+/// Enrich weapons with their resolved projectile tree, plus first-level fragment count and damage.
 /// The only reliable way to get actual fragment count is from projectiles data.
 pub fn enrich_weapons_from_projectiles(
     weapons: &mut [WeaponDeclared],
@@ -21,24 +28,23 @@ pub fn enrich_weapons_from_projectiles(
         let Some(ref proj_id) = w.projectile_id else {
             continue;
         };
-        let key = normalize_projectile_path(proj_id);
-        let Some(proj) = map.get(&key) else { continue };
-        if let Some(n) = proj.fragment_count {
-            w.fragment_count = Some(n);
-        }
-        if let Some(ref frag_id) = proj.fragment_id {
-            let frag_key = normalize_projectile_path(frag_id);
-            if let Some(frag_proj) = map.get(&frag_key) {
-                if let Some(d) = frag_proj.damage {
-                    w.fragment_damage = Some(d);
-                }
+        let tree = resolve_projectile_tree(proj_id, w.damage, map);
+        if let Some(frag) = tree.fragments.first() {
+            w.fragment_count = Some(frag.count);
+            if frag.status == ProjectileStatus::Resolved {
+                w.fragment_damage = Some(frag.damage);
             }
         }
+        w.projectile_tree = Some(tree);
+        w.flight = map
+            .get(&normalize_projectile_path(proj_id))
+            .map(|p| p.flight.clone());
     }
 }
 
 /// Extract a single weapon's declared stats from a Lua table (weapon blueprint).
-/// FAF: RackSalvoSize, MuzzleSalvoSize, MuzzleSalvoDelay, RackSalvoReloadTime drive real behavior.
+/// FAF: RackSalvoSize, MuzzleSalvoSize, MuzzleSalvoDelay, RackSalvoReloadTime drive real behavior;
+/// RackSalvoChargeTime, WeaponUnpacks and the timeouts feed the scheduler's state machine.
 /// ProjectilesPerOnFire is deprecated; fragment count comes from projectiles data. Damage does not include fragments or DoT.
 pub fn weapon_from_lua(table: &LuaValue) -> Option<WeaponDeclared> {
     let damage = table.get_num("Damage").unwrap_or(0.0);
@@ -48,6 +54,7 @@ pub fn weapon_from_lua(table: &LuaValue) -> Option<WeaponDeclared> {
         .map(|s| s.trim_matches('"').to_string());
     let rate_of_fire = table.get_num("RateOfFire").unwrap_or(1.0);
     let range = table.get_num("MaxRadius").unwrap_or(0.0);
+    let min_range = table.get_num("MinRadius").unwrap_or(0.0).max(0.0);
     let radius = table.get_num("DamageRadius").unwrap_or(0.0);
     let rack_salvo_size = table.get_num("RackSalvoSize").map(|n| n as u32);
     let rack_reload = table.get_num("RackSalvoReloadTime");
@@ -60,7 +67,12 @@ pub fn weapon_from_lua(table: &LuaValue) -> Option<WeaponDeclared> {
     let salvo_delay = table.get_num("SalvoDelay").or(muzzle_salvo_delay);
     let reload = table.get_num("ReloadTime").or(rack_reload);
     let muzzle = table.get_num("MuzzleVelocity");
-    let turret = table.get_bool("TurretCapable").unwrap_or(false);
+    let turret = turret_from_lua(table);
+    let firing_randomness = table.get_num("FiringRandomness");
+    let ballistic_arc = table.get_str("BallisticArc").map(str::to_string);
+    let label = table.get_str("Label").map(str::to_string);
+    let artillery_shield_blocks = table.get_bool("ArtilleryShieldBlocks").unwrap_or(false);
+    let enabled_by_enhancement = table.get_str("EnabledByEnhancement").map(str::to_string);
     let categories = categories_from_lua(table);
     let weapon_bp_id = table
         .get_str("BlueprintId")
@@ -69,6 +81,7 @@ pub fn weapon_from_lua(table: &LuaValue) -> Option<WeaponDeclared> {
         .to_string();
     let projectiles = table.get_num("ProjectilesPerOnFire").map(|n| n as u32);
     let projectiles_per_fire = projectiles.or(muzzle_salvo_size).unwrap_or(1).max(1);
+    let role = weapon_role_from_lua(table);
     Some(WeaponDeclared {
         weapon_bp_id,
         damage,
@@ -85,6 +98,7 @@ pub fn weapon_from_lua(table: &LuaValue) -> Option<WeaponDeclared> {
             None
         },
         range,
+        min_range,
         salvo_size,
         salvo_delay,
         reload_time: reload,
@@ -92,14 +106,56 @@ pub fn weapon_from_lua(table: &LuaValue) -> Option<WeaponDeclared> {
         rack_salvo_reload_time: rack_reload,
         muzzle_salvo_size,
         muzzle_salvo_delay,
-        turret_capable: turret,
+        turret_capable: turret.is_some(),
         target_categories: categories,
+        firing_randomness,
+        ballistic_arc,
+        label,
+        enabled_by_enhancement,
+        artillery_shield_blocks,
+        projectile_tree: None,
+        flight: None,
+        role,
+        intercepts: if role == WeaponRole::Defensive {
+            intercept_target_from_lua(table)
+        } else {
+            None
+        },
+        turret,
+        firing_tolerance: table.get_num("FiringTolerance"),
+        weapon_category: table.get_str("WeaponCategory").map(str::to_string),
+        bomb_drop_threshold: table.get_num("BombDropThreshold"),
+        damage_friendly: table.get_bool("DamageFriendly").unwrap_or(true),
+        collide_friendly: table.get_bool("CollideFriendly").unwrap_or(false),
+        rack_salvo_charge_time: table.get_num("RackSalvoChargeTime"),
+        weapon_unpacks: table.get_bool("WeaponUnpacks").unwrap_or(false),
+        unpack_animation_rate: table.get_num("WeaponUnpackAnimationRate"),
+        weapon_repack_timeout: table.get_num("WeaponRepackTimeout"),
+        rack_reload_timeout: table.get_num("RackReloadTimeout"),
+        turret_bone_yaw: table.get_str("TurretBoneYaw").map(str::to_string),
+        turret_bone_dual_yaw: if table.get_bool("TurretDualManipulators").unwrap_or(false) {
+            table.get_str("TurretBoneDualYaw").map(str::to_string)
+        } else {
+            None
+        },
+        slaved_to_body: table.get_bool("SlavedToBody").unwrap_or(false),
+        prefers_primary_target: table
+            .get_bool("PrefersPrimaryWeaponTarget")
+            .unwrap_or(false),
+        unpack_locks_motion: table.get_bool("WeaponUnpackLocksMotion").unwrap_or(false),
+        energy_required: table.get_num("EnergyRequired"),
+        energy_drain_per_sec: table.get_num("EnergyDrainPerSecond"),
+        projectiles_per_on_fire: projectiles,
     })
 }
 
 fn categories_from_lua(table: &LuaValue) -> Vec<String> {
-    let cats = table.get_table("TargetCategories");
-    if let Some(t) = cats {
+    string_list(table.get_table("TargetCategories"))
+}
+
+/// Collect the string entries of a Lua array table (e.g. Categories = { "LAND", "TECH1" }).
+fn string_list(list: Option<&LuaValue>) -> Vec<String> {
+    if let Some(t) = list {
         if let Some(len) = t.table_len() {
             let mut out = Vec::with_capacity(len);
             for i in 1..=len {
@@ -132,6 +188,64 @@ pub fn unit_id_from_lua(root: &LuaValue) -> Option<UnitId> {
     })
 }
 
+/// Extract unit-level stats (footprint, speed, motion type, defense, shield, economy, categories, veteran thresholds) from root unit table.
+/// Air units report Air.MaxAirspeed as their speed; Physics.MaxSpeed on air units is not the flight speed.
+pub fn unit_stats_from_lua(root: &LuaValue) -> UnitStats {
+    let physics = root.get_table("Physics");
+    let motion_type = physics
+        .and_then(|p| p.get_str("MotionType"))
+        .map(str::to_string);
+    let airspeed = root.get_table("Air").and_then(|a| a.get_num("MaxAirspeed"));
+    let motion_type_is_air = motion_type.as_deref() == Some("RULEUMT_Air");
+    let max_speed = if motion_type_is_air {
+        airspeed.or_else(|| physics.and_then(|p| p.get_num("MaxSpeed")))
+    } else {
+        physics.and_then(|p| p.get_num("MaxSpeed"))
+    };
+    let defense = root.get_table("Defense");
+    let veteran = root.get_table("Veteran");
+    let economy = root.get_table("Economy");
+    let veteran_levels = (1..=MAX_VET_LEVEL)
+        .map_while(|l| veteran.and_then(|v| v.get_num(&format!("Level{}", l))))
+        .collect();
+    UnitStats {
+        size_x: root.get_num("SizeX"),
+        size_z: root.get_num("SizeZ"),
+        max_speed,
+        motion_type,
+        max_health: defense.and_then(|d| d.get_num("MaxHealth").or_else(|| d.get_num("Health"))),
+        regen_rate: defense.and_then(|d| d.get_num("RegenRate")),
+        armor_type: defense
+            .and_then(|d| d.get_str("ArmorType"))
+            .map(str::to_string),
+        categories: string_list(root.get_table("Categories")),
+        veteran_levels,
+        shield: defense.and_then(shield_from_lua),
+        build_cost_mass: economy.and_then(|e| e.get_num("BuildCostMass")),
+        build_cost_energy: economy.and_then(|e| e.get_num("BuildCostEnergy")),
+        build_time: economy.and_then(|e| e.get_num("BuildTime")),
+        energy_production: economy.and_then(|e| e.get_num("ProductionPerSecondEnergy")),
+        energy_storage: economy.and_then(|e| e.get_num("StorageEnergy")),
+        air: if motion_type_is_air {
+            air_from_lua(root)
+        } else {
+            None
+        },
+    }
+}
+
+/// Defense.Shield; None when absent or without shield health.
+fn shield_from_lua(defense: &LuaValue) -> Option<ShieldStats> {
+    let shield = defense.get_table("Shield")?;
+    let max_health = shield.get_num("ShieldMaxHealth").filter(|h| *h > 0.0)?;
+    Some(ShieldStats {
+        max_health,
+        regen_rate: shield.get_num("ShieldRegenRate").unwrap_or(0.0).max(0.0),
+        recharge_time: shield.get_num("ShieldRechargeTime").unwrap_or(0.0).max(0.0),
+        size: shield.get_num("ShieldSize").unwrap_or(0.0),
+    })
+}
+
 /// Collect weapon tables from unit blueprint (Weapon array or Weapons table).
 pub fn weapons_from_unit_lua(root: &LuaValue) -> Vec<WeaponDeclared> {
     let mut out = Vec::new();
@@ -162,18 +276,20 @@ pub fn build_unit_summary(
     unit_id: UnitId,
     blueprint_path: String,
     weapons: Vec<WeaponDeclared>,
-    simulation_sec: f64,
-    gap_tolerance_sec: f64,
+    stats: UnitStats,
+    cfg: &AnalysisConfig,
     declared_dps_override: Option<f64>,
 ) -> UnitSummary {
+    let simulation_sec = cfg.simulation_seconds;
+    let gap_tolerance_sec = cfg.cadence_gap_tolerance_secs;
     let mut effective = Vec::with_capacity(weapons.len());
     let mut anomalies = Vec::new();
 
     for w in &weapons {
         let damage = total_damage_per_shot(w);
         let nominal = nominal_dps(damage, w.projectiles_per_fire, w.rate_of_fire);
-        let cycle = cycle_time_sec(w.rate_of_fire, w.reload_time);
-        let salvo_dur = salvo_duration_sec(w.salvo_size, w.salvo_delay);
+        let cycle = cycle_time_with(w.rate_of_fire, w.reload_time, cfg.timing);
+        let salvo_dur = salvo_duration_with(w.salvo_size, w.salvo_delay, cfg.timing);
         let eff_dps = effective_dps(
             damage,
             w.projectiles_per_fire,
@@ -181,6 +297,7 @@ pub fn build_unit_summary(
             w.reload_time,
             w.salvo_size,
             w.salvo_delay,
+            cfg.timing,
         );
         let shots = w.salvo_size.unwrap_or(1).max(1);
         let target_class_modifiers: Vec<TargetClassDps> = w
@@ -192,7 +309,9 @@ pub fn build_unit_summary(
                 modifier_note: None,
             })
             .collect();
-        effective.push(WeaponEffective {
+        let acquisition = acquisition_time(w);
+        let tof = time_of_flight_sec(w, w.range);
+        let mut e = WeaponEffective {
             weapon_bp_id: w.weapon_bp_id.clone(),
             nominal_dps: nominal,
             effective_dps: eff_dps,
@@ -201,9 +320,73 @@ pub fn build_unit_summary(
             salvo_duration_sec: salvo_dur,
             reload_sec: cycle,
             target_class_modifiers,
-        });
+            time_of_flight_sec: tof,
+            reaches_max_range: reaches_before_lifetime(w, w.range),
+            acquisition,
+            first_shot_latency_sec: acquisition
+                .map(|a| a.worst_sec)
+                .filter(|t| t.is_finite())
+                .unwrap_or(0.0)
+                + tof.unwrap_or(0.0),
+            switching_dps: switching_dps(
+                eff_dps,
+                cycle + salvo_dur,
+                acquisition,
+                cfg.target_switch_interval_secs,
+            ),
+            attack_run: None,
+        };
+        if let Some(run) = stats.air.and_then(|air| attack_run(w, &e, &air)) {
+            e.effective_dps = run.sustained_dps;
+            e.cycle_time_sec = run.reattack_interval_sec - salvo_dur;
+            e.reload_sec = e.cycle_time_sec;
+            e.switching_dps = run.sustained_dps;
+            for m in &mut e.target_class_modifiers {
+                m.effective_dps = run.sustained_dps;
+            }
+            e.attack_run = Some(run);
+        }
+        let eff_dps = e.effective_dps;
+        effective.push(e);
+        let structure = stats.motion_type.as_deref() == Some("RULEUMT_None");
+        if let Some(issue) = w
+            .turret
+            .filter(|_| w.role != WeaponRole::Death && w.role != WeaponRole::Dummy)
+            .and_then(|t| yaw_coverage_issue(&t, structure))
+        {
+            anomalies.push(Anomaly::turret_yaw_limited(
+                &unit_id.id,
+                &w.weapon_bp_id,
+                issue,
+            ));
+        }
+        if let Some(ratio) = tick_rate_ratio(w.rate_of_fire, w.reload_time)
+            .filter(|r| (r - 1.0).abs() > cfg.rof_rounding_threshold)
+            .filter(|_| w.role != WeaponRole::Death && w.role != WeaponRole::Dummy)
+        {
+            let declared = cycle_time_sec(w.rate_of_fire, w.reload_time);
+            anomalies.push(Anomaly::rof_tick_rounding(
+                &unit_id.id,
+                &w.weapon_bp_id,
+                declared,
+                declared / ratio,
+                cfg.rof_rounding_threshold,
+            ));
+        }
+        if let (Some(false), Some(flight)) = (reaches_before_lifetime(w, w.range), &w.flight) {
+            anomalies.push(Anomaly::projectile_lifetime_short(
+                &unit_id.id,
+                &w.weapon_bp_id,
+                tof.unwrap_or(0.0),
+                flight.lifetime.unwrap_or(0.0),
+                w.range,
+            ));
+        }
 
-        if declared_dps_override.is_none() && (nominal - eff_dps).abs() > 0.01 * nominal.max(1.0) {
+        if declared_dps_override.is_none()
+            && w.role.is_combat()
+            && (nominal - eff_dps).abs() > 0.01 * nominal.max(1.0)
+        {
             anomalies.push(Anomaly::declared_vs_effective_mismatch(
                 &unit_id.id,
                 &w.weapon_bp_id,
@@ -213,8 +396,15 @@ pub fn build_unit_summary(
         }
     }
 
+    let (combat_weapons, combat_effective): (Vec<WeaponDeclared>, Vec<WeaponEffective>) = weapons
+        .iter()
+        .zip(effective.iter())
+        .filter(|(w, _)| w.role.is_combat())
+        .map(|(w, e)| (w.clone(), e.clone()))
+        .unzip();
+
     if let Some(declared) = declared_dps_override {
-        let total_effective: f64 = effective.iter().map(|e| e.effective_dps).sum();
+        let total_effective: f64 = combat_effective.iter().map(|e| e.effective_dps).sum();
         if (declared - total_effective).abs() > 0.01 * declared.max(1.0) {
             anomalies.push(Anomaly::declared_vs_effective_mismatch(
                 &unit_id.id,
@@ -225,24 +415,142 @@ pub fn build_unit_summary(
         }
     }
 
-    if weapons.len() > 1 && !effective.is_empty() {
-        let result = scheduler::simulate(&weapons, &effective, simulation_sec, gap_tolerance_sec);
+    // Death, dummy and defensive weapons never fire alongside the guns; keep them out of the cadence check.
+    if combat_weapons.len() > 1 {
+        let resources =
+            scheduler::shared_resources(&combat_weapons, scheduler::EnergyPool::from_stats(&stats));
+        let opts = scheduler::SimOptions {
+            timing: cfg.timing,
+            resources: Some(resources.clone()),
+            ..Default::default()
+        };
+        let period = scheduler::hyperperiod(
+            &combat_weapons,
+            &combat_effective,
+            &resources,
+            &opts,
+            cfg.max_hyperperiod_secs,
+        );
+        let simulate = |window_sec: f64| {
+            scheduler::simulate_with(
+                &combat_weapons,
+                &combat_effective,
+                window_sec,
+                gap_tolerance_sec,
+                &opts,
+            )
+        };
+        // The period is exact only for the weapons alone: trust it once the contended pattern
+        // of the second period repeats the first.
+        let (result, window_note) = match &period {
+            Ok(h) => {
+                let result = simulate(h.window_sec());
+                if h.recurs(&result.events) {
+                    let note = format!(
+                        "Hyperperiod {:.1}s (LCM of {}){}, simulated twice",
+                        h.period_sec,
+                        h.weapon_periods_sec
+                            .iter()
+                            .filter(|p| **p > 0.0)
+                            .map(|p| format!("{:.1}s", p))
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        if h.lead_in_sec > 0.0 {
+                            format!(" after a {:.1}s lead-in", h.lead_in_sec)
+                        } else {
+                            String::new()
+                        }
+                    );
+                    (result, note)
+                } else {
+                    (
+                        simulate(simulation_sec),
+                        format!(
+                            "No exact hyperperiod (shared locks break the {:.1}s period); simulated",
+                            h.period_sec
+                        ),
+                    )
+                }
+            }
+            Err(why) => (
+                simulate(simulation_sec),
+                format!("No exact hyperperiod ({}); simulated", why),
+            ),
+        };
         let expected: u32 = result.weapon_expected_shots.values().sum();
         let actual: u32 = result.weapon_actual_shots.values().sum();
         if expected > 0 && (actual as f64) < (expected as f64) * 0.95 {
+            // Real weapons rarely carry a BlueprintId: name them by label or array index.
+            let names: Vec<String> = weapons
+                .iter()
+                .enumerate()
+                .filter(|(_, w)| w.role.is_combat())
+                .map(|(i, w)| w.display_name(i))
+                .collect();
+            let ids = |i: usize| names[i].as_str();
+            let lost_dps: Vec<f64> = combat_weapons
+                .iter()
+                .zip(&result.deficit_shots)
+                .map(|(w, &n)| n as f64 * volley_damage(w) / result.window_sec)
+                .collect();
+            // Weapon that blocked weapon `i` longest, if any lock held it back.
+            let blocker = |i: usize| {
+                result.blocked_by_sec[i]
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, s)| **s > 0.0)
+                    .max_by(|a, b| a.1.total_cmp(b.1))
+                    .map(|(j, _)| j)
+            };
+            let losses: Vec<String> = (0..combat_weapons.len())
+                .filter(|&i| result.deficit_shots[i] > 0)
+                .map(|i| {
+                    let waits: Vec<String> = result.blocked_by_sec[i]
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, s)| **s > 0.0)
+                        .map(|(j, s)| format!("{:.2}s on {}", s, ids(j)))
+                        .collect();
+                    format!(
+                        "{} -{} shots = {:.1} DPS{}",
+                        ids(i),
+                        result.deficit_shots[i],
+                        lost_dps[i],
+                        if waits.is_empty() {
+                            String::new()
+                        } else {
+                            format!(" (waited {})", waits.join(", "))
+                        }
+                    )
+                })
+                .collect();
             let technical = format!(
-                "Over {}s expected {} shots, got {}. Gaps: {}",
+                "{}. Over {}s the weapons fire {} shots alone, {} together. Lost: {}. Shared: {}. Gaps: {}",
+                window_note,
                 result.window_sec,
                 expected,
                 actual,
+                losses.join("; "),
+                resources
+                    .iter()
+                    .map(|r| r.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
                 result.gaps.len()
             );
+            let victim = (0..combat_weapons.len())
+                .max_by(|&a, &b| lost_dps[a].total_cmp(&lost_dps[b]).then(b.cmp(&a)))
+                .unwrap_or(0);
+            let energy_user = resources.iter().any(|r| {
+                matches!(r.kind, scheduler::ResourceKind::Energy { .. })
+                    && r.users.contains(&victim)
+            });
             anomalies.push(Anomaly::cadence_interference(
                 &unit_id.id,
-                &weapons
-                    .iter()
-                    .map(|w| w.weapon_bp_id.clone())
-                    .collect::<Vec<_>>(),
+                ids(victim),
+                blocker(victim).map(ids),
+                energy_user,
+                lost_dps[victim],
                 technical,
             ));
         }
@@ -261,14 +569,124 @@ pub fn build_unit_summary(
         }
     }
 
-    UnitSummary {
+    let interceptors = interceptors(&weapons, cfg.timing);
+    for i in interceptors
+        .iter()
+        .filter(|i| !i.keeps_up_with(&cfg.intercept_threat))
+    {
+        anomalies.push(Anomaly::interceptor_saturated(
+            &unit_id.id,
+            &i.weapon_bp_id,
+            i.target.as_str(),
+            i.interceptions_per_min,
+            &cfg.intercept_threat,
+        ));
+    }
+
+    let mut friendly: Vec<FriendlyFire> = Vec::new();
+    let mut enemy_dpm = 0.0;
+    // Aircraft fly above their formation; neither splash nor line hits are modelled for them.
+    if stats.air.is_none() {
+        for (w, e) in combat_weapons.iter().zip(combat_effective.iter()) {
+            match friendly_fire(w, e, &cfg.friendly_fire) {
+                Some(f) => {
+                    enemy_dpm += f.enemy_damage_per_min;
+                    friendly.push(f);
+                }
+                None => enemy_dpm += e.effective_dps * 60.0,
+            }
+        }
+    }
+    let friendly_dpm: f64 = friendly.iter().map(|f| f.friendly_damage_per_min).sum();
+    if friendly_dpm > cfg.friendly_fire.max_fraction * enemy_dpm && friendly_dpm > 0.0 {
+        anomalies.push(Anomaly::friendly_fire_risk(
+            &unit_id.id,
+            &friendly
+                .iter()
+                .filter(|f| f.friendly_damage_per_min > 0.0)
+                .map(|f| f.weapon_bp_id.clone())
+                .collect::<Vec<_>>(),
+            friendly_dpm,
+            enemy_dpm,
+            &cfg.friendly_fire,
+        ));
+    }
+
+    let range_bands = dps_by_range(&combat_weapons, &combat_effective);
+    for w in &weapons {
+        let Some(tree) = &w.projectile_tree else {
+            continue;
+        };
+        for node in tree.nodes() {
+            match node.status {
+                ProjectileStatus::Missing => anomalies.push(Anomaly::projectile_missing(
+                    &unit_id.id,
+                    &w.weapon_bp_id,
+                    &node.projectile_id,
+                )),
+                ProjectileStatus::Cycle => anomalies.push(Anomaly::projectile_cycle(
+                    &unit_id.id,
+                    &w.weapon_bp_id,
+                    &node.projectile_id,
+                )),
+                ProjectileStatus::Resolved => {}
+            }
+        }
+    }
+
+    let mut reached = false;
+    for b in &range_bands {
+        if b.is_dead_zone() {
+            anomalies.push(Anomaly::range_dead_zone(
+                &unit_id.id,
+                b.start,
+                b.end,
+                reached,
+            ));
+        } else {
+            reached = true;
+        }
+    }
+
+    let mut summary = UnitSummary {
         unit_id,
         blueprint_path,
         weapons,
         effective,
         anomalies,
         declared_dps_override,
+        stats,
+        range_bands,
+        veterancy: Vec::new(),
+        variants: Vec::new(),
+        cost_efficiency: Default::default(),
+        interceptors,
+        burst: Default::default(),
+        friendly_fire: friendly,
+        monte_carlo: None,
+    };
+    let mirror = damage_timeline(&summary, 1, DEFAULT_TTK_HORIZON_SECS, cfg.timing);
+    summary.veterancy = veterancy_table(&summary.stats, &cfg.veterancy, &mirror);
+    summary.cost_efficiency = cost_efficiency(&summary.stats, summary.total_dps());
+    summary.burst = burst_profile(
+        &summary,
+        simulation_sec,
+        &cfg.burst_windows_secs,
+        &cfg.damage_milestones,
+        cfg.timing,
+    );
+    if let Some(mc) = cfg.monte_carlo {
+        summary.monte_carlo = Some(monte_carlo(
+            &summary,
+            &summary.stats,
+            mc.runs,
+            mc.seed,
+            simulation_sec,
+            DEFAULT_TTK_HORIZON_SECS,
+            cfg.timing,
+        ));
     }
+    summary
 }
 
 /// Try to parse file and extract one unit summary (if file looks like a unit blueprint).
@@ -277,8 +695,7 @@ pub fn build_unit_summary(
 pub fn unit_summary_from_file(
     path: &Path,
     content: &str,
-    simulation_sec: f64,
-    gap_tolerance_sec: f64,
+    cfg: &AnalysisConfig,
     declared_dps_overrides: Option<&std::collections::HashMap<String, f64>>,
     projectile_map: Option<&HashMap<String, ProjectileData>>,
 ) -> Result<Option<UnitSummary>, String> {
@@ -300,7 +717,9 @@ pub fn unit_summary_from_file(
         }
     };
     let mut weapons = weapons_from_unit_lua(&root);
-    if weapons.is_empty() {
+    let stats = unit_stats_from_lua(&root);
+    // Weaponless units are skipped, except shield generators (targets can sit under them).
+    if weapons.is_empty() && stats.shield.is_none() {
         return Ok(None);
     }
     if let Some(map) = projectile_map {
@@ -309,12 +728,23 @@ pub fn unit_summary_from_file(
     let declared_override =
         declared_dps_overrides.and_then(|m| m.get(&unit_id.id.to_lowercase()).copied());
     let blueprint_path = path.to_string_lossy().to_string();
-    Ok(Some(build_unit_summary(
-        unit_id,
+    let enhancements = enhancements_from_lua(&root);
+    let variants = enhancement_variants(
+        &unit_id,
+        &blueprint_path,
+        &weapons,
+        &stats,
+        &enhancements,
+        cfg,
+    );
+    let mut summary = build_unit_summary(
+        unit_id.clone(),
         blueprint_path,
-        weapons,
-        simulation_sec,
-        gap_tolerance_sec,
+        base_weapons(&unit_id.id, &weapons, cfg),
+        stats,
+        cfg,
         declared_override,
-    )))
+    );
+    summary.variants = variants;
+    Ok(Some(summary))
 }
@@ -0,0 +1,98 @@
+//! Projectile flight: time of flight from launch speed, Acceleration and MaxSpeed, and whether the
+//! projectile reaches a distance before its Lifetime expires.
+//!
+//! Arcing weapons (BallisticArc low/high) fly the ballistic solution from MuzzleVelocity. Everything
+//! else flies straight: launch at MuzzleVelocity (else InitialSpeed), accelerate up to MaxSpeed.
+
+use super::{flight_time_sec, WeaponDeclared};
+
+fn is_arcing(w: &WeaponDeclared) -> bool {
+    matches!(
+        w.ballistic_arc.as_deref(),
+        Some("RULEUBA_LowArc") | Some("RULEUBA_HighArc")
+    )
+}
+
+/// Seconds for the weapon's projectile to travel `distance`, or None when it has no speed data.
+pub fn time_of_flight_sec(w: &WeaponDeclared, distance: f64) -> Option<f64> {
+    if distance <= 0.0 {
+        return Some(0.0);
+    }
+    if is_arcing(w) && w.muzzle_velocity.is_some() {
+        return Some(flight_time_sec(w, distance));
+    }
+    let flight = w.flight.clone().unwrap_or_default();
+    let max_speed = flight.max_speed.filter(|v| *v > 0.0);
+    let mut v0 = w
+        .muzzle_velocity
+        .or(flight.initial_speed)
+        .unwrap_or(0.0)
+        .max(0.0);
+    if let Some(cap) = max_speed {
+        v0 = v0.min(cap);
+    }
+    let accel = flight.acceleration.unwrap_or(0.0).max(0.0);
+    if accel <= 0.0 {
+        return (v0 > 0.0).then(|| distance / v0);
+    }
+    // Distance covered while accelerating from v0 to the cap (unbounded without MaxSpeed).
+    let (t_acc, d_acc) = match max_speed {
+        Some(cap) => {
+            let t = (cap - v0) / accel;
+            (t, v0 * t + 0.5 * accel * t * t)
+        }
+        None => (f64::INFINITY, f64::INFINITY),
+    };
+    if distance <= d_acc {
+        Some((-v0 + (v0 * v0 + 2.0 * accel * distance).sqrt()) / accel)
+    } else {
+        Some(t_acc + (distance - d_acc) / max_speed.unwrap_or(v0))
+    }
+}
+
+/// Whether the projectile reaches `distance` before its Lifetime expires (None: no Lifetime or no speed data).
+pub fn reaches_before_lifetime(w: &WeaponDeclared, distance: f64) -> Option<bool> {
+    let lifetime = w.flight.as_ref()?.lifetime.filter(|l| *l > 0.0)?;
+    Some(time_of_flight_sec(w, distance)? <= lifetime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ProjectileFlight;
+
+    fn missile(flight: ProjectileFlight) -> WeaponDeclared {
+        WeaponDeclared {
+            weapon_bp_id: "Missile".to_string(),
+            range: 60.0,
+            flight: Some(flight),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn accelerates_to_max_speed_then_cruises() {
+        let w = missile(ProjectileFlight {
+            initial_speed: Some(2.0),
+            acceleration: Some(4.0),
+            max_speed: Some(10.0),
+            ..Default::default()
+        });
+        // 2 s to reach 10 (covering 12), then 48 more at 10.
+        assert!((time_of_flight_sec(&w, 60.0).unwrap() - 6.8).abs() < 1e-9);
+        assert!((time_of_flight_sec(&w, 4.0).unwrap() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn lifetime_limits_reach() {
+        let w = missile(ProjectileFlight {
+            initial_speed: Some(10.0),
+            lifetime: Some(5.0),
+            ..Default::default()
+        });
+        assert_eq!(reaches_before_lifetime(&w, 40.0), Some(true));
+        assert_eq!(reaches_before_lifetime(&w, 60.0), Some(false));
+        let no_speed = missile(ProjectileFlight::default());
+        assert_eq!(time_of_flight_sec(&no_speed, 10.0), None);
+    }
+}
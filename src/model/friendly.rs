@@ -0,0 +1,152 @@
+//! Friendly fire: damage a weapon is expected to deal to its own side when fired from inside a formation.
+//!
+//! Friendly units are spread uniformly at `density` units per square ogrid within `line_depth`
+//! ogrids of the shooter; the enemy stands beyond them. Impacts land uniformly between MinRadius
+//! and MaxRadius, and with DamageFriendly only those whose DamageRadius reaches back into the
+//! formation splash friendlies, each taking the full volley damage (fragments are counted with the
+//! weapon's radius). With CollideFriendly, a direct-fire shot also crosses the formation on a
+//! one-ogrid-wide line and stops on the first friendly it meets; that shot never reaches the enemy.
+//! Air units fly above their formation and are not modelled.
+
+use super::{volley_damage, WeaponDeclared, WeaponEffective};
+use crate::config::FriendlyFireConfig;
+use serde::{Deserialize, Serialize};
+
+/// Friendly-fire estimate for one weapon.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FriendlyFire {
+    pub weapon_bp_id: String,
+    /// Expected friendly damage per fire event.
+    pub friendly_damage_per_shot: f64,
+    /// Chance that a shot is stopped by a friendly on its way out (CollideFriendly, direct fire).
+    pub collide_chance: f64,
+    /// Fraction of impacts whose splash reaches the formation around the shooter.
+    #[serde(default)]
+    pub near_impact_fraction: f64,
+    pub friendly_damage_per_min: f64,
+    pub enemy_damage_per_min: f64,
+    /// DamageFriendly splash reaches past MinRadius, so the firing unit can catch its own splash.
+    pub self_splash: bool,
+}
+
+/// Friendly-fire estimate of `w` with effective stats `e`; None when it can neither splash nor collide with friendlies.
+pub fn friendly_fire(
+    w: &WeaponDeclared,
+    e: &WeaponEffective,
+    cfg: &FriendlyFireConfig,
+) -> Option<FriendlyFire> {
+    let splash = w.damage_friendly && w.damage_radius > 0.0;
+    let collide = w.collide_friendly && w.blocked_by_shields();
+    if !splash && !collide {
+        return None;
+    }
+    let density = cfg.density.max(0.0);
+    let collide_chance = if collide {
+        1.0 - (-density * cfg.line_depth.max(0.0)).exp()
+    } else {
+        0.0
+    };
+    let near = if splash {
+        near_impact_fraction(w, cfg.line_depth.max(0.0))
+    } else {
+        0.0
+    };
+    let splashed = near * density * std::f64::consts::PI * w.damage_radius * w.damage_radius;
+    let volley = volley_damage(w);
+    let friendly_damage_per_shot = volley * (splashed + collide_chance);
+    let shots_per_min = if volley > 0.0 {
+        e.effective_dps * 60.0 / volley
+    } else {
+        0.0
+    };
+    Some(FriendlyFire {
+        weapon_bp_id: w.weapon_bp_id.clone(),
+        friendly_damage_per_shot,
+        collide_chance,
+        near_impact_fraction: near,
+        friendly_damage_per_min: friendly_damage_per_shot * shots_per_min,
+        enemy_damage_per_min: volley * (1.0 - collide_chance) * shots_per_min,
+        self_splash: splash && w.min_range < w.damage_radius,
+    })
+}
+
+/// Fraction of impacts, uniform over MinRadius..MaxRadius, whose DamageRadius reaches within
+/// `depth` of the shooter.
+fn near_impact_fraction(w: &WeaponDeclared, depth: f64) -> f64 {
+    let reach = depth + w.damage_radius;
+    let (min, max) = (w.min_range.max(0.0), w.range.max(0.0));
+    if max <= min {
+        return if min < reach { 1.0 } else { 0.0 };
+    }
+    ((reach - min) / (max - min)).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splash_scales_with_area_and_density() {
+        let w = WeaponDeclared {
+            weapon_bp_id: "Arty".to_string(),
+            damage: 100.0,
+            projectiles_per_fire: 1,
+            damage_radius: 2.0,
+            damage_friendly: true,
+            ..Default::default()
+        };
+        let e = WeaponEffective {
+            effective_dps: 10.0,
+            ..Default::default()
+        };
+        let cfg = FriendlyFireConfig {
+            density: 0.1,
+            ..Default::default()
+        };
+        let ff = friendly_fire(&w, &e, &cfg).unwrap();
+        assert_eq!(ff.near_impact_fraction, 1.0);
+        let area = std::f64::consts::PI * 4.0;
+        assert!((ff.friendly_damage_per_shot - 100.0 * 0.1 * area).abs() < 1e-9);
+        assert!((ff.enemy_damage_per_min - 600.0).abs() < 1e-9);
+        assert!(ff.self_splash);
+        let safe = WeaponDeclared {
+            damage_friendly: false,
+            ..w
+        };
+        assert!(friendly_fire(&safe, &e, &cfg).is_none());
+    }
+
+    #[test]
+    fn long_range_artillery_splashes_no_friendlies() {
+        // Sera T3 mobile artillery: lands 25..90 ogrids out, radius 5, well clear of a 10-ogrid formation.
+        let w = WeaponDeclared {
+            weapon_bp_id: "Arty".to_string(),
+            damage: 700.0,
+            projectiles_per_fire: 1,
+            damage_radius: 5.0,
+            min_range: 25.0,
+            range: 90.0,
+            damage_friendly: true,
+            ..Default::default()
+        };
+        let e = WeaponEffective {
+            effective_dps: 70.0,
+            ..Default::default()
+        };
+        let cfg = FriendlyFireConfig {
+            density: 0.1,
+            ..Default::default()
+        };
+        let ff = friendly_fire(&w, &e, &cfg).unwrap();
+        assert_eq!(ff.near_impact_fraction, 0.0);
+        assert_eq!(ff.friendly_damage_per_min, 0.0);
+        // A 30-ogrid gun without MinRadius lands inside 10 + 5 ogrids half the time.
+        let short = WeaponDeclared {
+            min_range: 0.0,
+            range: 30.0,
+            ..w
+        };
+        let ff = friendly_fire(&short, &e, &cfg).unwrap();
+        assert!((ff.near_impact_fraction - 0.5).abs() < 1e-9);
+    }
+}
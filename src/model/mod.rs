@@ -1,14 +1,64 @@
 //! Weapon and unit model: DPS, cadence, salvo, target class.
 
+mod accuracy;
+mod battle;
+mod bomber;
+mod burst;
+mod defense;
+mod duel;
+mod economy;
+mod enhancement;
 mod extract;
+mod flight;
+mod friendly;
+mod montecarlo;
 mod projectile;
+mod range;
+mod role;
+mod timeline;
+mod ttk;
+mod turret;
+mod veterancy;
 
+use crate::config::{TimingMode, SIM_TICK_SECS};
+pub use accuracy::{
+    combined_miss_radius, expected_dps_vs_target, flight_time_sec, hit_chance,
+    hit_chance_monte_carlo, HitEstimate, HitMode, TargetProfile, GRAVITY,
+};
+pub use battle::{
+    battle, ArmyReport, BattleOptions, BattleResult, BattleSample, BattleSide, GroupReport,
+};
+pub use bomber::{air_from_lua, attack_run, AirStats, AttackRun};
+pub use burst::{burst_profile, BurstProfile, CurvePoint, DamageMilestone, WindowDamage};
+pub use defense::{interceptions_per_min, interceptors, Interceptor};
+pub use duel::{duel, DuelOptions, DuelOutcome, DuelResult, DuelSample, DuelSide, SideSnapshot};
+pub use economy::{cost_efficiency, CostEfficiency};
+pub use enhancement::{
+    base_weapons, enhancement_variants, enhancements_from_lua, upgrade_path, Enhancement,
+    EnhancementVariant,
+};
 pub use extract::{
-    build_unit_summary, unit_id_from_lua, unit_summary_from_file, weapon_from_lua,
-    weapons_from_unit_lua,
+    build_unit_summary, unit_id_from_lua, unit_stats_from_lua, unit_summary_from_file,
+    weapon_from_lua, weapons_from_unit_lua,
 };
-pub use projectile::{normalize_projectile_path, projectile_from_lua, ProjectileData};
+pub use flight::{reaches_before_lifetime, time_of_flight_sec};
+pub use friendly::{friendly_fire, FriendlyFire};
+pub use montecarlo::{monte_carlo, unit_seed, MonteCarloSummary, Spread};
+pub use projectile::{
+    normalize_projectile_path, projectile_from_lua, resolve_projectile_tree, ProjectileData,
+    ProjectileFlight, ProjectileNode, ProjectileStatus,
+};
+pub use range::{dps_at_range, dps_by_range, RangeBand};
+pub use role::{intercept_target_from_lua, weapon_role_from_lua, InterceptTarget, WeaponRole};
 use serde::{Deserialize, Serialize};
+pub use timeline::{unit_timeline, StateSpan, UnitTimeline};
+pub use ttk::{
+    damage_timeline, time_to_collapse, time_to_kill, ttk_matrix, DamageEvent, TtkMatrix, TtkTarget,
+};
+pub use turret::{
+    acquisition_time, switching_dps, turret_from_lua, yaw_coverage_issue, Acquisition, TurretStats,
+};
+pub use veterancy::{stats_at_vet_level, veterancy_table, VetLevelStats};
 
 /// Identifies a unit blueprint (ID or name).
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -20,7 +70,7 @@ pub struct UnitId {
 /// Declared weapon stats from blueprint.
 /// FAF engine uses RackSalvoSize/MuzzleSalvoSize/MuzzleSalvoDelay (not ProjectilesPerOnFire, which is deprecated).
 /// Weapon Damage does not include fragments or DoT; fragment count/damage come from projectiles data.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct WeaponDeclared {
     pub weapon_bp_id: String,
     /// Direct hit damage from weapon blueprint.
@@ -38,7 +88,11 @@ pub struct WeaponDeclared {
     pub projectiles_per_fire: u32,
     pub rate_of_fire: f64,
     pub muzzle_velocity: Option<f64>,
+    /// MaxRadius: farthest distance the weapon can fire at.
     pub range: f64,
+    /// MinRadius: closest distance the weapon can fire at (artillery dead zone).
+    #[serde(default)]
+    pub min_range: f64,
     pub salvo_size: Option<u32>,
     pub salvo_delay: Option<f64>,
     pub reload_time: Option<f64>,
@@ -50,12 +104,196 @@ pub struct WeaponDeclared {
     pub muzzle_salvo_size: Option<u32>,
     /// MuzzleSalvoDelay: delay between muzzle shots. From FAF blueprint.
     pub muzzle_salvo_delay: Option<f64>,
+    /// Turreted (older data: TurretCapable).
     pub turret_capable: bool,
     pub target_categories: Vec<String>,
+    /// FiringRandomness: lateral velocity spread added to each shot. Drives the hit-chance estimate.
+    #[serde(default)]
+    pub firing_randomness: Option<f64>,
+    /// BallisticArc (RULEUBA_None / RULEUBA_LowArc / RULEUBA_HighArc); selects the flight-time solution.
+    #[serde(default)]
+    pub ballistic_arc: Option<String>,
+    /// Label (e.g. "RightZephyr"); names the weapon for enhancement effects.
+    #[serde(default)]
+    pub label: Option<String>,
+    /// EnabledByEnhancement: weapon only exists once this enhancement is installed.
+    #[serde(default)]
+    pub enabled_by_enhancement: Option<String>,
+    /// ArtilleryShieldBlocks: artillery shells from this weapon are stopped by shields.
+    #[serde(default)]
+    pub artillery_shield_blocks: bool,
+    /// Fired projectile and its resolved fragment tree (set when projectile data was loaded).
+    #[serde(default)]
+    pub projectile_tree: Option<ProjectileNode>,
+    /// Flight fields of the fired projectile (set when projectile data was loaded).
+    #[serde(default)]
+    pub flight: Option<ProjectileFlight>,
+    /// Combat, death, dummy or defensive; only combat weapons count toward DPS.
+    #[serde(default)]
+    pub role: WeaponRole,
+    /// What a defensive weapon shoots down (None for other roles).
+    #[serde(default)]
+    pub intercepts: Option<InterceptTarget>,
+    /// Yaw/pitch arcs and speeds (None for fixed weapons).
+    #[serde(default)]
+    pub turret: Option<TurretStats>,
+    /// FiringTolerance: degrees off target at which the weapon may already fire.
+    #[serde(default)]
+    pub firing_tolerance: Option<f64>,
+    /// WeaponCategory (e.g. "Direct Fire", "Artillery", "Bomb").
+    #[serde(default)]
+    pub weapon_category: Option<String>,
+    /// BombDropThreshold: how close (ogrids) the drop point must be before bombs are released.
+    #[serde(default)]
+    pub bomb_drop_threshold: Option<f64>,
+    /// DamageFriendly: splash also damages own units (true when the blueprint omits it).
+    #[serde(default = "default_true")]
+    pub damage_friendly: bool,
+    /// CollideFriendly: projectiles stop on own units in their path.
+    #[serde(default)]
+    pub collide_friendly: bool,
+    /// RackSalvoChargeTime: wind-up before each rack salvo fires.
+    #[serde(default)]
+    pub rack_salvo_charge_time: Option<f64>,
+    /// WeaponUnpacks: the weapon deploys before firing and packs up when idle.
+    #[serde(default)]
+    pub weapon_unpacks: bool,
+    /// WeaponUnpackAnimationRate: playback rate of the unpack animation (1.0 = authored speed).
+    #[serde(default)]
+    pub unpack_animation_rate: Option<f64>,
+    /// WeaponRepackTimeout: seconds without a target before an unpacked weapon packs up.
+    #[serde(default)]
+    pub weapon_repack_timeout: Option<f64>,
+    /// RackReloadTimeout: seconds without a target before a partly fired rack is reloaded.
+    #[serde(default)]
+    pub rack_reload_timeout: Option<f64>,
+    /// TurretBoneYaw: weapons naming the same yaw bone share one turret.
+    #[serde(default)]
+    pub turret_bone_yaw: Option<String>,
+    /// TurretDualManipulators: the weapon also drives TurretBoneDualYaw.
+    #[serde(default)]
+    pub turret_bone_dual_yaw: Option<String>,
+    /// SlavedToBody: the weapon aims by turning the whole unit.
+    #[serde(default)]
+    pub slaved_to_body: bool,
+    /// PrefersPrimaryWeaponTarget: follows the primary weapon's target, so never pulls the turret away.
+    #[serde(default)]
+    pub prefers_primary_target: bool,
+    /// WeaponUnpackLocksMotion: the unit stands still while this weapon deploys.
+    #[serde(default)]
+    pub unpack_locks_motion: bool,
+    /// EnergyRequired: energy drawn before each salvo.
+    #[serde(default)]
+    pub energy_required: Option<f64>,
+    /// EnergyDrainPerSecond: fastest rate the weapon can draw that energy.
+    #[serde(default)]
+    pub energy_drain_per_sec: Option<f64>,
+    /// ProjectilesPerOnFire as written; None when `projectiles_per_fire` fell back to MuzzleSalvoSize.
+    #[serde(default)]
+    pub projectiles_per_on_fire: Option<u32>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl WeaponDeclared {
+    /// True when a shield bubble stops this weapon's damage: direct-fire weapons always hit the
+    /// bubble, ballistic (arcing) weapons only when ArtilleryShieldBlocks is set.
+    pub fn blocked_by_shields(&self) -> bool {
+        let arcing = matches!(
+            self.ballistic_arc.as_deref(),
+            Some("RULEUBA_LowArc") | Some("RULEUBA_HighArc")
+        );
+        !arcing || self.artillery_shield_blocks
+    }
+
+    /// Bomb weapons are released on passes over the target (WeaponCategory "Bomb" or a BombDropThreshold).
+    pub fn is_bomb(&self) -> bool {
+        self.weapon_category
+            .as_deref()
+            .map(|c| c.eq_ignore_ascii_case("Bomb"))
+            .unwrap_or(false)
+            || self.bomb_drop_threshold.is_some()
+    }
+
+    /// Name to show for the weapon at `index` in its unit's Weapon array: the BlueprintId, else
+    /// the Label, else the index. Real weapons rarely have a BlueprintId.
+    pub fn display_name(&self, index: usize) -> String {
+        if self.weapon_bp_id != "unknown" {
+            return self.weapon_bp_id.clone();
+        }
+        match &self.label {
+            Some(l) if !l.is_empty() => l.clone(),
+            _ => format!("weapon {}", index + 1),
+        }
+    }
+}
+
+/// Unit-level blueprint stats that are not tied to a weapon (footprint, mobility, defense).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnitStats {
+    /// SizeX: footprint width in ogrids.
+    pub size_x: Option<f64>,
+    /// SizeZ: footprint length in ogrids.
+    pub size_z: Option<f64>,
+    /// Physics.MaxSpeed, or Air.MaxAirspeed for air units.
+    pub max_speed: Option<f64>,
+    /// Physics.MotionType (e.g. RULEUMT_Land, RULEUMT_Air, RULEUMT_None for structures).
+    pub motion_type: Option<String>,
+    /// Defense.MaxHealth (falls back to Defense.Health).
+    pub max_health: Option<f64>,
+    /// Defense.RegenRate: health regenerated per second.
+    pub regen_rate: Option<f64>,
+    /// Defense.ArmorType (e.g. Normal, Light, Structure).
+    pub armor_type: Option<String>,
+    /// Blueprint Categories (TECH1, LAND, DIRECTFIRE, ...); used to select unit sets.
+    pub categories: Vec<String>,
+    /// Veteran.Level1..Level5 thresholds.
+    pub veteran_levels: Vec<f64>,
+    /// Defense.Shield, when the unit projects a shield.
+    pub shield: Option<ShieldStats>,
+    /// Economy.BuildCostMass.
+    pub build_cost_mass: Option<f64>,
+    /// Economy.BuildCostEnergy.
+    pub build_cost_energy: Option<f64>,
+    /// Economy.BuildTime (build points).
+    pub build_time: Option<f64>,
+    /// Air table of flying units (airspeed, turn rate, break-off distance).
+    pub air: Option<AirStats>,
+    /// Economy.ProductionPerSecondEnergy: the unit's own energy income.
+    pub energy_production: Option<f64>,
+    /// Economy.StorageEnergy: the unit's own energy storage.
+    pub energy_storage: Option<f64>,
+}
+
+/// Shield bubble stats from Defense.Shield.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShieldStats {
+    /// ShieldMaxHealth.
+    pub max_health: f64,
+    /// ShieldRegenRate: shield health regenerated per second while up.
+    pub regen_rate: f64,
+    /// ShieldRechargeTime: seconds the shield stays down after collapsing.
+    pub recharge_time: f64,
+    /// ShieldSize: bubble diameter in ogrids.
+    pub size: f64,
+}
+
+impl UnitStats {
+    /// True when the unit carries every category in `required` (case-insensitive).
+    pub fn has_categories(&self, required: &[String]) -> bool {
+        required.iter().all(|r| {
+            self.categories
+                .iter()
+                .any(|c| c.eq_ignore_ascii_case(r.trim()))
+        })
+    }
 }
 
 /// Computed effective stats for one weapon.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct WeaponEffective {
     pub weapon_bp_id: String,
     pub nominal_dps: f64,
@@ -65,6 +303,24 @@ pub struct WeaponEffective {
     pub salvo_duration_sec: f64,
     pub reload_sec: f64,
     pub target_class_modifiers: Vec<TargetClassDps>,
+    /// Projectile time of flight to MaxRadius (None without speed data).
+    #[serde(default)]
+    pub time_of_flight_sec: Option<f64>,
+    /// Whether the projectile reaches MaxRadius before its Lifetime expires (None without Lifetime).
+    #[serde(default)]
+    pub reaches_max_range: Option<bool>,
+    /// Turret time to bring the weapon onto a new target (None for fixed weapons).
+    #[serde(default)]
+    pub acquisition: Option<Acquisition>,
+    /// Worst-case acquisition plus time of flight to MaxRadius: delay before the first hit on a new target.
+    #[serde(default)]
+    pub first_shot_latency_sec: f64,
+    /// Effective DPS when the weapon switches target every `target_switch_interval_secs`.
+    #[serde(default)]
+    pub switching_dps: f64,
+    /// Bombing-pass model; when set, effective DPS and cycle time come from it instead of RateOfFire.
+    #[serde(default)]
+    pub attack_run: Option<AttackRun>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -84,13 +340,100 @@ pub struct UnitSummary {
     pub anomalies: Vec<crate::anomaly::Anomaly>,
     /// When set, this is the declared DPS for the unit (e.g. from --declared-dps JSON); used for comparison with sum(effective).
     pub declared_dps_override: Option<f64>,
+    /// Unit-level stats (footprint, speed). Missing in scans stored before these were extracted.
+    #[serde(default)]
+    pub stats: UnitStats,
+    /// Unit DPS by engagement distance (piecewise constant over MinRadius/MaxRadius edges).
+    #[serde(default)]
+    pub range_bands: Vec<RangeBand>,
+    /// Health, regen and effective HP at vet levels 0..=5 (empty when the unit has no health).
+    #[serde(default)]
+    pub veterancy: Vec<VetLevelStats>,
+    /// One entry per enhancement upgrade path (commanders and SCUs only).
+    #[serde(default)]
+    pub variants: Vec<EnhancementVariant>,
+    /// DPS and effective HP per unit of build cost.
+    #[serde(default)]
+    pub cost_efficiency: CostEfficiency,
+    /// Defensive weapons rated by interceptions per minute.
+    #[serde(default)]
+    pub interceptors: Vec<Interceptor>,
+    /// Cumulative damage curve, best N-second windows and alpha strike.
+    #[serde(default)]
+    pub burst: BurstProfile,
+    /// Friendly-fire estimates for combat weapons that splash or collide with own units.
+    #[serde(default)]
+    pub friendly_fire: Vec<FriendlyFire>,
+    /// Seeded Monte Carlo spread of DPS and TTK (scans run with --runs).
+    #[serde(default)]
+    pub monte_carlo: Option<MonteCarloSummary>,
 }
 
-/// Total damage per shot: weapon Damage + InitialDamage + (fragment_count * fragment_damage). Weapon blueprint damage does not include fragments or DoT.
+impl UnitSummary {
+    /// Sum of effective DPS over combat weapons.
+    pub fn total_dps(&self) -> f64 {
+        self.combat().map(|(_, e)| e.effective_dps).sum()
+    }
+
+    /// Combat weapons paired with their effective stats.
+    pub fn combat(&self) -> impl Iterator<Item = (&WeaponDeclared, &WeaponEffective)> {
+        self.weapons
+            .iter()
+            .zip(self.effective.iter())
+            .filter(|(w, _)| w.role.is_combat())
+    }
+
+    /// Owned copies of the combat weapons and their effective stats, index-aligned for the scheduler.
+    pub fn combat_weapons(&self) -> (Vec<WeaponDeclared>, Vec<WeaponEffective>) {
+        self.combat().map(|(w, e)| (w.clone(), e.clone())).unzip()
+    }
+
+    /// Summary with the given weapons and stats and nothing derived, for model tests.
+    #[cfg(test)]
+    pub(crate) fn for_test(
+        id: &str,
+        weapons: Vec<(WeaponDeclared, WeaponEffective)>,
+        stats: UnitStats,
+    ) -> Self {
+        let (weapons, effective) = weapons.into_iter().unzip();
+        Self {
+            unit_id: UnitId {
+                id: id.to_string(),
+                name: None,
+            },
+            blueprint_path: String::new(),
+            weapons,
+            effective,
+            anomalies: vec![],
+            declared_dps_override: None,
+            stats,
+            range_bands: vec![],
+            veterancy: vec![],
+            variants: vec![],
+            cost_efficiency: Default::default(),
+            interceptors: vec![],
+            burst: Default::default(),
+            friendly_fire: vec![],
+            monte_carlo: None,
+        }
+    }
+}
+
+/// Total damage per shot: impact damage + InitialDamage. Weapon blueprint damage does not include fragments or DoT.
+/// A projectile that fragments on impact deals only its fragments' damage (the leaves of the resolved
+/// projectile tree, else fragment_count * fragment_damage); otherwise the weapon Damage lands.
 pub fn total_damage_per_shot(w: &WeaponDeclared) -> f64 {
-    let base = w.damage + w.initial_damage.unwrap_or(0.0);
-    let frag = w.fragment_count.unwrap_or(0) as f64 * w.fragment_damage.unwrap_or(0.0);
-    base + frag
+    let impact = match (&w.projectile_tree, w.fragment_count, w.fragment_damage) {
+        (Some(tree), _, _) if tree.splits() => tree.impact_damage(),
+        (None, Some(n), Some(d)) if n > 0 => n as f64 * d,
+        _ => w.damage,
+    };
+    impact + w.initial_damage.unwrap_or(0.0)
+}
+
+/// Damage of one fire event: every projectile of the shot, fragments included.
+pub fn volley_damage(w: &WeaponDeclared) -> f64 {
+    total_damage_per_shot(w) * w.projectiles_per_fire as f64
 }
 
 /// Compute nominal DPS: (total_damage_per_shot * projectiles) * rate, where rate is shots per second.
@@ -123,7 +466,58 @@ pub fn salvo_duration_sec(salvo_size: Option<u32>, salvo_delay: Option<f64>) ->
     }
 }
 
+/// Reload interval as the engine runs it: rounded to the nearest whole tick, at least one tick.
+/// `RateOfFire = 10/30` is a 3 s cycle, 30 ticks exactly; `RateOfFire = 0.3` → 3.333 s → 33 ticks
+/// = 3.3 s.
+pub fn tick_interval_sec(sec: f64) -> f64 {
+    if sec <= 0.0 {
+        return 0.0;
+    }
+    (sec / SIM_TICK_SECS).round().max(1.0) * SIM_TICK_SECS
+}
+
+/// Delay between salvo shots rounded to the nearest tick; may round to zero (same-tick shots).
+pub fn tick_delay_sec(sec: f64) -> f64 {
+    if sec <= 0.0 {
+        return 0.0;
+    }
+    (sec / SIM_TICK_SECS).round() * SIM_TICK_SECS
+}
+
+/// `cycle_time_sec` under `timing`.
+pub fn cycle_time_with(rate_of_fire: f64, reload_time: Option<f64>, timing: TimingMode) -> f64 {
+    let cycle = cycle_time_sec(rate_of_fire, reload_time);
+    match timing {
+        TimingMode::Continuous => cycle,
+        TimingMode::Ticks => tick_interval_sec(cycle),
+    }
+}
+
+/// `salvo_duration_sec` under `timing`: each delay is rounded to ticks before multiplying.
+pub fn salvo_duration_with(
+    salvo_size: Option<u32>,
+    salvo_delay: Option<f64>,
+    timing: TimingMode,
+) -> f64 {
+    let delay = match timing {
+        TimingMode::Continuous => salvo_delay,
+        TimingMode::Ticks => salvo_delay.map(tick_delay_sec),
+    };
+    salvo_duration_sec(salvo_size, delay)
+}
+
+/// Rate of fire a weapon actually achieves on engine ticks, as a fraction of its declared rate
+/// (below 1.0 = rounding slows it). None when the weapon has no cycle.
+pub fn tick_rate_ratio(rate_of_fire: f64, reload_time: Option<f64>) -> Option<f64> {
+    let declared = cycle_time_sec(rate_of_fire, reload_time);
+    if declared <= 0.0 {
+        return None;
+    }
+    Some(declared / tick_interval_sec(declared))
+}
+
 /// Effective DPS accounting for salvo and reload: damage per cycle / cycle time.
+/// With `TimingMode::Ticks` the cycle and salvo delays are rounded to engine ticks first.
 pub fn effective_dps(
     damage: f64,
     projectiles: u32,
@@ -131,12 +525,13 @@ pub fn effective_dps(
     reload_time: Option<f64>,
     salvo_size: Option<u32>,
     salvo_delay: Option<f64>,
+    timing: TimingMode,
 ) -> f64 {
-    let cycle = cycle_time_sec(rate_of_fire, reload_time);
+    let cycle = cycle_time_with(rate_of_fire, reload_time, timing);
     if cycle <= 0.0 {
         return 0.0;
     }
-    let salvo_dur = salvo_duration_sec(salvo_size, salvo_delay);
+    let salvo_dur = salvo_duration_with(salvo_size, salvo_delay, timing);
     let shots_per_cycle = if salvo_size.unwrap_or(1) > 0 {
         salvo_size.unwrap_or(1)
     } else {
@@ -173,7 +568,30 @@ mod tests {
 
     #[test]
     fn effective_dps_simple() {
-        let d = effective_dps(100.0, 1, 2.0, None, None, None);
+        let d = effective_dps(100.0, 1, 2.0, None, None, None, TimingMode::Continuous);
         assert!(d > 0.0 && d < 250.0);
     }
+
+    #[test]
+    fn stored_weapon_without_damage_friendly_keeps_friendly_splash() {
+        let mut v = serde_json::to_value(WeaponDeclared::default()).unwrap();
+        v.as_object_mut().unwrap().remove("damage_friendly");
+        let w: WeaponDeclared = serde_json::from_value(v).unwrap();
+        assert!(w.damage_friendly);
+    }
+
+    #[test]
+    fn tick_timing_rounds_intervals() {
+        // 1/0.3 = 3.33 s runs as 33 ticks; 1/15 s rounds up to one tick.
+        assert!((tick_interval_sec(1.0 / 0.3) - 3.3).abs() < 1e-9);
+        assert!((tick_interval_sec(1.0 / 15.0) - 0.1).abs() < 1e-9);
+        assert!((tick_delay_sec(0.04) - 0.0).abs() < 1e-9);
+        let cont = effective_dps(10.0, 1, 15.0, None, None, None, TimingMode::Continuous);
+        let ticks = effective_dps(10.0, 1, 15.0, None, None, None, TimingMode::Ticks);
+        assert!((cont - 150.0).abs() < 1e-6);
+        assert!((ticks - 100.0).abs() < 1e-6);
+        let ratio = tick_rate_ratio(15.0, None).unwrap();
+        assert!((ratio - 2.0 / 3.0).abs() < 1e-9);
+        assert!((tick_rate_ratio(10.0 / 30.0, None).unwrap() - 1.0).abs() < 1e-9);
+    }
 }
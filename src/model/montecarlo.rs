@@ -0,0 +1,247 @@
+//! Seeded Monte Carlo over the scheduler timeline: spread of effective DPS and time-to-kill.
+//!
+//! The fire schedule is deterministic and computed once. Each run then replays it shot by shot,
+//! drawing the random parts from one seeded generator:
+//! - muzzle spread: a uniform disc of FiringRandomness × flight time;
+//! - launch speed: uniform within ±InitialSpeedRange of MuzzleVelocity. This rescales flight
+//!   time, and for arcing weapons moves the landing point along the line of fire;
+//! - target movement: a uniform disc of target speed × flight time.
+//!
+//! A shot hits when it lands within the target footprint radius plus DamageRadius, the same
+//! rule as the analytical hit model. Every weapon fires at its MaxRadius.
+
+use super::ttk::unit_schedule;
+use super::{
+    flight_time_sec, time_to_kill, volley_damage, DamageEvent, TargetProfile, TtkTarget, UnitStats,
+    UnitSummary, WeaponDeclared,
+};
+use crate::config::TimingMode;
+use crate::util::SeededRng;
+use serde::{Deserialize, Serialize};
+
+/// Mean and 5th/95th percentiles of a sampled quantity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Spread {
+    pub mean: f64,
+    pub p5: f64,
+    pub p95: f64,
+}
+
+impl Spread {
+    /// Nearest-rank percentiles of `samples`; zero when empty.
+    pub fn of(samples: &[f64]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let rank = |p: f64| {
+            let k = (p * sorted.len() as f64).ceil() as usize;
+            sorted[k.clamp(1, sorted.len()) - 1]
+        };
+        Self {
+            mean: sorted.iter().fold(0.0, |sum, x| sum + x) / sorted.len() as f64,
+            p5: rank(0.05),
+            p95: rank(0.95),
+        }
+    }
+}
+
+/// Monte Carlo result for one unit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonteCarloSummary {
+    pub runs: u32,
+    /// Seed of this unit's generator (derived from the scan seed and the unit ID).
+    pub seed: u64,
+    /// Landed damage over the simulation window, per second.
+    pub effective_dps: Spread,
+    /// Time to kill the target, over the runs that killed it within the horizon.
+    pub ttk_sec: Option<Spread>,
+    /// Fraction of runs that killed the target within the horizon.
+    pub kill_fraction: f64,
+}
+
+/// Generator seed for one unit: the scan seed mixed with the unit ID (FNV-1a), so a unit's
+/// result does not depend on which other units are in the scan or their order.
+pub fn unit_seed(seed: u64, unit_id: &str) -> u64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in unit_id.to_lowercase().bytes() {
+        h ^= b as u64;
+        h = h.wrapping_mul(0x0100_0000_01b3);
+    }
+    seed ^ h
+}
+
+fn is_arcing(w: &WeaponDeclared) -> bool {
+    matches!(
+        w.ballistic_arc.as_deref(),
+        Some("RULEUBA_LowArc") | Some("RULEUBA_HighArc")
+    )
+}
+
+/// Impact time offset and hit for one shot at MaxRadius.
+fn sample_shot(
+    w: &WeaponDeclared,
+    target: &TargetProfile,
+    tof: f64,
+    rng: &mut SeededRng,
+) -> (f64, bool) {
+    let launch = w.muzzle_velocity.unwrap_or(0.0);
+    let speed_range = w
+        .flight
+        .as_ref()
+        .and_then(|f| f.initial_speed_range)
+        .unwrap_or(0.0)
+        .max(0.0);
+    // Launch speed as a multiple of nominal; floored so a wide range cannot stop the shell.
+    let factor = if launch > 0.0 && speed_range > 0.0 {
+        ((launch + speed_range * (2.0 * rng.next_f64() - 1.0)) / launch).max(0.1)
+    } else {
+        1.0
+    };
+    let (tof, along) = if is_arcing(w) {
+        // Same elevation: flight time scales with speed, ballistic range with speed squared.
+        (tof * factor, w.range * (factor * factor - 1.0))
+    } else {
+        (tof / factor, 0.0)
+    };
+    let spread = w.firing_randomness.unwrap_or(0.0).max(0.0) * tof;
+    let (sx, sy) = rng.point_in_disc(spread);
+    let (mx, my) = rng.point_in_disc(target.speed * tof);
+    let (dx, dy) = (sx + mx + along, sy + my);
+    let hit_radius = target.radius + w.damage_radius.max(0.0);
+    (tof, dx * dx + dy * dy <= hit_radius * hit_radius)
+}
+
+/// `runs` seeded replays of the attacker's combat-weapon schedule against `target`.
+/// DPS counts damage landing within `window_sec`; TTK replays up to `horizon_sec`.
+pub fn monte_carlo(
+    attacker: &UnitSummary,
+    target: &UnitStats,
+    runs: u32,
+    seed: u64,
+    window_sec: f64,
+    horizon_sec: f64,
+    timing: TimingMode,
+) -> MonteCarloSummary {
+    let seed = unit_seed(seed, &attacker.unit_id.id);
+    let span = window_sec.max(horizon_sec);
+    let (weapons, schedule) = unit_schedule(attacker, span, timing);
+    let profile = TargetProfile::from_stats(target);
+    let ttk_target = TtkTarget::from_stats(target);
+    let tofs: Vec<f64> = weapons
+        .iter()
+        .map(|w| flight_time_sec(w, w.range))
+        .collect();
+
+    let mut rng = SeededRng::new(seed);
+    let mut dps = Vec::with_capacity(runs as usize);
+    let mut ttk = Vec::new();
+    for _ in 0..runs {
+        let mut timeline: Vec<DamageEvent> = Vec::with_capacity(schedule.events.len());
+        for e in &schedule.events {
+            let w = &weapons[e.weapon_index];
+            let (tof, hit) = sample_shot(w, &profile, tofs[e.weapon_index], &mut rng);
+            let impact = e.time_sec + tof;
+            if hit && impact <= span {
+                timeline.push(DamageEvent {
+                    time_sec: impact,
+                    damage: volley_damage(w),
+                    shield_blocked: w.blocked_by_shields(),
+                });
+            }
+        }
+        timeline.sort_by(|a, b| a.time_sec.total_cmp(&b.time_sec));
+        if window_sec > 0.0 {
+            let landed: f64 = timeline
+                .iter()
+                .filter(|d| d.time_sec <= window_sec)
+                .fold(0.0, |sum, d| sum + d.damage);
+            dps.push(landed / window_sec);
+        }
+        timeline.retain(|d| d.time_sec <= horizon_sec);
+        if let Some(t) = time_to_kill(&timeline, &ttk_target) {
+            ttk.push(t);
+        }
+    }
+    MonteCarloSummary {
+        runs,
+        seed,
+        effective_dps: Spread::of(&dps),
+        ttk_sec: (!ttk.is_empty()).then(|| Spread::of(&ttk)),
+        kill_fraction: if runs > 0 {
+            ttk.len() as f64 / runs as f64
+        } else {
+            0.0
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::WeaponEffective;
+
+    fn unit(firing_randomness: f64) -> UnitSummary {
+        let w = WeaponDeclared {
+            weapon_bp_id: "Gun".to_string(),
+            damage: 50.0,
+            projectiles_per_fire: 1,
+            rate_of_fire: 1.0,
+            range: 20.0,
+            muzzle_velocity: Some(20.0),
+            firing_randomness: Some(firing_randomness),
+            ..Default::default()
+        };
+        let e = WeaponEffective {
+            weapon_bp_id: "Gun".to_string(),
+            effective_dps: 50.0,
+            cycle_time_sec: 1.0,
+            shots_per_cycle: 1,
+            time_of_flight_sec: Some(1.0),
+            ..Default::default()
+        };
+        let stats = UnitStats {
+            size_x: Some(1.0),
+            size_z: Some(1.0),
+            max_health: Some(500.0),
+            ..Default::default()
+        };
+        UnitSummary::for_test("uel0101", vec![(w, e)], stats)
+    }
+
+    #[test]
+    fn no_randomness_gives_a_point_spread() {
+        let u = unit(0.0);
+        let mc = monte_carlo(&u, &u.stats, 20, 1, 10.0, 60.0, TimingMode::Continuous);
+        // Shots at 0..9 land at 1..10: 500 damage over 10 s.
+        assert_eq!(mc.effective_dps.p5, mc.effective_dps.p95);
+        assert!((mc.effective_dps.mean - 50.0).abs() < 1e-9);
+        let ttk = mc.ttk_sec.unwrap();
+        assert!((ttk.mean - 10.0).abs() < 1e-9);
+        assert_eq!(mc.kill_fraction, 1.0);
+    }
+
+    #[test]
+    fn seeded_runs_are_reproducible_and_spread_out() {
+        let u = unit(1.0);
+        let a = monte_carlo(&u, &u.stats, 200, 42, 30.0, 120.0, TimingMode::Continuous);
+        let b = monte_carlo(&u, &u.stats, 200, 42, 30.0, 120.0, TimingMode::Continuous);
+        assert_eq!(a, b);
+        assert!(a.effective_dps.p5 < a.effective_dps.mean);
+        assert!(a.effective_dps.mean < a.effective_dps.p95);
+        assert!(a.effective_dps.p95 <= 50.0 + 1e-9);
+        assert_ne!(
+            monte_carlo(&u, &u.stats, 200, 43, 30.0, 120.0, TimingMode::Continuous),
+            a
+        );
+    }
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let samples: Vec<f64> = (1..=100).map(f64::from).collect();
+        let s = Spread::of(&samples);
+        assert_eq!((s.p5, s.p95), (5.0, 95.0));
+        assert!((s.mean - 50.5).abs() < 1e-9);
+    }
+}
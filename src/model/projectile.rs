@@ -2,6 +2,8 @@
 //! The only reliable way to get actual fragment count is from projectiles data; weapon damage does not include fragments or DoT.
 
 use crate::parser::LuaValue;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Parsed projectile data from a *_proj.bp file. Used to resolve fragment count (and optionally fragment damage) for weapons.
 #[derive(Debug, Clone, Default)]
@@ -12,6 +14,29 @@ pub struct ProjectileData {
     pub fragment_id: Option<String>,
     /// Damage from this projectile (e.g. when used as a fragment); not all FAF bps define it.
     pub damage: Option<f64>,
+    /// Flight fields from Physics.
+    pub flight: ProjectileFlight,
+}
+
+/// Projectile Physics flight fields. Unset fields are None (engine defaults are not guessed).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectileFlight {
+    /// InitialSpeed: launch speed when the weapon has no MuzzleVelocity.
+    pub initial_speed: Option<f64>,
+    /// InitialSpeedRange: launch speed varies uniformly by up to this much either way.
+    pub initial_speed_range: Option<f64>,
+    /// MaxSpeed: cap applied while accelerating.
+    pub max_speed: Option<f64>,
+    /// Acceleration in ogrids/s².
+    pub acceleration: Option<f64>,
+    /// Lifetime in seconds before the projectile expires.
+    pub lifetime: Option<f64>,
+    pub track_target: Option<bool>,
+    /// TurnRate in degrees per second.
+    pub turn_rate: Option<f64>,
+    /// DetonateBelowHeight: projectile detonates (or splits) below this height.
+    pub detonate_below_height: Option<f64>,
 }
 
 /// Normalize projectile path for lookup: lowercase, consistent slashes.
@@ -27,16 +52,217 @@ pub fn normalize_projectile_path(path: &str) -> String {
 }
 
 /// Extract projectile data from a parsed ProjectileBlueprint root table.
-/// Reads Physics.Fragments, Physics.FragmentId, and optional Damage (for fragment projectiles).
+/// Reads Physics.Fragments, Physics.FragmentId, optional Damage (for fragment projectiles) and the flight fields.
 /// Returns Default when no Physics or no fragment/damage fields (so every projectile can be stored for lookup).
 pub fn projectile_from_lua(root: &LuaValue) -> Option<ProjectileData> {
     let physics = root.get_table("Physics")?;
     let fragment_count = physics.get_num("Fragments").map(|n| n as u32);
     let fragment_id = physics.get_str("FragmentId").map(str::to_string);
     let damage = root.get_num("Damage").or_else(|| physics.get_num("Damage"));
+    let flight = ProjectileFlight {
+        initial_speed: physics.get_num("InitialSpeed"),
+        initial_speed_range: physics.get_num("InitialSpeedRange"),
+        max_speed: physics.get_num("MaxSpeed"),
+        acceleration: physics.get_num("Acceleration"),
+        lifetime: physics.get_num("Lifetime"),
+        track_target: physics.get_bool("TrackTarget"),
+        turn_rate: physics.get_num("TurnRate"),
+        detonate_below_height: physics.get_num("DetonateBelowHeight"),
+    };
     Some(ProjectileData {
         fragment_count,
         fragment_id,
         damage,
+        flight,
     })
 }
+
+/// How a node of a projectile tree was resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProjectileStatus {
+    Resolved,
+    /// The referenced projectile blueprint was not found.
+    Missing,
+    /// The projectile already appears higher up the chain (FragmentId loop); not expanded.
+    Cycle,
+}
+
+/// One projectile in a weapon's fragment tree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectileNode {
+    /// Normalized projectile path (see `normalize_projectile_path`).
+    pub projectile_id: String,
+    /// Copies spawned per parent projectile (Physics.Fragments of the parent; 1 for the fired projectile).
+    pub count: u32,
+    /// Damage per copy: the projectile's own Damage, else inherited from its parent (the weapon for the root).
+    pub damage: f64,
+    pub inherited_damage: bool,
+    pub status: ProjectileStatus,
+    pub fragments: Vec<ProjectileNode>,
+}
+
+impl ProjectileNode {
+    /// The projectile splits into resolved fragments on impact instead of dealing its own damage.
+    pub fn splits(&self) -> bool {
+        self.fragments
+            .iter()
+            .any(|f| f.status == ProjectileStatus::Resolved)
+    }
+
+    /// Damage one copy of this node deals on impact: a projectile that splits deals only what its
+    /// fragments deal (copies multiply down the tree), so only leaves count. Missing and cyclic
+    /// fragments contribute nothing; a node without resolved fragments deals its own damage.
+    pub fn impact_damage(&self) -> f64 {
+        if !self.splits() {
+            return self.damage;
+        }
+        self.fragments
+            .iter()
+            .filter(|f| f.status == ProjectileStatus::Resolved)
+            .map(|f| f.count as f64 * f.impact_damage())
+            .sum()
+    }
+
+    /// This node and all descendants, depth-first.
+    pub fn nodes(&self) -> Vec<&ProjectileNode> {
+        let mut out = vec![self];
+        for f in &self.fragments {
+            out.extend(f.nodes());
+        }
+        out
+    }
+}
+
+/// Resolve the full fragment tree of `projectile_id` fired with `weapon_damage`.
+/// Follows Physics.FragmentId recursively; a projectile already on the current chain is marked as a cycle.
+pub fn resolve_projectile_tree(
+    projectile_id: &str,
+    weapon_damage: f64,
+    map: &HashMap<String, ProjectileData>,
+) -> ProjectileNode {
+    let mut chain = Vec::new();
+    resolve_node(
+        &normalize_projectile_path(projectile_id),
+        1,
+        weapon_damage,
+        true,
+        map,
+        &mut chain,
+    )
+}
+
+fn resolve_node(
+    key: &str,
+    count: u32,
+    parent_damage: f64,
+    is_root: bool,
+    map: &HashMap<String, ProjectileData>,
+    chain: &mut Vec<String>,
+) -> ProjectileNode {
+    let mut node = ProjectileNode {
+        projectile_id: key.to_string(),
+        count,
+        damage: parent_damage,
+        inherited_damage: true,
+        status: ProjectileStatus::Resolved,
+        fragments: Vec::new(),
+    };
+    if chain.iter().any(|k| k == key) {
+        node.status = ProjectileStatus::Cycle;
+        return node;
+    }
+    let Some(data) = map.get(key) else {
+        node.status = ProjectileStatus::Missing;
+        return node;
+    };
+    // The fired projectile carries the weapon's damage; fragments may declare their own.
+    if let (false, Some(d)) = (is_root, data.damage) {
+        node.damage = d;
+        node.inherited_damage = false;
+    }
+    if let Some(frag_id) = &data.fragment_id {
+        chain.push(key.to_string());
+        node.fragments.push(resolve_node(
+            &normalize_projectile_path(frag_id),
+            data.fragment_count.unwrap_or(1),
+            node.damage,
+            false,
+            map,
+            chain,
+        ));
+        chain.pop();
+    }
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proj(
+        fragment_id: Option<&str>,
+        fragments: Option<u32>,
+        damage: Option<f64>,
+    ) -> ProjectileData {
+        ProjectileData {
+            fragment_count: fragments,
+            fragment_id: fragment_id.map(str::to_string),
+            damage,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn nested_fragments_multiply_and_inherit_damage() {
+        let map: HashMap<String, ProjectileData> = [
+            (
+                "projectiles/a/a_proj.bp",
+                proj(Some("/projectiles/b/b_proj.bp"), Some(4), None),
+            ),
+            (
+                "projectiles/b/b_proj.bp",
+                proj(Some("/projectiles/c/c_proj.bp"), Some(3), None),
+            ),
+            ("projectiles/c/c_proj.bp", proj(None, None, Some(5.0))),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+        let tree = resolve_projectile_tree("/projectiles/a/a_proj.bp", 100.0, &map);
+        let b = &tree.fragments[0];
+        assert!(b.inherited_damage);
+        assert_eq!((b.count, b.damage), (4, 100.0));
+        let c = &b.fragments[0];
+        assert!(!c.inherited_damage);
+        assert_eq!((c.count, c.damage), (3, 5.0));
+        // Only the leaves land: 4 × 3 × 5.
+        assert_eq!(tree.impact_damage(), 60.0);
+        assert_eq!(tree.nodes().len(), 3);
+    }
+
+    #[test]
+    fn cycles_and_missing_targets_are_marked() {
+        let map: HashMap<String, ProjectileData> = [
+            (
+                "projectiles/a/a_proj.bp",
+                proj(Some("/projectiles/b/b_proj.bp"), Some(2), None),
+            ),
+            (
+                "projectiles/b/b_proj.bp",
+                proj(Some("/projectiles/a/a_proj.bp"), Some(2), Some(10.0)),
+            ),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+        let tree = resolve_projectile_tree("/projectiles/a/a_proj.bp", 100.0, &map);
+        let back = &tree.fragments[0].fragments[0];
+        assert_eq!(back.status, ProjectileStatus::Cycle);
+        assert!(back.fragments.is_empty());
+        // The second level's copies are the leaves: 2 × 10.
+        assert_eq!(tree.impact_damage(), 20.0);
+
+        let missing = resolve_projectile_tree("/projectiles/x/x_proj.bp", 100.0, &map);
+        assert_eq!(missing.status, ProjectileStatus::Missing);
+    }
+}
@@ -0,0 +1,122 @@
+//! Unit DPS as a function of engagement distance, from each weapon's MinRadius..MaxRadius band.
+
+use super::{WeaponDeclared, WeaponEffective};
+use serde::{Deserialize, Serialize};
+
+/// Constant-DPS stretch of the range curve: distances in [start, end] see `dps` from `weapon_ids`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RangeBand {
+    pub start: f64,
+    pub end: f64,
+    pub dps: f64,
+    pub weapon_ids: Vec<String>,
+}
+
+impl RangeBand {
+    /// No weapon can fire anywhere in this band.
+    pub fn is_dead_zone(&self) -> bool {
+        self.weapon_ids.is_empty()
+    }
+}
+
+/// Piecewise-constant unit DPS over distance from 0 to the longest MaxRadius.
+/// Band edges are every weapon's MinRadius and MaxRadius; weapons without a MaxRadius are ignored.
+pub fn dps_by_range(weapons: &[WeaponDeclared], effective: &[WeaponEffective]) -> Vec<RangeBand> {
+    let mut edges: Vec<f64> = vec![0.0];
+    for w in weapons.iter().filter(|w| w.range > 0.0) {
+        edges.push(w.min_range.clamp(0.0, w.range));
+        edges.push(w.range);
+    }
+    edges.sort_by(f64::total_cmp);
+    edges.dedup_by(|a, b| (*a - *b).abs() < 1e-9);
+    edges
+        .windows(2)
+        .map(|pair| {
+            let (start, end) = (pair[0], pair[1]);
+            let mut dps = 0.0;
+            let mut weapon_ids = Vec::new();
+            for (w, e) in weapons.iter().zip(effective.iter()) {
+                if w.range > 0.0 && w.min_range <= start && w.range >= end {
+                    dps += e.effective_dps;
+                    weapon_ids.push(w.weapon_bp_id.clone());
+                }
+            }
+            RangeBand {
+                start,
+                end,
+                dps,
+                weapon_ids,
+            }
+        })
+        .collect()
+}
+
+/// DPS at a single distance (0 beyond the curve). At a band edge the farther band wins.
+pub fn dps_at_range(bands: &[RangeBand], distance: f64) -> f64 {
+    bands
+        .iter()
+        .rev()
+        .find(|b| distance >= b.start && distance <= b.end)
+        .map(|b| b.dps)
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weapon(id: &str, min: f64, max: f64, dps: f64) -> (WeaponDeclared, WeaponEffective) {
+        (
+            WeaponDeclared {
+                weapon_bp_id: id.to_string(),
+                min_range: min,
+                range: max,
+                ..Default::default()
+            },
+            WeaponEffective {
+                weapon_bp_id: id.to_string(),
+                effective_dps: dps,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn artillery_min_radius_leaves_dead_zone() {
+        let (w, e) = weapon("Arty", 25.0, 90.0, 70.0);
+        let bands = dps_by_range(&[w], &[e]);
+        assert_eq!(bands.len(), 2);
+        assert!(bands[0].is_dead_zone());
+        assert_eq!((bands[0].start, bands[0].end), (0.0, 25.0));
+        assert_eq!(bands[1].dps, 70.0);
+    }
+
+    #[test]
+    fn overlapping_weapons_sum() {
+        let (ws, es): (Vec<_>, Vec<_>) = [
+            weapon("Gun", 0.0, 20.0, 10.0),
+            weapon("Arty", 30.0, 60.0, 40.0),
+            weapon("Aa", 0.0, 40.0, 5.0),
+        ]
+        .into_iter()
+        .unzip();
+        let bands = dps_by_range(&ws, &es);
+        assert_eq!(dps_at_range(&bands, 10.0), 15.0);
+        assert_eq!(dps_at_range(&bands, 35.0), 45.0);
+        assert!(bands.iter().all(|b| !b.is_dead_zone()));
+    }
+
+    #[test]
+    fn gap_between_weapons_is_dead_zone() {
+        let (ws, es): (Vec<_>, Vec<_>) = [
+            weapon("Gun", 0.0, 20.0, 10.0),
+            weapon("Arty", 30.0, 60.0, 40.0),
+        ]
+        .into_iter()
+        .unzip();
+        let bands = dps_by_range(&ws, &es);
+        let dead: Vec<_> = bands.iter().filter(|b| b.is_dead_zone()).collect();
+        assert_eq!(dead.len(), 1);
+        assert_eq!((dead[0].start, dead[0].end), (20.0, 30.0));
+    }
+}
@@ -0,0 +1,189 @@
+//! Weapon roles: which entries of a unit's Weapon array actually fight.
+//!
+//! Blueprints also list death explosions, air-crash impacts, dummy weapons (target markers,
+//! animation drivers) and point defenses against missiles. Those are reported but excluded
+//! from unit DPS, the cadence scheduler and time-to-kill.
+
+use crate::parser::LuaValue;
+use serde::{Deserialize, Serialize};
+
+/// What a weapon is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum WeaponRole {
+    /// Fires at enemy units; counts toward DPS.
+    #[default]
+    Combat,
+    /// Fires when the unit dies or crashes (FireOnDeath, WeaponCategory "Death", DeathWeapon/DeathImpact labels).
+    Death,
+    /// Never deals damage (DummyWeapon, Dummy labels, RateOfFire = 0).
+    Dummy,
+    /// Shoots down projectiles: WeaponCategory "Defense", or targets only missiles or torpedoes.
+    Defensive,
+}
+
+/// What a defensive weapon shoots down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InterceptTarget {
+    /// Tactical/strategic missiles (TMD, SMD).
+    Missile,
+    /// Torpedoes (anti-torpedo launchers).
+    Torpedo,
+    /// A "Defense" weapon without missile or torpedo target categories.
+    Projectile,
+}
+
+impl InterceptTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InterceptTarget::Missile => "missile",
+            InterceptTarget::Torpedo => "torpedo",
+            InterceptTarget::Projectile => "projectile",
+        }
+    }
+}
+
+impl WeaponRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WeaponRole::Combat => "combat",
+            WeaponRole::Death => "death",
+            WeaponRole::Dummy => "dummy",
+            WeaponRole::Defensive => "defensive",
+        }
+    }
+
+    pub fn is_combat(&self) -> bool {
+        *self == WeaponRole::Combat
+    }
+}
+
+/// Target categories of a weapon: the TargetCategories list plus the words of TargetRestrictOnlyAllow.
+fn target_words(table: &LuaValue) -> Vec<String> {
+    let mut words = Vec::new();
+    if let Some(list) = table.get_table("TargetCategories") {
+        for i in 1..=list.table_len().unwrap_or(0) {
+            if let Some(s) = list.get_by_index(i as u32).and_then(|v| v.as_str()) {
+                words.push(s.to_uppercase());
+            }
+        }
+    }
+    if let Some(allow) = table.get_str("TargetRestrictOnlyAllow") {
+        words.extend(
+            allow
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|w| !w.is_empty())
+                .map(str::to_uppercase),
+        );
+    }
+    words
+}
+
+/// What the weapon intercepts, if it is a projectile defense. A weapon counts when its
+/// WeaponCategory is "Defense" or when every target category names missiles or torpedoes;
+/// "Anti Navy" weapons therefore only count when they target torpedoes, not submarines.
+pub fn intercept_target_from_lua(table: &LuaValue) -> Option<InterceptTarget> {
+    let words = target_words(table);
+    let only = |allowed: &[&str], required: &str| {
+        words.iter().any(|w| w.contains(required))
+            && words.iter().all(|w| allowed.iter().any(|a| w.contains(a)))
+    };
+    let defense = table
+        .get_str("WeaponCategory")
+        .map(|c| c.eq_ignore_ascii_case("Defense"))
+        .unwrap_or(false);
+    if only(&["TORPEDO"], "TORPEDO") {
+        Some(InterceptTarget::Torpedo)
+    } else if only(&["MISSILE", "TACTICAL", "STRATEGIC"], "MISSILE") {
+        Some(InterceptTarget::Missile)
+    } else if defense {
+        Some(InterceptTarget::Projectile)
+    } else {
+        None
+    }
+}
+
+/// Classify one weapon table. Death is checked first: crash weapons are often also DummyWeapon.
+pub fn weapon_role_from_lua(table: &LuaValue) -> WeaponRole {
+    let label = table.get_str("Label").unwrap_or("").to_lowercase();
+    let category = table.get_str("WeaponCategory").unwrap_or("");
+    if table.get_bool("FireOnDeath").unwrap_or(false)
+        || category.eq_ignore_ascii_case("Death")
+        || ["death", "crash", "collision"]
+            .iter()
+            .any(|k| label.contains(k))
+    {
+        return WeaponRole::Death;
+    }
+    if intercept_target_from_lua(table).is_some() {
+        return WeaponRole::Defensive;
+    }
+    if table.get_bool("DummyWeapon").unwrap_or(false)
+        || label.contains("dummy")
+        || table.get_num("RateOfFire") == Some(0.0)
+    {
+        return WeaponRole::Dummy;
+    }
+    WeaponRole::Combat
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn role(weapon: &str) -> WeaponRole {
+        let root = crate::parser::parse_blueprint(&format!("{{ W = {} }}", weapon)).unwrap();
+        weapon_role_from_lua(root.get_table("W").unwrap())
+    }
+
+    #[test]
+    fn classifies_by_label_category_and_flags() {
+        assert_eq!(
+            role(r#"{ Label = "MainGun", WeaponCategory = "Direct Fire", RateOfFire = 1 }"#),
+            WeaponRole::Combat
+        );
+        assert_eq!(
+            role(r#"{ Label = "DeathImpact", WeaponCategory = "Death", DummyWeapon = true }"#),
+            WeaponRole::Death
+        );
+        assert_eq!(
+            role(r#"{ Label = "Nuke", FireOnDeath = true }"#),
+            WeaponRole::Death
+        );
+        assert_eq!(
+            role(r#"{ Label = "AntiMissile", WeaponCategory = "Defense" }"#),
+            WeaponRole::Defensive
+        );
+        assert_eq!(
+            role(
+                r#"{ Label = "Torpedo", WeaponCategory = "Anti Navy", TargetCategories = { "SUBMERSIBLE" } }"#
+            ),
+            WeaponRole::Combat
+        );
+        assert_eq!(
+            role(
+                r#"{ Label = "AntiTorpedo", WeaponCategory = "Anti Navy", TargetCategories = { "TORPEDO" } }"#
+            ),
+            WeaponRole::Defensive
+        );
+        assert_eq!(
+            role(r#"{ Label = "TargetPainter", DummyWeapon = true }"#),
+            WeaponRole::Dummy
+        );
+        assert_eq!(
+            role(r#"{ Label = "Turret", RateOfFire = 0 }"#),
+            WeaponRole::Dummy
+        );
+    }
+
+    #[test]
+    fn intercept_target_from_restrict_expression() {
+        let root = crate::parser::parse_blueprint(
+            r#"{ W = { Label = "AntiMissile", TargetRestrictOnlyAllow = "TACTICAL MISSILE" } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            intercept_target_from_lua(root.get_table("W").unwrap()),
+            Some(InterceptTarget::Missile)
+        );
+    }
+}
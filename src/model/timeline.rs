@@ -0,0 +1,135 @@
+//! Scheduler timeline of one unit, kept for storage, trace export and the swimlane chart.
+
+use super::UnitSummary;
+use crate::config::AnalysisConfig;
+use crate::scheduler::{self, FireEvent, Gap, StateTransition, WeaponState};
+use serde::{Deserialize, Serialize};
+
+/// Fire events, gaps and state transitions of a unit's combat weapons over the simulation window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnitTimeline {
+    pub unit_id: String,
+    pub window_sec: f64,
+    /// Weapon IDs by scheduler index (events refer to weapons by index).
+    pub weapons: Vec<String>,
+    pub events: Vec<FireEvent>,
+    pub gaps: Vec<Gap>,
+    pub transitions: Vec<StateTransition>,
+}
+
+/// A weapon state held over `[start_sec, end_sec)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StateSpan {
+    pub weapon_index: usize,
+    pub state: WeaponState,
+    pub start_sec: f64,
+    pub end_sec: f64,
+}
+
+impl UnitTimeline {
+    /// Non-idle state spans per weapon; a state still held at the end runs to the window end.
+    pub fn state_spans(&self) -> Vec<StateSpan> {
+        let mut spans = Vec::new();
+        for weapon_index in 0..self.weapons.len() {
+            let mut changes = self
+                .transitions
+                .iter()
+                .filter(|t| t.weapon_index == weapon_index)
+                .peekable();
+            while let Some(t) = changes.next() {
+                let end_sec = changes.peek().map_or(self.window_sec, |n| n.time_sec);
+                if t.to != WeaponState::Idle && end_sec > t.time_sec {
+                    spans.push(StateSpan {
+                        weapon_index,
+                        state: t.to,
+                        start_sec: t.time_sec,
+                        end_sec,
+                    });
+                }
+            }
+        }
+        spans
+    }
+}
+
+/// Timeline of `u`'s combat weapons with the scan settings used by the cadence check;
+/// None when the unit has no combat weapon.
+pub fn unit_timeline(u: &UnitSummary, cfg: &AnalysisConfig) -> Option<UnitTimeline> {
+    let (weapons, effective) = u.combat_weapons();
+    if weapons.is_empty() {
+        return None;
+    }
+    let result = scheduler::simulate_with(
+        &weapons,
+        &effective,
+        cfg.simulation_seconds,
+        cfg.cadence_gap_tolerance_secs,
+        &scheduler::SimOptions {
+            timing: cfg.timing,
+            resources: Some(scheduler::shared_resources(
+                &weapons,
+                scheduler::EnergyPool::from_stats(&u.stats),
+            )),
+            ..Default::default()
+        },
+    );
+    Some(UnitTimeline {
+        unit_id: u.unit_id.id.clone(),
+        window_sec: result.window_sec,
+        weapons: weapons.into_iter().map(|w| w.weapon_bp_id).collect(),
+        events: result.events,
+        gaps: result.gaps,
+        transitions: result.transitions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(
+        time_sec: f64,
+        weapon_index: usize,
+        from: WeaponState,
+        to: WeaponState,
+    ) -> StateTransition {
+        StateTransition {
+            time_sec,
+            weapon_bp_id: format!("w{}", weapon_index),
+            weapon_index,
+            from,
+            to,
+        }
+    }
+
+    #[test]
+    fn state_spans_skip_idle_and_run_to_window_end() {
+        use WeaponState::*;
+        let t = UnitTimeline {
+            unit_id: "u".to_string(),
+            window_sec: 10.0,
+            weapons: vec!["w0".to_string(), "w1".to_string()],
+            events: vec![],
+            gaps: vec![],
+            transitions: vec![
+                change(0.0, 0, Idle, RackSalvoCharge),
+                change(0.0, 1, Idle, MuzzleSalvo),
+                change(1.5, 0, RackSalvoCharge, RackSalvoReload),
+                change(2.0, 1, MuzzleSalvo, Idle),
+            ],
+        };
+        let spans: Vec<(usize, WeaponState, f64, f64)> = t
+            .state_spans()
+            .iter()
+            .map(|s| (s.weapon_index, s.state, s.start_sec, s.end_sec))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                (0, RackSalvoCharge, 0.0, 1.5),
+                (0, RackSalvoReload, 1.5, 10.0),
+                (1, MuzzleSalvo, 0.0, 2.0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,419 @@
+//! Time-to-kill: replay the scheduler's fire timeline against a target's health and regen.
+//!
+//! Damage lands per fire event (so salvo and reload granularity count), every shot is assumed
+//! to hit, and the target regenerates between events up to its max health.
+//!
+//! A shielded target sits inside a bubble: blockable damage drains the shield first (the shot
+//! that collapses it is fully absorbed), the shield regenerates while up, and after collapsing
+//! it stays down for ShieldRechargeTime before returning at full strength.
+
+use super::{volley_damage, ShieldStats, UnitStats, UnitSummary, WeaponDeclared};
+use crate::config::{TimingMode, DEFAULT_CADENCE_GAP_TOLERANCE_SECS};
+use crate::scheduler;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Defensive side of a time-to-kill calculation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TtkTarget {
+    pub health: f64,
+    pub regen_rate: f64,
+    /// Shield covering the target (its own, or a shield unit it sits under).
+    pub shield: Option<ShieldStats>,
+}
+
+impl TtkTarget {
+    /// Target protected by its own shield, if it has one.
+    pub fn from_stats(stats: &UnitStats) -> Self {
+        Self {
+            health: stats.max_health.unwrap_or(0.0).max(0.0),
+            regen_rate: stats.regen_rate.unwrap_or(0.0).max(0.0),
+            shield: stats.shield,
+        }
+    }
+
+    /// Same target under `shield` (replaces its own).
+    pub fn under_shield(self, shield: ShieldStats) -> Self {
+        Self {
+            shield: Some(shield),
+            ..self
+        }
+    }
+
+    /// Health plus shield health: what an attacker whose shots are all blocked has to remove.
+    pub fn effective_hp(&self) -> f64 {
+        self.health + self.shield.map(|s| s.max_health).unwrap_or(0.0)
+    }
+
+    /// Target at `vet_level`, from the summary's veterancy table (base stats when the table is empty).
+    pub fn from_summary(u: &UnitSummary, vet_level: u8) -> Self {
+        match u.veterancy.iter().find(|v| v.level == vet_level) {
+            Some(v) => Self {
+                health: v.max_health,
+                regen_rate: v.regen_rate,
+                shield: u.stats.shield,
+            },
+            None => Self::from_stats(&u.stats),
+        }
+    }
+}
+
+/// Damage dealt at one instant of the attacker's timeline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DamageEvent {
+    pub time_sec: f64,
+    pub damage: f64,
+    /// Whether a shield stops this damage (see `WeaponDeclared::blocked_by_shields`).
+    pub shield_blocked: bool,
+}
+
+/// Damage events of `count` identical attackers firing in sync for `horizon_sec` seconds.
+/// Damage lands at impact time (fire time plus time of flight to MaxRadius); later impacts are dropped.
+/// Computed once per attacker and replayed against every target.
+pub fn damage_timeline(
+    attacker: &UnitSummary,
+    count: u32,
+    horizon_sec: f64,
+    timing: TimingMode,
+) -> Vec<DamageEvent> {
+    if count == 0 || horizon_sec <= 0.0 {
+        return Vec::new();
+    }
+    let (weapons, result) = unit_schedule(attacker, horizon_sec, timing);
+    let mut events: Vec<DamageEvent> = result
+        .events
+        .iter()
+        .filter(|e| e.impact_time_sec <= horizon_sec)
+        .map(|e| {
+            let w = &weapons[e.weapon_index];
+            DamageEvent {
+                time_sec: e.impact_time_sec,
+                damage: volley_damage(w) * count as f64,
+                shield_blocked: w.blocked_by_shields(),
+            }
+        })
+        .collect();
+    events.sort_by(|a, b| a.time_sec.total_cmp(&b.time_sec));
+    events
+}
+
+/// Combat weapons of `u` and their scheduler timeline over `horizon_sec` under `timing`, with
+/// the unit's shared turrets, unpack locks and energy pool.
+pub(super) fn unit_schedule(
+    u: &UnitSummary,
+    horizon_sec: f64,
+    timing: TimingMode,
+) -> (Vec<WeaponDeclared>, scheduler::ScheduleResult) {
+    let (weapons, effective) = u.combat_weapons();
+    let result = scheduler::simulate_with(
+        &weapons,
+        &effective,
+        horizon_sec,
+        DEFAULT_CADENCE_GAP_TOLERANCE_SECS,
+        &scheduler::SimOptions {
+            resources: Some(scheduler::shared_resources(
+                &weapons,
+                scheduler::EnergyPool::from_stats(&u.stats),
+            )),
+            timing,
+            ..Default::default()
+        },
+    );
+    (weapons, result)
+}
+
+/// Longest MaxRadius among combat weapons (0 without any).
+pub(super) fn reach(u: &UnitSummary) -> f64 {
+    u.combat().map(|(w, _)| w.range).fold(0.0, f64::max)
+}
+
+/// Damage multiplier for the unit's Defense.ArmorType in `armor` (lowercase keys); unlisted
+/// types take full damage.
+pub(super) fn armor_multiplier(u: &UnitSummary, armor: &BTreeMap<String, f64>) -> f64 {
+    u.stats
+        .armor_type
+        .as_ref()
+        .and_then(|a| armor.get(&a.to_lowercase()))
+        .copied()
+        .unwrap_or(1.0)
+}
+
+/// Time of the event that brings the target to zero health, or None if it survives the timeline.
+/// A target without health (no Defense.MaxHealth) has no TTK: None.
+pub fn time_to_kill(timeline: &[DamageEvent], target: &TtkTarget) -> Option<f64> {
+    if target.health <= 0.0 {
+        return None;
+    }
+    let mut hp = target.health;
+    let mut shield = target.shield.map(ShieldState::new);
+    let mut last = 0.0f64;
+    for e in timeline {
+        let dt = e.time_sec - last;
+        hp = (hp + target.regen_rate * dt).min(target.health);
+        last = e.time_sec;
+        if let Some(s) = shield.as_mut() {
+            s.advance(e.time_sec, dt);
+            if e.shield_blocked && s.absorb(e.damage, e.time_sec).is_some() {
+                continue;
+            }
+        }
+        hp -= e.damage;
+        if hp <= 0.0 {
+            return Some(e.time_sec);
+        }
+    }
+    None
+}
+
+/// Time of the event that collapses `shield`, or None if it holds for the whole timeline.
+pub fn time_to_collapse(timeline: &[DamageEvent], shield: &ShieldStats) -> Option<f64> {
+    let mut s = ShieldState::new(*shield);
+    let mut last = 0.0f64;
+    for e in timeline.iter().filter(|e| e.shield_blocked) {
+        s.advance(e.time_sec, e.time_sec - last);
+        last = e.time_sec;
+        s.absorb(e.damage, e.time_sec);
+        if s.down_until.is_some() {
+            return Some(e.time_sec);
+        }
+    }
+    None
+}
+
+/// Shield health and collapse/recharge state during a replay.
+pub(super) struct ShieldState {
+    stats: ShieldStats,
+    hp: f64,
+    /// Time the shield comes back up after collapsing.
+    down_until: Option<f64>,
+}
+
+impl ShieldState {
+    pub(super) fn new(stats: ShieldStats) -> Self {
+        Self {
+            stats,
+            hp: stats.max_health,
+            down_until: None,
+        }
+    }
+
+    /// Regenerate over `dt` seconds ending at `now`, or come back up at full health once recharged.
+    pub(super) fn advance(&mut self, now: f64, dt: f64) {
+        match self.down_until {
+            Some(t) if now >= t => {
+                self.down_until = None;
+                self.hp = self.stats.max_health;
+            }
+            Some(_) => {}
+            None => self.hp = (self.hp + self.stats.regen_rate * dt).min(self.stats.max_health),
+        }
+    }
+
+    /// Shield health left; zero while down.
+    pub(super) fn health(&self) -> f64 {
+        if self.down_until.is_some() {
+            0.0
+        } else {
+            self.hp
+        }
+    }
+
+    /// Take a hit while up. Returns the shield health it removed (at most what was left; the
+    /// rest of a collapsing hit is lost), or None when the shield is down and the damage passes
+    /// through.
+    pub(super) fn absorb(&mut self, damage: f64, now: f64) -> Option<f64> {
+        if self.down_until.is_some() {
+            return None;
+        }
+        let absorbed = damage.min(self.hp);
+        self.hp -= damage;
+        if self.hp <= 0.0 {
+            self.hp = 0.0;
+            self.down_until = Some(now + self.stats.recharge_time);
+        }
+        Some(absorbed)
+    }
+}
+
+/// One unit under fire in a duel or battle: its health and its own shield.
+pub(super) struct Member {
+    pub(super) health: f64,
+    pub(super) shield: Option<ShieldState>,
+}
+
+impl Member {
+    /// `u` at full health, under its own shield.
+    pub(super) fn new(u: &UnitSummary) -> Self {
+        Self {
+            health: u.stats.max_health.unwrap_or(0.0).max(0.0),
+            shield: u.stats.shield.map(ShieldState::new),
+        }
+    }
+
+    pub(super) fn alive(&self) -> bool {
+        self.health > 0.0
+    }
+
+    /// Health plus shield health left.
+    pub(super) fn durability(&self) -> f64 {
+        self.health + self.shield.as_ref().map_or(0.0, ShieldState::health)
+    }
+}
+
+/// Time-to-kill matrix: rows are attackers (`count` of each), columns are targets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TtkMatrix {
+    pub attacker_count: u32,
+    /// Veterancy level applied to every target (0 = unvetted).
+    pub target_vet_level: u8,
+    pub horizon_sec: f64,
+    /// Shield unit every target sits under ("vs shielded target" scenario), if any.
+    #[serde(default)]
+    pub shield_unit: Option<String>,
+    pub unit_ids: Vec<String>,
+    pub unit_names: Vec<Option<String>>,
+    /// cells[attacker][target]: seconds to kill, None when not killed within the horizon.
+    pub cells: Vec<Vec<Option<f64>>>,
+}
+
+/// Compute the TTK of `count` of each unit against every unit in `units` (including itself),
+/// with targets at `target_vet_level`. With `shield_unit`, every target sits under that unit's shield.
+pub fn ttk_matrix(
+    units: &[UnitSummary],
+    count: u32,
+    target_vet_level: u8,
+    horizon_sec: f64,
+    shield_unit: Option<&UnitSummary>,
+    timing: TimingMode,
+) -> TtkMatrix {
+    let shield = shield_unit.and_then(|s| s.stats.shield);
+    let targets: Vec<TtkTarget> = units
+        .iter()
+        .map(|u| {
+            let t = TtkTarget::from_summary(u, target_vet_level);
+            match shield {
+                Some(s) => t.under_shield(s),
+                None => t,
+            }
+        })
+        .collect();
+    let cells = units
+        .iter()
+        .map(|a| {
+            let timeline = damage_timeline(a, count, horizon_sec, timing);
+            targets.iter().map(|t| time_to_kill(&timeline, t)).collect()
+        })
+        .collect();
+    TtkMatrix {
+        attacker_count: count,
+        target_vet_level,
+        horizon_sec,
+        shield_unit: shield_unit.map(|s| s.unit_id.id.clone()),
+        unit_ids: units.iter().map(|u| u.unit_id.id.clone()).collect(),
+        unit_names: units.iter().map(|u| u.unit_id.name.clone()).collect(),
+        cells,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{build_unit_summary, UnitId, WeaponDeclared};
+
+    fn gun_unit() -> UnitSummary {
+        let w = WeaponDeclared {
+            weapon_bp_id: "Gun".to_string(),
+            damage: 10.0,
+            projectiles_per_fire: 1,
+            rate_of_fire: 2.0,
+            range: 20.0,
+            ..Default::default()
+        };
+        build_unit_summary(
+            UnitId {
+                id: "gun".to_string(),
+                name: None,
+            },
+            "gun.lua".to_string(),
+            vec![w],
+            UnitStats::default(),
+            &crate::config::AnalysisConfig::default(),
+            None,
+        )
+    }
+
+    #[test]
+    fn ttk_counts_discrete_shots() {
+        let timeline = damage_timeline(&gun_unit(), 1, 60.0, TimingMode::Continuous);
+        let target = TtkTarget {
+            health: 100.0,
+            regen_rate: 0.0,
+            shield: None,
+        };
+        // Ten shots of 10 damage, first at t=0, then every 0.5s.
+        assert_eq!(time_to_kill(&timeline, &target), Some(4.5));
+        let two = damage_timeline(&gun_unit(), 2, 60.0, TimingMode::Continuous);
+        assert_eq!(time_to_kill(&two, &target), Some(2.0));
+    }
+
+    #[test]
+    fn target_without_max_health_has_no_ttk() {
+        let timeline = damage_timeline(&gun_unit(), 1, 60.0, TimingMode::Continuous);
+        let target = TtkTarget::from_stats(&UnitStats::default());
+        assert_eq!(time_to_kill(&timeline, &target), None);
+        assert_eq!(time_to_kill(&[], &target), None);
+    }
+
+    #[test]
+    fn regen_can_outpace_damage() {
+        let timeline = damage_timeline(&gun_unit(), 1, 60.0, TimingMode::Continuous);
+        let target = TtkTarget {
+            health: 100.0,
+            regen_rate: 25.0,
+            shield: None,
+        };
+        assert_eq!(time_to_kill(&timeline, &target), None);
+    }
+
+    #[test]
+    fn shield_absorbs_collapses_and_recharges() {
+        let timeline = damage_timeline(&gun_unit(), 1, 60.0, TimingMode::Continuous);
+        let shield = ShieldStats {
+            max_health: 50.0,
+            regen_rate: 0.0,
+            recharge_time: 10.0,
+            size: 10.0,
+        };
+        let target = TtkTarget {
+            health: 100.0,
+            regen_rate: 0.0,
+            shield: None,
+        }
+        .under_shield(shield);
+        // Five shots collapse the shield (t=2.0), ten more go through from t=2.5 to t=7.0.
+        assert_eq!(time_to_collapse(&timeline, &shield), Some(2.0));
+        assert_eq!(time_to_kill(&timeline, &target), Some(7.0));
+        assert_eq!(target.effective_hp(), 150.0);
+    }
+
+    #[test]
+    fn unblocked_artillery_ignores_shield() {
+        let mut u = gun_unit();
+        u.weapons[0].ballistic_arc = Some("RULEUBA_HighArc".to_string());
+        let timeline = damage_timeline(&u, 1, 60.0, TimingMode::Continuous);
+        let shield = ShieldStats {
+            max_health: 1000.0,
+            ..Default::default()
+        };
+        let target = TtkTarget {
+            health: 100.0,
+            regen_rate: 0.0,
+            shield: Some(shield),
+        };
+        assert_eq!(time_to_kill(&timeline, &target), Some(4.5));
+        assert_eq!(time_to_collapse(&timeline, &shield), None);
+        u.weapons[0].artillery_shield_blocks = true;
+        let blocked = damage_timeline(&u, 1, 60.0, TimingMode::Continuous);
+        assert_eq!(time_to_kill(&blocked, &target), None);
+    }
+}
@@ -0,0 +1,224 @@
+//! Turret traverse: how long a turreted weapon takes to bring its barrel onto a new target.
+//!
+//! Only yaw and pitch travel are modelled (both axes move at once, so the slower one decides).
+//! The weapon may fire once the aim is within FiringTolerance degrees of the target. Bearings of
+//! the old and new target are taken as independent and uniform over the yaw arc.
+
+use super::WeaponDeclared;
+use crate::parser::LuaValue;
+use serde::{Deserialize, Serialize};
+
+/// Turret fields of a weapon with `Turreted = true`. Angles in degrees, speeds in degrees/second.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TurretStats {
+    /// TurretYaw: centre of the yaw arc, relative to the unit's facing.
+    pub yaw: f64,
+    /// TurretYawRange: the turret turns this far either side of TurretYaw (180 = all around).
+    pub yaw_range: f64,
+    /// TurretYawSpeed.
+    pub yaw_speed: f64,
+    /// TurretPitch: rest elevation.
+    pub pitch: f64,
+    /// TurretPitchRange: elevation travel either side of TurretPitch.
+    pub pitch_range: f64,
+    /// TurretPitchSpeed.
+    pub pitch_speed: f64,
+}
+
+impl TurretStats {
+    /// The yaw arc reaches every bearing around the unit.
+    pub fn covers_all_around(&self) -> bool {
+        self.yaw_range >= 180.0
+    }
+
+    /// Width of the yaw arc in degrees (at most 360).
+    pub fn yaw_arc(&self) -> f64 {
+        (2.0 * self.yaw_range.max(0.0)).min(360.0)
+    }
+}
+
+/// Target-acquisition time of a turreted weapon.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Acquisition {
+    /// Turret pointing at one edge of its arc, new target at the opposite edge (or behind it).
+    pub worst_sec: f64,
+    /// Expected time over uniformly spread old and new target bearings.
+    pub avg_sec: f64,
+}
+
+/// Turret fields from a weapon table; None unless `Turreted = true` (older data: `TurretCapable`).
+pub fn turret_from_lua(table: &LuaValue) -> Option<TurretStats> {
+    let turreted = table
+        .get_bool("Turreted")
+        .or_else(|| table.get_bool("TurretCapable"))
+        .unwrap_or(false);
+    if !turreted {
+        return None;
+    }
+    Some(TurretStats {
+        yaw: table.get_num("TurretYaw").unwrap_or(0.0),
+        yaw_range: table.get_num("TurretYawRange").unwrap_or(180.0),
+        yaw_speed: table.get_num("TurretYawSpeed").unwrap_or(0.0),
+        pitch: table.get_num("TurretPitch").unwrap_or(0.0),
+        pitch_range: table.get_num("TurretPitchRange").unwrap_or(0.0),
+        pitch_speed: table.get_num("TurretPitchSpeed").unwrap_or(0.0),
+    })
+}
+
+/// Why the turret cannot bring the weapon onto every target it should engage, if it cannot.
+/// A structure never turns, so anything short of a full circle leaves a blind arc; a mobile
+/// unit turns to face its target, so the arc must include the unit's facing.
+pub fn yaw_coverage_issue(t: &TurretStats, structure: bool) -> Option<String> {
+    if t.yaw_speed <= 0.0 && t.yaw_range > 0.0 {
+        return Some("TurretYawSpeed is 0; the turret cannot traverse its arc".to_string());
+    }
+    if t.covers_all_around() {
+        return None;
+    }
+    let centre = (t.yaw + 180.0).rem_euclid(360.0) - 180.0;
+    if structure {
+        Some(format!(
+            "structure cannot turn; {:.0}° around it is outside the yaw arc",
+            360.0 - t.yaw_arc()
+        ))
+    } else if centre.abs() > t.yaw_range {
+        Some(format!(
+            "yaw arc {:.0}°±{:.0}° does not include the unit's facing",
+            centre, t.yaw_range
+        ))
+    } else {
+        None
+    }
+}
+
+/// Seconds to turn `degrees` at `speed`; instant without travel, never without speed.
+fn travel_sec(degrees: f64, speed: f64) -> f64 {
+    if degrees <= 0.0 {
+        0.0
+    } else if speed > 0.0 {
+        degrees / speed
+    } else {
+        f64::INFINITY
+    }
+}
+
+/// Mean travel beyond `tol` between two independent uniform points on an arc of width `arc`:
+/// the gap d has density 2(W - d)/W^2, so E[max(0, d - tol)] = (W - tol)^3 / (3 W^2).
+fn arc_mean_travel(arc: f64, tol: f64) -> f64 {
+    if arc <= 0.0 {
+        return 0.0;
+    }
+    (arc - tol).max(0.0).powi(3) / (3.0 * arc * arc)
+}
+
+/// Acquisition time of `w`; None when the weapon has no turret data or its turret has no yaw speed.
+/// Full circle: the angle d between bearings is uniform on [0, 180], so E[max(0, d - tol)] = (180 - tol)^2 / 360.
+/// Limited yaw arc and pitch: see `arc_mean_travel`. Pitch spans the full ±TurretPitchRange, so
+/// the turret may have to swing through 2 × TurretPitchRange.
+pub fn acquisition_time(w: &WeaponDeclared) -> Option<Acquisition> {
+    let t = w.turret?;
+    if t.yaw_speed <= 0.0 {
+        return None;
+    }
+    let tol = w.firing_tolerance.unwrap_or(0.0).max(0.0);
+    let (sweep, mean_yaw) = if t.covers_all_around() {
+        let left = (180.0 - tol).max(0.0);
+        (180.0, left * left / 360.0)
+    } else {
+        let arc = t.yaw_arc();
+        (arc, arc_mean_travel(arc, tol))
+    };
+    let pitch_arc = 2.0 * t.pitch_range.max(0.0);
+    let pitch_worst = travel_sec(pitch_arc - tol, t.pitch_speed);
+    let pitch_avg = travel_sec(arc_mean_travel(pitch_arc, tol), t.pitch_speed);
+    Some(Acquisition {
+        worst_sec: travel_sec(sweep - tol, t.yaw_speed).max(pitch_worst),
+        avg_sec: travel_sec(mean_yaw, t.yaw_speed).max(pitch_avg),
+    })
+}
+
+/// DPS when the target changes every `switch_interval_sec`: each switch costs the average
+/// acquisition time not already hidden behind one reload cycle.
+pub fn switching_dps(
+    effective_dps: f64,
+    cycle_sec: f64,
+    acquisition: Option<Acquisition>,
+    switch_interval_sec: f64,
+) -> f64 {
+    let lost = acquisition
+        .map(|a| (a.avg_sec - cycle_sec).max(0.0))
+        .unwrap_or(0.0);
+    if switch_interval_sec <= 0.0 || !lost.is_finite() {
+        return effective_dps;
+    }
+    effective_dps * switch_interval_sec / (switch_interval_sec + lost)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turret(yaw_range: f64, yaw_speed: f64, tol: f64) -> WeaponDeclared {
+        WeaponDeclared {
+            turret: Some(TurretStats {
+                yaw_range,
+                yaw_speed,
+                ..Default::default()
+            }),
+            firing_tolerance: Some(tol),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn full_circle_and_limited_arc() {
+        let full = acquisition_time(&turret(180.0, 30.0, 0.0)).unwrap();
+        assert!((full.worst_sec - 6.0).abs() < 1e-9);
+        assert!((full.avg_sec - 3.0).abs() < 1e-9);
+        let arc = acquisition_time(&turret(45.0, 30.0, 0.0)).unwrap();
+        assert!((arc.worst_sec - 3.0).abs() < 1e-9);
+        assert!((arc.avg_sec - 1.0).abs() < 1e-9);
+        // Pitch ±45° at 10°/s: worst 90° of travel, mean 90 / 3 = 30°.
+        let mut pitched = turret(180.0, 1000.0, 0.0);
+        if let Some(t) = pitched.turret.as_mut() {
+            t.pitch_range = 45.0;
+            t.pitch_speed = 10.0;
+        }
+        let pitched = acquisition_time(&pitched).unwrap();
+        assert!((pitched.worst_sec - 9.0).abs() < 1e-9);
+        assert!((pitched.avg_sec - 3.0).abs() < 1e-9);
+        let tolerant = acquisition_time(&turret(180.0, 30.0, 180.0)).unwrap();
+        assert_eq!(tolerant.worst_sec, 0.0);
+        assert!(acquisition_time(&WeaponDeclared::default()).is_none());
+    }
+
+    #[test]
+    fn yaw_coverage() {
+        let side = TurretStats {
+            yaw: 90.0,
+            yaw_range: 45.0,
+            yaw_speed: 30.0,
+            ..Default::default()
+        };
+        assert!(yaw_coverage_issue(&side, false).is_some());
+        let front = TurretStats { yaw: 0.0, ..side };
+        assert!(yaw_coverage_issue(&front, false).is_none());
+        assert!(yaw_coverage_issue(&front, true).is_some());
+        let full = TurretStats {
+            yaw_range: 180.0,
+            ..front
+        };
+        assert!(yaw_coverage_issue(&full, true).is_none());
+    }
+
+    #[test]
+    fn switching_loses_time_beyond_reload() {
+        let acq = Some(Acquisition {
+            worst_sec: 6.0,
+            avg_sec: 3.0,
+        });
+        assert!((switching_dps(100.0, 1.0, acq, 8.0) - 80.0).abs() < 1e-9);
+        assert_eq!(switching_dps(100.0, 5.0, acq, 8.0), 100.0);
+        assert_eq!(switching_dps(100.0, 1.0, None, 8.0), 100.0);
+    }
+}
@@ -0,0 +1,109 @@
+//! Veterancy: unit stats at each vet level from the configurable buff table.
+
+use super::{time_to_kill, DamageEvent, TtkTarget, UnitStats};
+use crate::config::{VeterancyBuffs, MAX_VET_LEVEL};
+use serde::{Deserialize, Serialize};
+
+/// Defensive stats of a unit at one veterancy level.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VetLevelStats {
+    pub level: u8,
+    /// Veteran.LevelN from the blueprint: what it takes to reach this level (None for level 0).
+    pub threshold: Option<f64>,
+    pub max_health: f64,
+    pub regen_rate: f64,
+    /// Health plus own shield health: what an attacker has to remove at this level.
+    pub effective_hp: f64,
+    /// Time for one unvetted copy of the same unit to kill it at this level (None: no kill).
+    pub mirror_ttk_sec: Option<f64>,
+}
+
+/// Unit stats with the buffs of `level` applied (max health scaled, regen added).
+pub fn stats_at_vet_level(stats: &UnitStats, level: u8, buffs: &VeterancyBuffs) -> UnitStats {
+    let level = level.min(MAX_VET_LEVEL);
+    let mut out = stats.clone();
+    out.max_health = stats.max_health.map(|h| h * buffs.max_health_mult(level));
+    let regen = stats.regen_rate.unwrap_or(0.0) + buffs.regen_bonus(&stats.categories, level);
+    out.regen_rate = if regen > 0.0 || stats.regen_rate.is_some() {
+        Some(regen)
+    } else {
+        None
+    };
+    out
+}
+
+/// Stats for levels 0..=5. `mirror_timeline` is the unit's own damage timeline, used for the mirror TTK.
+pub fn veterancy_table(
+    stats: &UnitStats,
+    buffs: &VeterancyBuffs,
+    mirror_timeline: &[DamageEvent],
+) -> Vec<VetLevelStats> {
+    if stats.max_health.is_none() {
+        return Vec::new();
+    }
+    (0..=MAX_VET_LEVEL)
+        .map(|level| {
+            let vet = stats_at_vet_level(stats, level, buffs);
+            let target = TtkTarget::from_stats(&vet);
+            VetLevelStats {
+                level,
+                threshold: if level == 0 {
+                    None
+                } else {
+                    stats.veteran_levels.get(level as usize - 1).copied()
+                },
+                max_health: target.health,
+                regen_rate: target.regen_rate,
+                effective_hp: target.effective_hp(),
+                mirror_ttk_sec: time_to_kill(mirror_timeline, &target),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn t1_tank() -> UnitStats {
+        UnitStats {
+            max_health: Some(300.0),
+            categories: vec!["LAND".to_string(), "TECH1".to_string()],
+            veteran_levels: vec![3.0, 6.0, 9.0, 12.0, 15.0],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn default_buffs_scale_health_and_regen() {
+        let s = stats_at_vet_level(&t1_tank(), 3, &VeterancyBuffs::default());
+        assert!((s.max_health.unwrap() - 390.0).abs() < 1e-9);
+        assert_eq!(s.regen_rate, Some(3.0));
+    }
+
+    #[test]
+    fn buff_table_loads_from_toml() {
+        let buffs: VeterancyBuffs = toml::from_str(
+            "max_health_per_level = 0.05\n[regen_per_level]\nTECH1 = [2, 4, 6, 8, 10]\n",
+        )
+        .unwrap();
+        let s = stats_at_vet_level(&t1_tank(), 5, &buffs);
+        assert!((s.max_health.unwrap() - 375.0).abs() < 1e-9);
+        assert_eq!(s.regen_rate, Some(10.0));
+    }
+
+    #[test]
+    fn table_has_all_levels_and_thresholds() {
+        let events = vec![DamageEvent {
+            time_sec: 1.0,
+            damage: 1000.0,
+            shield_blocked: true,
+        }];
+        let table = veterancy_table(&t1_tank(), &VeterancyBuffs::default(), &events);
+        assert_eq!(table.len(), 6);
+        assert_eq!(table[0].threshold, None);
+        assert_eq!(table[5].threshold, Some(15.0));
+        assert!(table[5].effective_hp > table[0].effective_hp);
+        assert_eq!(table[5].mirror_ttk_sec, Some(1.0));
+    }
+}
@@ -1,6 +1,14 @@
 //! JSON and HTML report generation.
 
-use crate::model::UnitSummary;
+mod svg;
+
+use crate::config::{TimingMode, DEFAULT_TTK_HORIZON_SECS};
+use crate::model::{
+    damage_timeline, time_to_collapse, ArmyReport, BattleResult, DuelOutcome, DuelResult,
+    ProjectileNode, ProjectileStatus, TtkMatrix, UnitSummary, UnitTimeline,
+};
+use crate::scheduler::WeaponState;
+use serde_json::json;
 use std::fs;
 use std::path::Path;
 
@@ -10,7 +18,169 @@ pub fn write_json_report(units: &[UnitSummary], path: &Path) -> Result<(), Strin
     Ok(())
 }
 
-pub fn write_html_report(units: &[UnitSummary], out_dir: &Path) -> Result<(), String> {
+/// Write a duel result, per-second trace included, as pretty JSON.
+pub fn write_duel_json(result: &DuelResult, path: &Path) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(result).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| e.to_string())
+}
+
+/// Write a battle result, losses trace included, as pretty JSON.
+pub fn write_battle_json(result: &BattleResult, path: &Path) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(result).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| e.to_string())
+}
+
+/// Write the battle summary page: outcome, per-group table and units alive over time.
+pub fn write_battle_html(name: &str, result: &BattleResult, path: &Path) -> Result<(), String> {
+    fs::write(path, render_battle_page(name, result)).map_err(|e| e.to_string())
+}
+
+fn render_battle_page(name: &str, result: &BattleResult) -> String {
+    let winner = match result.outcome {
+        DuelOutcome::A => format!("{} (A)", html_escape(&result.a.name)),
+        DuelOutcome::B => format!("{} (B)", html_escape(&result.b.name)),
+        DuelOutcome::Draw => "draw (both destroyed)".to_string(),
+        DuelOutcome::Timeout => "none before the horizon".to_string(),
+    };
+    let rows: String = [("A", &result.a), ("B", &result.b)]
+        .iter()
+        .map(|(label, army)| render_army_rows(label, army))
+        .collect();
+    let series = [
+        svg::Series {
+            label: &result.a.name,
+            color: "#06c",
+            points: result
+                .trace
+                .iter()
+                .map(|s| (s.time_sec, s.a_alive.iter().sum::<u32>() as f64))
+                .collect(),
+        },
+        svg::Series {
+            label: &result.b.name,
+            color: "#c33",
+            points: result
+                .trace
+                .iter()
+                .map(|s| (s.time_sec, s.b_alive.iter().sum::<u32>() as f64))
+                .collect(),
+        },
+    ];
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head><meta charset="utf-8"><title>FAF Simlint – Battle: {name}</title>
+<style>body{{font-family:system-ui,sans-serif;margin:1rem;}} table{{border-collapse:collapse;}} th,td{{border:1px solid #ccc;padding:6px;text-align:right;}} td:first-child,td:nth-child(2){{text-align:left;}}</style>
+</head>
+<body>
+<h1>Battle: {name}</h1>
+<p>Winner: {winner} at {end:.1}s. Armies start {range:.1} ogrids apart and close until in range. All shots assumed to hit; overkill is damage beyond what killed the target, including volleys landing on dead units.</p>
+<table><thead><tr><th>Army</th><th>Unit</th><th>Count</th><th>Survivors</th><th>Health left</th><th>Damage dealt</th><th>Overkill</th><th>Volleys</th><th>First loss</th></tr></thead>
+<tbody>{rows}</tbody>
+</table>
+<h2>Units alive</h2>
+{chart}
+</body>
+</html>"#,
+        name = html_escape(name),
+        winner = winner,
+        end = result.end_time_sec,
+        range = result.start_range,
+        rows = rows,
+        chart = svg::line_chart(&series, "Time (s)", "Units alive"),
+    )
+}
+
+fn render_army_rows(label: &str, army: &ArmyReport) -> String {
+    let army_label = format!(
+        "{} {} <small>({})</small>",
+        label,
+        html_escape(&army.name),
+        army.policy.as_str()
+    );
+    let mut rows: String = army
+        .groups
+        .iter()
+        .map(|g| {
+            format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.0}</td><td>{:.0}</td><td>{:.0}</td><td>{}</td><td>{}</td></tr>",
+                army_label,
+                html_escape(&g.unit_id),
+                g.count,
+                g.survivors,
+                g.health_left,
+                g.damage_dealt,
+                g.damage_wasted,
+                g.volleys_fired,
+                g.first_loss_sec
+                    .map(|t| format!("{:.1}s", t))
+                    .unwrap_or_else(|| "—".to_string())
+            )
+        })
+        .collect();
+    let spent = army.damage_dealt() + army.damage_wasted();
+    rows.push_str(&format!(
+        "<tr><th>{}</th><th>total</th><th>{}</th><th>{}</th><th></th><th>{:.0}</th><th>{:.0} ({:.0}%)</th><th></th><th></th></tr>",
+        army_label,
+        army.units(),
+        army.survivors(),
+        army.damage_dealt(),
+        army.damage_wasted(),
+        if spent > 0.0 { 100.0 * army.damage_wasted() / spent } else { 0.0 }
+    ));
+    rows
+}
+
+/// Chrome `trace_event` JSON of a unit timeline (chrome://tracing, Perfetto): one thread per
+/// weapon with its states as slices and fire events as instants, plus a thread of cadence gaps.
+pub fn chrome_trace(timeline: &UnitTimeline) -> serde_json::Value {
+    let us = |t: f64| (t * 1_000_000.0).round() as i64;
+    let mut events = vec![
+        json!({"name": "process_name", "ph": "M", "pid": 1, "tid": 0, "args": {"name": timeline.unit_id}}),
+        json!({"name": "thread_name", "ph": "M", "pid": 1, "tid": 0, "args": {"name": "gaps"}}),
+    ];
+    for (i, w) in timeline.weapons.iter().enumerate() {
+        events.push(
+            json!({"name": "thread_name", "ph": "M", "pid": 1, "tid": i + 1, "args": {"name": w}}),
+        );
+    }
+    for s in timeline.state_spans() {
+        events.push(json!({
+            "name": s.state.as_str(), "cat": "state", "ph": "X", "pid": 1, "tid": s.weapon_index + 1,
+            "ts": us(s.start_sec), "dur": us(s.end_sec) - us(s.start_sec),
+        }));
+    }
+    for e in &timeline.events {
+        events.push(json!({
+            "name": "fire", "cat": "shot", "ph": "i", "s": "t", "pid": 1, "tid": e.weapon_index + 1,
+            "ts": us(e.time_sec),
+            "args": {"shot_index": e.shot_index, "impact_sec": e.impact_time_sec},
+        }));
+    }
+    for g in &timeline.gaps {
+        events.push(json!({
+            "name": "gap", "cat": "gap", "ph": "X", "pid": 1, "tid": 0,
+            "ts": us(g.start_sec), "dur": us(g.end_sec) - us(g.start_sec),
+            "args": {"weapons_around": g.weapons_around},
+        }));
+    }
+    json!({"traceEvents": events, "displayTimeUnit": "ms"})
+}
+
+/// Write a unit timeline as Chrome trace JSON.
+pub fn write_chrome_trace(timeline: &UnitTimeline, path: &Path) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(&chrome_trace(timeline)).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| e.to_string())
+}
+
+/// Write the index, anomaly and unit pages; `timelines` adds the scheduler swimlane to unit pages
+/// and `timing` is the scheduler timing used for the shield collapse column.
+pub fn write_html_report(
+    units: &[UnitSummary],
+    timelines: &[UnitTimeline],
+    out_dir: &Path,
+    timing: TimingMode,
+) -> Result<(), String> {
     fs::create_dir_all(out_dir).map_err(|e| e.to_string())?;
     let index = render_index(units);
     fs::write(out_dir.join("index.html"), index).map_err(|e| e.to_string())?;
@@ -19,26 +189,41 @@ pub fn write_html_report(units: &[UnitSummary], out_dir: &Path) -> Result<(), St
     for u in units {
         let name = crate::util::normalize_id(&u.unit_id.id).replace(' ', "_");
         let path = out_dir.join(format!("unit_{}.html", name));
-        let content = render_unit_page(u);
+        let timeline = timelines.iter().find(|t| t.unit_id == u.unit_id.id);
+        let content = render_unit_page(u, timeline, timing);
         fs::write(path, content).map_err(|e| e.to_string())?;
     }
     Ok(())
 }
 
 fn render_index(units: &[UnitSummary]) -> String {
-    let rows: String = units
+    // One <tbody> per unit so sorting keeps enhancement sub-rows under their unit.
+    let groups: String = units
         .iter()
         .map(|u| {
             let id = &u.unit_id.id;
             let name = crate::util::normalize_id(id).replace(' ', "_");
             let display = u.unit_id.name.as_deref().unwrap_or(id);
-            let anomaly_count = u.anomalies.len();
+            let variants: String = u
+                .variants
+                .iter()
+                .map(|v| {
+                    format!(
+                        r##"<tr class="variant"><td>&nbsp;&nbsp;↳ <a href="unit_{}.html#enh-{}">{} + {}</a></td>{}</tr>"##,
+                        name,
+                        html_escape(&v.enhancement),
+                        html_escape(display),
+                        html_escape(&v.enhancement),
+                        index_cells(&v.summary)
+                    )
+                })
+                .collect();
             format!(
-                r#"<tr><td><a href="unit_{}.html">{}</a></td><td>{}</td><td>{}</td></tr>"#,
+                r#"<tbody><tr><td><a href="unit_{}.html">{}</a></td>{}</tr>{}</tbody>"#,
                 name,
                 html_escape(display),
-                u.weapons.len(),
-                anomaly_count
+                index_cells(u),
+                variants
             )
         })
         .collect();
@@ -46,14 +231,14 @@ fn render_index(units: &[UnitSummary]) -> String {
         r#"<!DOCTYPE html>
 <html lang="en">
 <head><meta charset="utf-8"><title>FAF Simlint – Units</title>
-<style>body{{font-family:system-ui,sans-serif;margin:1rem;}} table{{border-collapse:collapse;}} th,td{{border:1px solid #ccc;padding:6px;}} a{{color:#06c;}}</style>
+<style>body{{font-family:system-ui,sans-serif;margin:1rem;}} table{{border-collapse:collapse;}} th,td{{border:1px solid #ccc;padding:6px;}} th{{cursor:pointer;}} td.n{{text-align:right;}} a{{color:#06c;}}</style>
 </head>
 <body>
 <h1>FAF Unit Weapon Behavior Report</h1>
-<p>Unit list. <a href="anomalies.html">Anomalies</a></p>
+<p>Unit list. <a href="anomalies.html">Anomalies</a>. Click a column header to sort.</p>
 <input type="text" id="search" placeholder="Search unit ID or name…" style="margin-bottom:8px;">
-<table><thead><tr><th>Unit</th><th>Weapons</th><th>Anomalies</th></tr></thead>
-<tbody>{}</tbody>
+<table id="units"><thead><tr><th>Unit</th><th>Weapons</th><th>Anomalies</th><th>DPS</th><th>Mass</th><th>DPS/mass</th><th>DPS/energy</th><th>EHP/mass</th><th>DPS/build-s</th></tr></thead>
+{}
 </table>
 <script>
 document.getElementById('search').oninput=function(){{
@@ -62,13 +247,49 @@ document.getElementById('search').oninput=function(){{
    r.style.display=r.textContent.toLowerCase().indexOf(q)===-1?'none':'';
  }});
 }};
+document.querySelectorAll('#units th').forEach(function(th,col){{
+ th.onclick=function(){{
+  var t=document.getElementById('units'), desc=th.dataset.dir!=='desc';
+  th.dataset.dir=desc?'desc':'asc';
+  var key=function(b){{
+   var c=b.rows[0].cells[col];
+   return c.dataset.v!==undefined?parseFloat(c.dataset.v):c.textContent.toLowerCase();
+  }};
+  Array.from(t.tBodies).sort(function(a,b){{
+   var x=key(a), y=key(b);
+   if(typeof x==='number'&&isNaN(x))return 1;
+   if(typeof y==='number'&&isNaN(y))return -1;
+   return (x<y?-1:x>y?1:0)*(desc?-1:1);
+  }}).forEach(function(b){{t.appendChild(b);}});
+ }};
+}});
 </script>
 </body>
 </html>"#,
-        rows
+        groups
     )
 }
 
+/// Numeric index columns with a `data-v` sort key (empty key sorts last).
+fn index_cells(u: &UnitSummary) -> String {
+    let num = |v: Option<f64>, digits: usize| match v {
+        Some(v) => format!(r#"<td class="n" data-v="{}">{:.*}</td>"#, v, digits, v),
+        None => r#"<td class="n" data-v="">—</td>"#.to_string(),
+    };
+    let c = &u.cost_efficiency;
+    [
+        num(Some(u.weapons.len() as f64), 0),
+        num(Some(u.anomalies.len() as f64), 0),
+        num(Some(u.total_dps()), 2),
+        num(u.stats.build_cost_mass, 0),
+        num(c.dps_per_mass, 4),
+        num(c.dps_per_energy, 5),
+        num(c.ehp_per_mass, 2),
+        num(c.dps_per_build_second, 4),
+    ]
+    .concat()
+}
+
 fn render_anomalies_page(units: &[UnitSummary]) -> String {
     let mut items = Vec::new();
     for u in units {
@@ -109,32 +330,49 @@ fn render_anomalies_page(units: &[UnitSummary]) -> String {
     )
 }
 
-fn render_unit_page(u: &UnitSummary) -> String {
+fn render_unit_page(
+    u: &UnitSummary,
+    timeline: Option<&UnitTimeline>,
+    timing: TimingMode,
+) -> String {
     let id = &u.unit_id.id;
     let name = u.unit_id.name.as_deref().unwrap_or(id);
     let declared_rows: String = u
         .weapons
         .iter()
-        .map(|w| {
+        .enumerate()
+        .map(|(i, w)| {
             format!(
-                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
-                html_escape(&w.weapon_bp_id),
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                html_escape(&w.display_name(i)),
+                w.role.as_str(),
                 w.damage,
                 w.projectiles_per_fire,
-                w.rate_of_fire
+                w.rate_of_fire,
+                if w.blocked_by_shields() { "yes" } else { "no" }
             )
         })
         .collect();
     let effective_rows: String = u
         .effective
         .iter()
-        .map(|e| {
+        .zip(&u.weapons)
+        .enumerate()
+        .map(|(i, (e, w))| {
             format!(
-                "<tr><td>{}</td><td>{:.2}</td><td>{:.2}</td><td>{}</td></tr>",
-                html_escape(&e.weapon_bp_id),
+                "<tr><td>{}</td><td>{:.2}</td><td>{:.2}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.2}</td><td>{:.2}</td></tr>",
+                html_escape(&w.display_name(i)),
                 e.nominal_dps,
                 e.effective_dps,
-                e.cycle_time_sec
+                e.cycle_time_sec,
+                e.time_of_flight_sec
+                    .map(|t| format!("{:.2}", t))
+                    .unwrap_or_else(|| "–".to_string()),
+                e.acquisition
+                    .map(|a| format!("{:.2} / {:.2}", a.avg_sec, a.worst_sec))
+                    .unwrap_or_else(|| "–".to_string()),
+                e.first_shot_latency_sec,
+                e.switching_dps
             )
         })
         .collect();
@@ -153,6 +391,21 @@ fn render_unit_page(u: &UnitSummary) -> String {
             )
         })
         .collect();
+    let sections = [
+        render_cost_section(u),
+        render_range_section(u),
+        render_timeline_section(timeline),
+        render_burst_section(u),
+        render_monte_carlo_section(u),
+        render_attack_run_section(u),
+        render_friendly_fire_section(u),
+        render_projectile_section(u),
+        render_shield_section(u, timing),
+        render_defense_section(u),
+        render_veterancy_section(u),
+        render_enhancements_section(u),
+    ]
+    .concat();
     format!(
         r#"<!DOCTYPE html>
 <html lang="en">
@@ -164,9 +417,11 @@ fn render_unit_page(u: &UnitSummary) -> String {
 <p><a href="index.html">Back to list</a></p>
 {}
 <h2>Declared weapon stats (blueprint)</h2>
-<table><thead><tr><th>Weapon</th><th>Damage</th><th>Projectiles</th><th>ROF</th></tr></thead><tbody>{}</tbody></table>
+<p>Only combat weapons count toward unit DPS, cadence and time-to-kill; death, dummy and defensive weapons are listed for reference.</p>
+<table><thead><tr><th>Weapon</th><th>Role</th><th>Damage</th><th>Projectiles</th><th>ROF</th><th>Blocked by shields</th></tr></thead><tbody>{}</tbody></table>
 <h2>Effective (computed)</h2>
-<table><thead><tr><th>Weapon</th><th>Nominal DPS</th><th>Effective DPS</th><th>Cycle (s)</th></tr></thead><tbody>{}</tbody></table>
+<table><thead><tr><th>Weapon</th><th>Nominal DPS</th><th>Effective DPS</th><th>Cycle (s)</th><th>Flight to max range (s)</th><th>Acquisition avg / worst (s)</th><th>First shot (s)</th><th>Switching DPS</th></tr></thead><tbody>{}</tbody></table>
+{}
 <h2>Anomalies</h2>
 <ul>{}</ul>
 </body>
@@ -176,6 +431,7 @@ fn render_unit_page(u: &UnitSummary) -> String {
         declared_override_note,
         declared_rows,
         effective_rows,
+        sections,
         if anomaly_list.is_empty() {
             "<li>None</li>".to_string()
         } else {
@@ -184,6 +440,519 @@ fn render_unit_page(u: &UnitSummary) -> String {
     )
 }
 
+/// DPS-by-range step chart with dead zones shaded, plus the band table.
+fn render_range_section(u: &UnitSummary) -> String {
+    if u.range_bands.is_empty() {
+        return String::new();
+    }
+    let segments: Vec<(f64, f64, f64)> = u
+        .range_bands
+        .iter()
+        .map(|b| (b.start, b.end, b.dps))
+        .collect();
+    let dead: Vec<(f64, f64)> = u
+        .range_bands
+        .iter()
+        .filter(|b| b.is_dead_zone())
+        .map(|b| (b.start, b.end))
+        .collect();
+    let rows: String = u
+        .range_bands
+        .iter()
+        .map(|b| {
+            format!(
+                "<tr><td>{:.0}–{:.0}</td><td>{:.2}</td><td>{}</td></tr>",
+                b.start,
+                b.end,
+                b.dps,
+                if b.is_dead_zone() {
+                    "<em>dead zone</em>".to_string()
+                } else {
+                    html_escape(&b.weapon_ids.join(", "))
+                }
+            )
+        })
+        .collect();
+    format!(
+        "<h2>DPS by range</h2>\n{}\n<table><thead><tr><th>Range</th><th>DPS</th><th>Weapons</th></tr></thead><tbody>{}</tbody></table>",
+        svg::step_chart(&segments, &dead, "Range (ogrids)", "DPS"),
+        rows
+    )
+}
+
+/// Fill colour of a weapon state in the swimlane chart.
+fn state_fill(state: WeaponState) -> &'static str {
+    match state {
+        WeaponState::Idle => "#ffffff",
+        WeaponState::Unpacking | WeaponState::Repack => "#d9c2e9",
+        WeaponState::Aiming => "#e0e0e0",
+        WeaponState::RackSalvoCharge => "#f9d77e",
+        WeaponState::MuzzleSalvo => "#8fc98f",
+        WeaponState::RackSalvoReload => "#cfe0f3",
+    }
+}
+
+/// Scheduler swimlane: one row per combat weapon, states as bars, shots as ticks, gaps in red.
+fn render_timeline_section(timeline: Option<&UnitTimeline>) -> String {
+    let Some(t) = timeline else {
+        return String::new();
+    };
+    let spans = t.state_spans();
+    let lanes: Vec<svg::Lane> = t
+        .weapons
+        .iter()
+        .enumerate()
+        .map(|(i, w)| svg::Lane {
+            label: w,
+            spans: spans
+                .iter()
+                .filter(|s| s.weapon_index == i)
+                .map(|s| (s.start_sec, s.end_sec, state_fill(s.state)))
+                .collect(),
+            shots: t
+                .events
+                .iter()
+                .filter(|e| e.weapon_index == i)
+                .map(|e| e.time_sec)
+                .collect(),
+        })
+        .collect();
+    let gaps: Vec<(f64, f64)> = t.gaps.iter().map(|g| (g.start_sec, g.end_sec)).collect();
+    format!(
+        "<h2>Schedule</h2>\n<p>Fire events (blue ticks) over {}s. Bars: charge (yellow), muzzle salvo (green), reload (blue), unpack/repack (purple). Red: gaps in the unit's fire. {} shots, {} gaps.</p>\n{}",
+        t.window_sec,
+        t.events.len(),
+        t.gaps.len(),
+        svg::swimlane(&lanes, &gaps, t.window_sec)
+    )
+}
+
+/// Cumulative damage step chart, alpha strike, best windows and damage milestones.
+fn render_burst_section(u: &UnitSummary) -> String {
+    let b = &u.burst;
+    if b.curve.len() < 2 && b.alpha_damage <= 0.0 {
+        return String::new();
+    }
+    let segments: Vec<(f64, f64, f64)> = b
+        .curve
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let end = b
+                .curve
+                .get(i + 1)
+                .map(|n| n.time_sec)
+                .unwrap_or(b.horizon_sec);
+            (p.time_sec, end, p.damage)
+        })
+        .collect();
+    let windows: String = b
+        .windows
+        .iter()
+        .map(|w| {
+            format!(
+                "<tr><td>Max damage in {}s</td><td>{:.0}</td></tr>",
+                w.window_sec, w.max_damage
+            )
+        })
+        .collect();
+    let milestones: String = b
+        .milestones
+        .iter()
+        .map(|m| {
+            format!(
+                "<tr><td>Time to {:.0} damage</td><td>{}</td></tr>",
+                m.damage,
+                m.time_sec
+                    .map(|t| format!("{:.2}s", t))
+                    .unwrap_or_else(|| format!("not within {}s", b.horizon_sec))
+            )
+        })
+        .collect();
+    format!(
+        "<h2>Burst damage</h2>\n{}\n<table><tbody><tr><td>Alpha strike (first volley)</td><td>{:.0}</td></tr>{}{}</tbody></table>",
+        svg::step_chart(&segments, &[], "Time (s)", "Cumulative damage"),
+        b.alpha_damage,
+        windows,
+        milestones
+    )
+}
+
+/// Bombing-pass figures for bomb weapons on aircraft.
+fn render_attack_run_section(u: &UnitSummary) -> String {
+    let rows: String = u
+        .effective
+        .iter()
+        .filter_map(|e| e.attack_run.map(|r| (e, r)))
+        .map(|(e, r)| {
+            format!(
+                "<tr><td>{}</td><td>{:.2}</td><td>{:.0}</td><td>{:.2}</td><td>{:.2}</td><td>{:.1}</td></tr>",
+                html_escape(&e.weapon_bp_id),
+                r.reattack_interval_sec,
+                r.damage_per_pass,
+                r.sustained_dps,
+                r.rof_dps,
+                r.carpet_length
+            )
+        })
+        .collect();
+    if rows.is_empty() {
+        return String::new();
+    }
+    format!(
+        "<h2>Attack runs</h2>\n<p>Sustained DPS from re-attack passes (break-off, 180° turn, return) replaces the RateOfFire figure.</p>\n<table><thead><tr><th>Weapon</th><th>Re-attack interval (s)</th><th>Damage per pass</th><th>Sustained DPS</th><th>ROF-based DPS</th><th>Carpet length</th></tr></thead><tbody>{}</tbody></table>",
+        rows
+    )
+}
+
+/// Seeded Monte Carlo spread of effective DPS and time-to-kill against the unit itself.
+fn render_monte_carlo_section(u: &UnitSummary) -> String {
+    let Some(mc) = &u.monte_carlo else {
+        return String::new();
+    };
+    let ttk = match &mc.ttk_sec {
+        Some(t) => format!(
+            "<tr><td>TTK vs self (s)</td><td>{:.2}</td><td>{:.2}</td><td>{:.2}</td></tr>",
+            t.mean, t.p5, t.p95
+        ),
+        None => String::new(),
+    };
+    format!(
+        "<h2>Monte Carlo</h2>\n<p>{} runs, seed {}. Shots sample FiringRandomness, InitialSpeedRange and target movement at max range; {:.0}% of runs killed the target.</p>\n<table><thead><tr><th>Metric</th><th>Mean</th><th>p5</th><th>p95</th></tr></thead><tbody><tr><td>Effective DPS</td><td>{:.2}</td><td>{:.2}</td><td>{:.2}</td></tr>{}</tbody></table>",
+        mc.runs,
+        mc.seed,
+        mc.kill_fraction * 100.0,
+        mc.effective_dps.mean,
+        mc.effective_dps.p5,
+        mc.effective_dps.p95,
+        ttk
+    )
+}
+
+/// Friendly splash and collision estimates per weapon.
+fn render_friendly_fire_section(u: &UnitSummary) -> String {
+    if u.friendly_fire.is_empty() {
+        return String::new();
+    }
+    let rows: String = u
+        .friendly_fire
+        .iter()
+        .map(|f| {
+            format!(
+                "<tr><td>{}</td><td>{:.2}</td><td>{:.1}</td><td>{:.2}</td><td>{:.0}</td><td>{:.0}</td><td>{}</td></tr>",
+                html_escape(&f.weapon_bp_id),
+                f.near_impact_fraction,
+                f.friendly_damage_per_shot,
+                f.collide_chance,
+                f.friendly_damage_per_min,
+                f.enemy_damage_per_min,
+                if f.self_splash { "yes" } else { "no" }
+            )
+        })
+        .collect();
+    format!(
+        "<h2>Friendly fire</h2>\n<p>Expected damage to own units fired from inside a formation (DamageFriendly splash landing near the shooter, CollideFriendly hits).</p>\n<table><thead><tr><th>Weapon</th><th>Near impacts</th><th>Friendly damage/shot</th><th>Collide chance</th><th>Friendly damage/min</th><th>Enemy damage/min</th><th>Self-splash</th></tr></thead><tbody>{}</tbody></table>",
+        rows
+    )
+}
+
+/// Economy costs and the cost-efficiency ratios derived from them.
+fn render_cost_section(u: &UnitSummary) -> String {
+    let s = &u.stats;
+    if s.build_cost_mass.is_none() && s.build_cost_energy.is_none() && s.build_time.is_none() {
+        return String::new();
+    }
+    let opt = |v: Option<f64>, digits: usize| {
+        v.map(|v| format!("{:.*}", digits, v))
+            .unwrap_or_else(|| "—".to_string())
+    };
+    let c = &u.cost_efficiency;
+    format!(
+        "<h2>Cost efficiency</h2>\n<table><thead><tr><th>Mass</th><th>Energy</th><th>Build time</th><th>DPS/mass</th><th>DPS/energy</th><th>EHP/mass</th><th>DPS/build-s</th></tr></thead><tbody><tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr></tbody></table>",
+        opt(s.build_cost_mass, 0),
+        opt(s.build_cost_energy, 0),
+        opt(s.build_time, 0),
+        opt(c.dps_per_mass, 4),
+        opt(c.dps_per_energy, 5),
+        opt(c.ehp_per_mass, 2),
+        opt(c.dps_per_build_second, 4)
+    )
+}
+
+/// Projectile fragment trees, drawn as nested lists, for weapons that fragment or reference missing projectiles.
+fn render_projectile_section(u: &UnitSummary) -> String {
+    let trees: String = u
+        .weapons
+        .iter()
+        .filter_map(|w| {
+            let tree = w.projectile_tree.as_ref()?;
+            let interesting = !tree.fragments.is_empty()
+                || tree
+                    .nodes()
+                    .iter()
+                    .any(|n| n.status != ProjectileStatus::Resolved);
+            interesting.then(|| {
+                format!(
+                    "<h3>{}</h3>\n<ul>{}</ul>\n<p>Impact damage per shot: {:.1}</p>",
+                    html_escape(&w.weapon_bp_id),
+                    render_projectile_node(tree),
+                    tree.impact_damage()
+                )
+            })
+        })
+        .collect();
+    if trees.is_empty() {
+        return String::new();
+    }
+    format!("<h2>Projectile tree</h2>\n{}", trees)
+}
+
+fn render_projectile_node(n: &ProjectileNode) -> String {
+    let status = match n.status {
+        ProjectileStatus::Resolved => String::new(),
+        ProjectileStatus::Missing => r#" <strong style="color:#b00">missing</strong>"#.to_string(),
+        ProjectileStatus::Cycle => r#" <strong style="color:#b00">cycle</strong>"#.to_string(),
+    };
+    let children: String = n.fragments.iter().map(render_projectile_node).collect();
+    format!(
+        "<li>×{} <code>{}</code> — {:.1} damage{}{}{}</li>",
+        n.count,
+        html_escape(&n.projectile_id),
+        n.damage,
+        if n.inherited_damage {
+            " (inherited)"
+        } else {
+            ""
+        },
+        status,
+        if children.is_empty() {
+            String::new()
+        } else {
+            format!("<ul>{}</ul>", children)
+        }
+    )
+}
+
+/// Defense.Shield stats and how long one copy of this unit takes to collapse the shield.
+fn render_shield_section(u: &UnitSummary, timing: TimingMode) -> String {
+    let Some(shield) = u.stats.shield else {
+        return String::new();
+    };
+    let mirror = damage_timeline(u, 1, DEFAULT_TTK_HORIZON_SECS, timing);
+    format!(
+        "<h2>Shield</h2>\n<table><thead><tr><th>Shield health</th><th>Regen (HP/s)</th><th>Recharge (s)</th><th>Size</th><th>Mirror collapse (s)</th></tr></thead><tbody><tr><td>{:.0}</td><td>{:.1}</td><td>{:.1}</td><td>{:.1}</td><td>{}</td></tr></tbody></table>",
+        shield.max_health,
+        shield.regen_rate,
+        shield.recharge_time,
+        shield.size,
+        time_to_collapse(&mirror, &shield)
+            .map(|t| format!("{:.1}", t))
+            .unwrap_or_else(|| "holds".to_string())
+    )
+}
+
+/// Defensive weapons with their interception rate; saturation comes from the scan's anomalies.
+fn render_defense_section(u: &UnitSummary) -> String {
+    if u.interceptors.is_empty() {
+        return String::new();
+    }
+    let rows: String = u
+        .interceptors
+        .iter()
+        .map(|i| {
+            let saturated = u.anomalies.iter().any(|a| {
+                a.code == "INTERCEPTOR_SATURATED" && a.weapon_ids.contains(&i.weapon_bp_id)
+            });
+            format!(
+                "<tr><td>{}</td><td>{}</td><td>{:.1}</td><td>{}</td><td>{}</td></tr>",
+                html_escape(&i.weapon_bp_id),
+                i.target.as_str(),
+                i.interceptions_per_min,
+                i.range,
+                if saturated { "saturated" } else { "keeps up" }
+            )
+        })
+        .collect();
+    format!(
+        "<h2>Defensive weapons</h2>\n<p>Not counted in DPS. Each projectile fired can stop one incoming missile or torpedo.</p>\n<table><thead><tr><th>Weapon</th><th>Intercepts</th><th>Interceptions/min</th><th>Range</th><th>Reference salvo</th></tr></thead><tbody>{}</tbody></table>",
+        rows
+    )
+}
+
+/// Health, regen, effective HP and mirror TTK per veterancy level.
+fn render_veterancy_section(u: &UnitSummary) -> String {
+    if u.veterancy.is_empty() {
+        return String::new();
+    }
+    let rows: String = u
+        .veterancy
+        .iter()
+        .map(|v| {
+            format!(
+                "<tr><td>{}</td><td>{}</td><td>{:.0}</td><td>{:.1}</td><td>{:.0}</td><td>{}</td></tr>",
+                v.level,
+                v.threshold.map(|t| t.to_string()).unwrap_or_else(|| "—".to_string()),
+                v.max_health,
+                v.regen_rate,
+                v.effective_hp,
+                v.mirror_ttk_sec
+                    .map(|t| format!("{:.1}", t))
+                    .unwrap_or_else(|| "no kill".to_string())
+            )
+        })
+        .collect();
+    format!(
+        "<h2>Veterancy</h2>\n<p>Mirror TTK: time for one unvetted copy of this unit to kill it.</p>\n<table><thead><tr><th>Level</th><th>Threshold</th><th>Max health</th><th>Regen (HP/s)</th><th>Effective HP</th><th>Mirror TTK (s)</th></tr></thead><tbody>{}</tbody></table>",
+        rows
+    )
+}
+
+/// One row per enhancement upgrade path: cumulative cost, DPS, health and max range.
+fn render_enhancements_section(u: &UnitSummary) -> String {
+    if u.variants.is_empty() {
+        return String::new();
+    }
+    let rows: String = u
+        .variants
+        .iter()
+        .map(|v| {
+            let dps = v.summary.total_dps();
+            let max_range = v.summary.weapons.iter().map(|w| w.range).fold(0.0, f64::max);
+            format!(
+                r#"<tr id="enh-{}"><td>{}</td><td>{}</td><td>{}</td><td>{:.0} / {:.0} / {:.0}</td><td>{:.2}</td><td>{:.0}</td><td>{:.0}</td><td>{}</td></tr>"#,
+                html_escape(&v.enhancement),
+                html_escape(&v.enhancement),
+                html_escape(&v.path.join(" → ")),
+                html_escape(v.slot.as_deref().unwrap_or("—")),
+                v.build_cost_mass,
+                v.build_cost_energy,
+                v.build_time,
+                dps,
+                v.summary.stats.max_health.unwrap_or(0.0),
+                max_range,
+                if v.uses_declared_data { "data file" } else { "blueprint" }
+            )
+        })
+        .collect();
+    format!(
+        "<h2>Enhancements</h2>\n<p>Each row is the unit with that upgrade path installed (prerequisites included).</p>\n<table><thead><tr><th>Enhancement</th><th>Path</th><th>Slot</th><th>Cost (M / E / s)</th><th>Effective DPS</th><th>Max health</th><th>Max range</th><th>Source</th></tr></thead><tbody>{}</tbody></table>",
+        rows
+    )
+}
+
+/// Write the TTK matrix as CSV: one row per attacker, one column per target; empty cell = no kill.
+pub fn write_ttk_csv(matrix: &TtkMatrix, path: &Path) -> Result<(), String> {
+    let mut out = String::from("attacker");
+    for id in &matrix.unit_ids {
+        out.push(',');
+        out.push_str(&csv_field(id));
+    }
+    out.push('\n');
+    for (id, row) in matrix.unit_ids.iter().zip(matrix.cells.iter()) {
+        out.push_str(&csv_field(id));
+        for cell in row {
+            out.push(',');
+            if let Some(t) = cell {
+                out.push_str(&format!("{:.2}", t));
+            }
+        }
+        out.push('\n');
+    }
+    fs::write(path, out).map_err(|e| e.to_string())
+}
+
+/// Write the TTK matrix as an HTML heatmap (green = fast kill, red = slow, grey = no kill).
+pub fn write_ttk_html(matrix: &TtkMatrix, path: &Path) -> Result<(), String> {
+    fs::write(path, render_ttk_page(matrix)).map_err(|e| e.to_string())
+}
+
+fn render_ttk_page(matrix: &TtkMatrix) -> String {
+    let finite: Vec<f64> = matrix
+        .cells
+        .iter()
+        .flatten()
+        .filter_map(|c| *c)
+        .filter(|t| *t > 0.0)
+        .collect();
+    let min = finite.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = finite.iter().copied().fold(0.0f64, f64::max);
+    let label = |i: usize| {
+        let id = &matrix.unit_ids[i];
+        match &matrix.unit_names[i] {
+            Some(n) => format!("{} ({})", html_escape(id), html_escape(n)),
+            None => html_escape(id),
+        }
+    };
+    let header: String = (0..matrix.unit_ids.len())
+        .map(|i| format!("<th>{}</th>", label(i)))
+        .collect();
+    let rows: String = matrix
+        .cells
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let cells: String = row
+                .iter()
+                .map(|c| match c {
+                    Some(t) => format!(
+                        r#"<td style="background:{}">{:.1}</td>"#,
+                        heat_color(*t, min, max),
+                        t
+                    ),
+                    None => r#"<td class="nokill">—</td>"#.to_string(),
+                })
+                .collect();
+            format!("<tr><th>{}</th>{}</tr>", label(i), cells)
+        })
+        .collect();
+    let shield_note = match &matrix.shield_unit {
+        Some(id) => format!(
+            ", under the shield of {} (blocked shots drain the shield until it collapses and recharges)",
+            html_escape(id)
+        ),
+        None => String::new(),
+    };
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head><meta charset="utf-8"><title>FAF Simlint – Time to kill</title>
+<style>body{{font-family:system-ui,sans-serif;margin:1rem;}} table{{border-collapse:collapse;}} th,td{{border:1px solid #ccc;padding:6px;text-align:right;}} .nokill{{background:#ddd;color:#666;text-align:center;}}</style>
+</head>
+<body>
+<h1>Time to kill (seconds)</h1>
+<p>Rows: {} × attacker. Columns: target at vet level {}{}. Grey: no kill within {:.0}s. All shots assumed to hit; target regen applied between shots.</p>
+<table><thead><tr><th>Attacker \ Target</th>{}</tr></thead>
+<tbody>{}</tbody>
+</table>
+</body>
+</html>"#,
+        matrix.attacker_count,
+        matrix.target_vet_level,
+        shield_note,
+        matrix.horizon_sec,
+        header,
+        rows
+    )
+}
+
+/// Heatmap colour on a log scale between the fastest (green) and slowest (red) kill.
+fn heat_color(t: f64, min: f64, max: f64) -> String {
+    let frac = if t <= 0.0 || max <= min || min <= 0.0 {
+        0.0
+    } else {
+        ((t.ln() - min.ln()) / (max.ln() - min.ln())).clamp(0.0, 1.0)
+    };
+    format!("hsl({:.0},70%,70%)", 120.0 * (1.0 - frac))
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
 fn html_escape(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
@@ -225,6 +994,7 @@ mod tests {
                 muzzle_salvo_delay: None,
                 turret_capable: true,
                 target_categories: vec![],
+                ..Default::default()
             }],
             effective: vec![WeaponEffective {
                 weapon_bp_id: "W1".to_string(),
@@ -235,14 +1005,45 @@ mod tests {
                 salvo_duration_sec: 0.0,
                 reload_sec: 0.5,
                 target_class_modifiers: vec![],
+                time_of_flight_sec: None,
+                reaches_max_range: None,
+                ..Default::default()
             }],
             anomalies: vec![],
             declared_dps_override: None,
+            stats: Default::default(),
+            range_bands: vec![],
+            veterancy: vec![],
+            variants: vec![],
+            cost_efficiency: Default::default(),
+            interceptors: Vec::new(),
+            burst: Default::default(),
+            friendly_fire: vec![],
+            monte_carlo: None,
         }];
         let dir = tempfile::tempdir().unwrap();
-        write_html_report(&units, dir.path()).unwrap();
+        write_html_report(&units, &[], dir.path(), TimingMode::Continuous).unwrap();
         assert!(dir.path().join("index.html").exists());
         assert!(dir.path().join("anomalies.html").exists());
         assert!(dir.path().join("unit_test01.html").exists());
     }
+
+    #[test]
+    fn ttk_csv_marks_no_kill_as_empty() {
+        let matrix = TtkMatrix {
+            attacker_count: 1,
+            target_vet_level: 0,
+            horizon_sec: 600.0,
+            shield_unit: None,
+            unit_ids: vec!["a".to_string(), "b".to_string()],
+            unit_names: vec![None, Some("Bee".to_string())],
+            cells: vec![vec![Some(4.5), None], vec![Some(12.0), Some(3.25)]],
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let csv = dir.path().join("ttk.csv");
+        write_ttk_csv(&matrix, &csv).unwrap();
+        let text = fs::read_to_string(&csv).unwrap();
+        assert_eq!(text, "attacker,a,b\na,4.50,\nb,12.00,3.25\n");
+        write_ttk_html(&matrix, &dir.path().join("ttk.html")).unwrap();
+    }
 }
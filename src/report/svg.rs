@@ -0,0 +1,333 @@
+//! Inline SVG charts for the static HTML report (no JS, no external assets).
+
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 240.0;
+const MARGIN_LEFT: f64 = 56.0;
+const MARGIN_RIGHT: f64 = 16.0;
+const MARGIN_TOP: f64 = 12.0;
+const MARGIN_BOTTOM: f64 = 36.0;
+const TICKS: usize = 5;
+
+/// Maps data coordinates onto the plot area.
+struct Frame {
+    x_max: f64,
+    y_max: f64,
+}
+
+impl Frame {
+    fn new(x_max: f64, y_max: f64) -> Self {
+        Self {
+            x_max: if x_max > 0.0 { x_max } else { 1.0 },
+            y_max: if y_max > 0.0 { y_max * 1.1 } else { 1.0 },
+        }
+    }
+
+    fn x(&self, v: f64) -> f64 {
+        MARGIN_LEFT + (v / self.x_max) * (WIDTH - MARGIN_LEFT - MARGIN_RIGHT)
+    }
+
+    fn y(&self, v: f64) -> f64 {
+        HEIGHT - MARGIN_BOTTOM - (v / self.y_max) * (HEIGHT - MARGIN_TOP - MARGIN_BOTTOM)
+    }
+
+    fn axes(&self, x_label: &str, y_label: &str) -> String {
+        let mut out = format!(
+            r##"<line x1="{l}" y1="{b}" x2="{r}" y2="{b}" stroke="#333"/><line x1="{l}" y1="{t}" x2="{l}" y2="{b}" stroke="#333"/>"##,
+            l = MARGIN_LEFT,
+            r = WIDTH - MARGIN_RIGHT,
+            t = MARGIN_TOP,
+            b = HEIGHT - MARGIN_BOTTOM
+        );
+        for i in 0..=TICKS {
+            let xv = self.x_max * i as f64 / TICKS as f64;
+            let yv = self.y_max * i as f64 / TICKS as f64;
+            out.push_str(&format!(
+                r##"<text x="{:.1}" y="{:.1}" font-size="10" text-anchor="middle">{}</text><text x="{:.1}" y="{:.1}" font-size="10" text-anchor="end">{}</text>"##,
+                self.x(xv),
+                HEIGHT - MARGIN_BOTTOM + 14.0,
+                tick_label(xv),
+                MARGIN_LEFT - 4.0,
+                self.y(yv) + 3.0,
+                tick_label(yv)
+            ));
+        }
+        out.push_str(&format!(
+            r##"<text x="{:.1}" y="{:.1}" font-size="11" text-anchor="middle">{}</text><text x="12" y="{:.1}" font-size="11" text-anchor="middle" transform="rotate(-90 12 {:.1})">{}</text>"##,
+            (MARGIN_LEFT + WIDTH - MARGIN_RIGHT) / 2.0,
+            HEIGHT - 4.0,
+            super::html_escape(x_label),
+            HEIGHT / 2.0,
+            HEIGHT / 2.0,
+            super::html_escape(y_label)
+        ));
+        out
+    }
+}
+
+fn tick_label(v: f64) -> String {
+    if v >= 100.0 || v == v.floor() {
+        format!("{:.0}", v)
+    } else {
+        format!("{:.1}", v)
+    }
+}
+
+fn wrap(body: String) -> String {
+    wrap_sized(HEIGHT, body)
+}
+
+fn wrap_sized(height: f64, body: String) -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" role="img">{body}</svg>"#,
+        w = WIDTH,
+        h = height,
+        body = body
+    )
+}
+
+/// Height of one swimlane row.
+const LANE_HEIGHT: f64 = 22.0;
+/// Left margin of the swimlane chart, wide enough for weapon labels.
+const LANE_LABEL_WIDTH: f64 = 150.0;
+/// Longest lane label shown; longer labels keep their tail and the full label is in the tooltip.
+const LANE_LABEL_CHARS: usize = 22;
+
+/// One row of the swimlane chart: state spans `(start, end, fill)` and shot times.
+pub(super) struct Lane<'a> {
+    pub label: &'a str,
+    pub spans: Vec<(f64, f64, &'static str)>,
+    pub shots: Vec<f64>,
+}
+
+/// Swimlane chart over `[0, window]`: one row per lane, shots as ticks, `gaps` shaded red
+/// across every row.
+pub(super) fn swimlane(lanes: &[Lane], gaps: &[(f64, f64)], window: f64) -> String {
+    let window = if window > 0.0 { window } else { 1.0 };
+    let rows_bottom = MARGIN_TOP + LANE_HEIGHT * lanes.len() as f64;
+    let height = rows_bottom + MARGIN_BOTTOM;
+    let x = |v: f64| LANE_LABEL_WIDTH + (v / window) * (WIDTH - LANE_LABEL_WIDTH - MARGIN_RIGHT);
+    let mut body = String::new();
+    for (a, b) in gaps {
+        body.push_str(&format!(
+            r##"<rect x="{:.1}" y="{}" width="{:.1}" height="{:.1}" fill="#f4c7c3"><title>gap {:.2}s</title></rect>"##,
+            x(*a),
+            MARGIN_TOP,
+            (x(*b) - x(*a)).max(1.0),
+            rows_bottom - MARGIN_TOP,
+            b - a
+        ));
+    }
+    for (i, lane) in lanes.iter().enumerate() {
+        let top = MARGIN_TOP + LANE_HEIGHT * i as f64;
+        let mid = top + LANE_HEIGHT / 2.0;
+        let chars = lane.label.chars().count();
+        let short: String = if chars > LANE_LABEL_CHARS {
+            let tail: String = lane
+                .label
+                .chars()
+                .skip(chars + 1 - LANE_LABEL_CHARS)
+                .collect();
+            format!("…{}", tail)
+        } else {
+            lane.label.to_string()
+        };
+        body.push_str(&format!(
+            r##"<text x="{:.1}" y="{:.1}" font-size="10" text-anchor="end"><title>{}</title>{}</text><line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#ddd"/>"##,
+            LANE_LABEL_WIDTH - 4.0,
+            mid + 3.0,
+            super::html_escape(lane.label),
+            super::html_escape(&short),
+            LANE_LABEL_WIDTH,
+            top + LANE_HEIGHT,
+            WIDTH - MARGIN_RIGHT,
+            top + LANE_HEIGHT
+        ));
+        for (a, b, fill) in &lane.spans {
+            body.push_str(&format!(
+                r##"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}" fill-opacity="0.8"/>"##,
+                x(*a),
+                top + 4.0,
+                (x(*b) - x(*a)).max(0.5),
+                LANE_HEIGHT - 8.0,
+                fill
+            ));
+        }
+        for t in &lane.shots {
+            body.push_str(&format!(
+                r##"<line x1="{x:.1}" y1="{:.1}" x2="{x:.1}" y2="{:.1}" stroke="#06c" stroke-width="1.5"/>"##,
+                top + 2.0,
+                top + LANE_HEIGHT - 2.0,
+                x = x(*t)
+            ));
+        }
+    }
+    body.push_str(&format!(
+        r##"<line x1="{l}" y1="{b}" x2="{r}" y2="{b}" stroke="#333"/>"##,
+        l = LANE_LABEL_WIDTH,
+        r = WIDTH - MARGIN_RIGHT,
+        b = rows_bottom
+    ));
+    for i in 0..=TICKS {
+        let v = window * i as f64 / TICKS as f64;
+        body.push_str(&format!(
+            r##"<text x="{:.1}" y="{:.1}" font-size="10" text-anchor="middle">{}</text>"##,
+            x(v),
+            rows_bottom + 14.0,
+            tick_label(v)
+        ));
+    }
+    body.push_str(&format!(
+        r##"<text x="{:.1}" y="{:.1}" font-size="11" text-anchor="middle">Time (s)</text>"##,
+        (LANE_LABEL_WIDTH + WIDTH - MARGIN_RIGHT) / 2.0,
+        height - 4.0
+    ));
+    wrap_sized(height, body)
+}
+
+/// Step chart of piecewise-constant segments `(start, end, value)`.
+/// Segments listed in `shaded` (same `(start, end)` units as x) are highlighted in red.
+pub(super) fn step_chart(
+    segments: &[(f64, f64, f64)],
+    shaded: &[(f64, f64)],
+    x_label: &str,
+    y_label: &str,
+) -> String {
+    let x_max = segments.iter().map(|s| s.1).fold(0.0f64, f64::max);
+    let y_max = segments.iter().map(|s| s.2).fold(0.0f64, f64::max);
+    let f = Frame::new(x_max, y_max);
+    let mut body = String::new();
+    for (a, b) in shaded {
+        body.push_str(&format!(
+            r##"<rect x="{:.1}" y="{}" width="{:.1}" height="{:.1}" fill="#f4c7c3"/>"##,
+            f.x(*a),
+            MARGIN_TOP,
+            (f.x(*b) - f.x(*a)).max(1.0),
+            HEIGHT - MARGIN_TOP - MARGIN_BOTTOM
+        ));
+    }
+    body.push_str(&f.axes(x_label, y_label));
+    let mut path = String::new();
+    for (i, (a, b, v)) in segments.iter().enumerate() {
+        let cmd = if i == 0 { 'M' } else { 'L' };
+        path.push_str(&format!(
+            "{}{:.1},{:.1} L{:.1},{:.1} ",
+            cmd,
+            f.x(*a),
+            f.y(*v),
+            f.x(*b),
+            f.y(*v)
+        ));
+    }
+    body.push_str(&format!(
+        r##"<path d="{}" fill="none" stroke="#06c" stroke-width="2"/>"##,
+        path.trim_end()
+    ));
+    wrap(body)
+}
+
+/// One series of a line chart: legend label, stroke colour and `(x, y)` points.
+pub(super) struct Series<'a> {
+    pub label: &'a str,
+    pub color: &'static str,
+    pub points: Vec<(f64, f64)>,
+}
+
+/// Line chart of several series on shared axes, with a legend in the top right corner.
+pub(super) fn line_chart(series: &[Series], x_label: &str, y_label: &str) -> String {
+    let points = || series.iter().flat_map(|s| s.points.iter());
+    let x_max = points().map(|p| p.0).fold(0.0f64, f64::max);
+    let y_max = points().map(|p| p.1).fold(0.0f64, f64::max);
+    let f = Frame::new(x_max, y_max);
+    let mut body = f.axes(x_label, y_label);
+    for (i, s) in series.iter().enumerate() {
+        let path: Vec<String> = s
+            .points
+            .iter()
+            .enumerate()
+            .map(|(j, (x, y))| {
+                format!(
+                    "{}{:.1},{:.1}",
+                    if j == 0 { 'M' } else { 'L' },
+                    f.x(*x),
+                    f.y(*y)
+                )
+            })
+            .collect();
+        let legend_y = MARGIN_TOP + 12.0 + 14.0 * i as f64;
+        body.push_str(&format!(
+            r##"<path d="{}" fill="none" stroke="{c}" stroke-width="2"/><line x1="{:.1}" y1="{y:.1}" x2="{:.1}" y2="{y:.1}" stroke="{c}" stroke-width="2"/><text x="{:.1}" y="{:.1}" font-size="10" text-anchor="end">{}</text>"##,
+            path.join(" "),
+            WIDTH - MARGIN_RIGHT - 20.0,
+            WIDTH - MARGIN_RIGHT - 4.0,
+            WIDTH - MARGIN_RIGHT - 24.0,
+            legend_y + 3.0,
+            super::html_escape(s.label),
+            c = s.color,
+            y = legend_y
+        ));
+    }
+    wrap(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step_chart_is_svg_with_shading() {
+        let svg = step_chart(
+            &[(0.0, 25.0, 0.0), (25.0, 90.0, 70.0)],
+            &[(0.0, 25.0)],
+            "Range",
+            "DPS",
+        );
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("<rect"));
+        assert!(svg.contains("<path"));
+    }
+
+    #[test]
+    fn swimlane_has_a_row_per_lane_and_shaded_gaps() {
+        let lanes = [
+            Lane {
+                label: "Gun",
+                spans: vec![(0.0, 1.0, "#f9d77e")],
+                shots: vec![1.0, 3.0],
+            },
+            Lane {
+                label: "/projectiles/xsl0304/Missile<Rack>",
+                spans: vec![],
+                shots: vec![2.0],
+            },
+        ];
+        let svg = swimlane(&lanes, &[(4.0, 6.0)], 10.0);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(&format!(
+            r#"height="{}""#,
+            MARGIN_TOP + 2.0 * LANE_HEIGHT + MARGIN_BOTTOM
+        )));
+        assert_eq!(svg.matches(r##"stroke="#06c""##).count(), 3);
+        assert!(svg.contains("gap 2.00s"));
+        assert!(svg.contains("…xsl0304/Missile&lt;Rack&gt;</text>"));
+    }
+
+    #[test]
+    fn line_chart_draws_a_path_and_legend_per_series() {
+        let series = [
+            Series {
+                label: "A",
+                color: "#06c",
+                points: vec![(0.0, 20.0), (10.0, 12.0)],
+            },
+            Series {
+                label: "B <army>",
+                color: "#c33",
+                points: vec![(0.0, 15.0), (10.0, 0.0)],
+            },
+        ];
+        let svg = line_chart(&series, "Time (s)", "Units alive");
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches("<path").count(), 2);
+        assert!(svg.contains("B &lt;army&gt;"));
+    }
+}
@@ -0,0 +1,263 @@
+//! Exact analysis window for the cadence check: the hyperperiod of the weapons' cycles.
+//!
+//! Alone, each weapon repeats every charge + salvo + reload once it is past its lead-in (unpack
+//! and aim). Periods are exact fractions (RateOfFire 3 gives 1/3 s), so the combined uncontended
+//! pattern repeats every rational LCM of them, and one period after the longest lead-in covers
+//! every phase alignment, where a fixed window would truncate long patterns and over-run short
+//! ones. Shared turrets and unpack locks can shift that pattern, so the window spans two periods
+//! and the caller trusts it only when the second repeats the first (`Hyperperiod::recurs`).
+//!
+//! There is no exact period when a cycle is not a fraction with a small denominator, when a
+//! weapon draws on an energy pool (its reload then depends on the pool level), or when the LCM
+//! exceeds the bound; the caller then falls back to a fixed window.
+
+use super::{state_timing, FireEvent, ResourceKind, SharedResource, SimOptions};
+use crate::model::{WeaponDeclared, WeaponEffective};
+
+/// Period of the combined fire pattern and the lead-in before it starts repeating.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hyperperiod {
+    pub period_sec: f64,
+    /// Longest unpack + aim before a weapon's first charge.
+    pub lead_in_sec: f64,
+    /// Each weapon's period, by weapon index (0 for weapons that never cycle).
+    pub weapon_periods_sec: Vec<f64>,
+}
+
+impl Hyperperiod {
+    /// Simulation window covering the lead-in and two periods.
+    pub fn window_sec(&self) -> f64 {
+        self.lead_in_sec + 2.0 * self.period_sec
+    }
+
+    /// The simulated fire pattern of the second period repeats the first, shot for shot.
+    pub fn recurs(&self, events: &[FireEvent]) -> bool {
+        let start = self.lead_in_sec;
+        let period = |k: f64| {
+            let (from, to) = (
+                start + k * self.period_sec,
+                start + (k + 1.0) * self.period_sec,
+            );
+            events
+                .iter()
+                .filter(move |e| e.time_sec >= from - 1e-9 && e.time_sec < to - 1e-9)
+                .map(move |e| (e.weapon_index, e.time_sec - from))
+        };
+        let (first, second): (Vec<_>, Vec<_>) = (period(0.0).collect(), period(1.0).collect());
+        first.len() == second.len()
+            && first
+                .iter()
+                .zip(&second)
+                .all(|(a, b)| a.0 == b.0 && (a.1 - b.1).abs() < 1e-6)
+    }
+}
+
+/// Largest denominator tried when reading a period as an exact fraction of seconds.
+const MAX_DENOMINATOR: u64 = 10_000;
+
+/// `sec` as a reduced fraction `(numerator, denominator)`, or None when no fraction with a
+/// denominator up to `MAX_DENOMINATOR` matches it. Continued fractions give the best candidates.
+fn rational(sec: f64) -> Option<(u64, u64)> {
+    if sec <= 0.0 || !sec.is_finite() {
+        return None;
+    }
+    let (mut h0, mut h1, mut k0, mut k1) = (0u64, 1u64, 1u64, 0u64);
+    let mut x = sec;
+    loop {
+        let a = x.floor();
+        let (h, k) = (
+            (a as u64).checked_mul(h1)?.checked_add(h0)?,
+            (a as u64).checked_mul(k1)?.checked_add(k0)?,
+        );
+        if k > MAX_DENOMINATOR {
+            return None;
+        }
+        if (h as f64 / k as f64 - sec).abs() < 1e-9 * sec.max(1.0) {
+            return Some((h, k));
+        }
+        (h0, h1, k0, k1) = (h1, h, k1, k);
+        x = 1.0 / (x - a);
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Hyperperiod of `weapons` under `opts`, at most `max_sec` long. The error says why there is
+/// none, for the anomaly note.
+pub fn hyperperiod(
+    weapons: &[WeaponDeclared],
+    effective: &[WeaponEffective],
+    resources: &[SharedResource],
+    opts: &SimOptions,
+    max_sec: f64,
+) -> Result<Hyperperiod, String> {
+    let n = weapons.len().min(effective.len());
+    if let Some(r) = resources
+        .iter()
+        .find(|r| matches!(r.kind, ResourceKind::Energy { .. }) && !r.users.is_empty())
+    {
+        return Err(format!("reloads depend on the {} pool", r.name));
+    }
+    // Running LCM as a fraction: lcm(a/b, c/d) = lcm(a, c) / gcd(b, d) for reduced fractions.
+    let (mut num, mut den): (u64, u64) = (0, 1);
+    let mut lead_in_sec: f64 = 0.0;
+    let mut weapon_periods_sec = Vec::with_capacity(n);
+    for i in 0..n {
+        let t = state_timing(&weapons[i], &effective[i], opts);
+        let shots = effective[i].shots_per_cycle.max(1);
+        let salvo = t.shot_spacing_sec * (shots - 1) as f64;
+        let period = t.charge_sec + salvo + t.reload_sec;
+        if period <= 0.0 {
+            // Never engages (see `run`).
+            weapon_periods_sec.push(0.0);
+            continue;
+        }
+        let (p, q) = rational(period).ok_or_else(|| {
+            format!(
+                "{} cycle {:.4}s is not an exact fraction of a second",
+                weapons[i].weapon_bp_id, period
+            )
+        })?;
+        (num, den) = if num == 0 {
+            (p, q)
+        } else {
+            ((num / gcd(num, p)).saturating_mul(p), gcd(den, q))
+        };
+        if num as f64 / den as f64 > max_sec + 1e-9 {
+            return Err(format!("period exceeds {}s", max_sec));
+        }
+        lead_in_sec = lead_in_sec.max(t.unpack_sec + t.aim_sec);
+        weapon_periods_sec.push(p as f64 / q as f64);
+    }
+    if weapon_periods_sec.iter().all(|p| *p == 0.0) {
+        return Err("no weapon cycles".to_string());
+    }
+    Ok(Hyperperiod {
+        period_sec: num as f64 / den as f64,
+        lead_in_sec,
+        weapon_periods_sec,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TimingMode;
+    use crate::scheduler::{shared_resources, EnergyPool};
+
+    fn weapon(
+        id: &str,
+        cycle: f64,
+        shots: u32,
+        salvo_sec: f64,
+    ) -> (WeaponDeclared, WeaponEffective) {
+        (
+            WeaponDeclared {
+                weapon_bp_id: id.to_string(),
+                damage: 10.0,
+                projectiles_per_fire: 1,
+                rate_of_fire: 1.0 / cycle,
+                ..Default::default()
+            },
+            WeaponEffective {
+                weapon_bp_id: id.to_string(),
+                cycle_time_sec: cycle,
+                shots_per_cycle: shots,
+                salvo_duration_sec: salvo_sec,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn run(
+        set: &[(WeaponDeclared, WeaponEffective)],
+        timing: TimingMode,
+    ) -> Result<Hyperperiod, String> {
+        let (w, e): (Vec<_>, Vec<_>) = set.iter().cloned().unzip();
+        let opts = SimOptions {
+            timing,
+            ..Default::default()
+        };
+        hyperperiod(&w, &e, &shared_resources(&w, None), &opts, 300.0)
+    }
+
+    #[test]
+    fn period_is_lcm_of_tick_cycles() {
+        // 1.5 s and 2.5 s (2.0 s cycle + 0.5 s salvo span) -> 7.5 s.
+        let h = run(
+            &[weapon("A", 1.5, 1, 0.0), weapon("B", 2.0, 2, 1.0)],
+            TimingMode::Continuous,
+        )
+        .unwrap();
+        assert_eq!(h.weapon_periods_sec, vec![1.5, 2.5]);
+        assert!((h.period_sec - 7.5).abs() < 1e-9);
+        assert_eq!(h.window_sec(), 2.0 * h.period_sec);
+    }
+
+    #[test]
+    fn off_grid_cycles_have_a_rational_period() {
+        let set = [weapon("A", 1.0 / 3.0, 1, 0.0), weapon("B", 1.0, 1, 0.0)];
+        // 1/3 s divides 1.0 s exactly.
+        let h = run(&set, TimingMode::Continuous).unwrap();
+        assert!((h.period_sec - 1.0).abs() < 1e-9);
+        // Rounded to 0.3 s: LCM with 1.0 s is 3.0 s.
+        let h = run(&set, TimingMode::Ticks).unwrap();
+        assert!((h.period_sec - 3.0).abs() < 1e-9);
+        // RateOfFire 10/83 (8.3 s) against 1/3 s: lcm(83/10, 1/3) = 83/1.
+        let h = run(
+            &[weapon("A", 8.3, 1, 0.0), weapon("B", 1.0 / 3.0, 1, 0.0)],
+            TimingMode::Continuous,
+        )
+        .unwrap();
+        assert!((h.period_sec - 83.0).abs() < 1e-9);
+        let err = run(
+            &[weapon("A", std::f64::consts::PI, 1, 0.0)],
+            TimingMode::Continuous,
+        );
+        assert!(err.unwrap_err().contains("not an exact fraction"));
+    }
+
+    #[test]
+    fn recurrence_compares_the_two_periods() {
+        let h = Hyperperiod {
+            period_sec: 1.0,
+            lead_in_sec: 0.0,
+            weapon_periods_sec: vec![1.0],
+        };
+        let shot = |t: f64| FireEvent {
+            time_sec: t,
+            weapon_bp_id: "A".to_string(),
+            weapon_index: 0,
+            shot_index: 0,
+            impact_time_sec: t,
+        };
+        assert!(h.recurs(&[shot(0.0), shot(0.5), shot(1.0), shot(1.5)]));
+        assert!(!h.recurs(&[shot(0.0), shot(0.5), shot(1.0), shot(1.6)]));
+        assert!(!h.recurs(&[shot(0.0), shot(0.5), shot(1.0)]));
+    }
+
+    #[test]
+    fn long_periods_and_energy_pools_fall_back() {
+        let set = [weapon("A", 13.7, 1, 0.0), weapon("B", 17.3, 1, 0.0)];
+        assert_eq!(
+            run(&set, TimingMode::Continuous).unwrap_err(),
+            "period exceeds 300s"
+        );
+
+        let (mut w, e) = weapon("Beam", 2.0, 1, 0.0);
+        w.energy_required = Some(500.0);
+        let pool = EnergyPool {
+            income_per_sec: 100.0,
+            storage: 1000.0,
+        };
+        let resources = shared_resources(std::slice::from_ref(&w), Some(pool));
+        let err = hyperperiod(&[w], &[e], &resources, &SimOptions::default(), 300.0);
+        assert!(err.unwrap_err().starts_with("reloads depend on"));
+    }
+}
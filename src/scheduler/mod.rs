@@ -1,13 +1,79 @@
 //! Micro-scheduler: simulates weapon firing over N seconds to detect cadence interference.
+//!
+//! Event-driven: every weapon state change is an event on a binary-heap queue, so cost grows
+//! with the number of events, not events × weapons. Events at the same instant are ordered by
+//! these rules, in turn:
+//!
+//! 1. time, compared at microsecond resolution (so float drift cannot reorder simultaneous events);
+//! 2. event kind, in `EventKind` declaration order (reload/charge completions before fires,
+//!    fires before impacts);
+//! 3. stated weapon priority, higher first (`SimOptions::priority`; default 0);
+//! 4. weapon ID, then the weapon's parameters, so blueprint order never decides;
+//! 5. shot index within the salvo, then insertion order.
+//!
+//! Permuting the weapon slice therefore gives the same timeline (only `weapon_index` values
+//! change) unless priorities say otherwise.
+//!
+//! Each weapon runs the state machine in `state`; its transitions are logged for timeline rendering.
 
-use crate::model::{WeaponDeclared, WeaponEffective};
-use std::collections::BTreeMap;
+mod hyperperiod;
+mod resource;
+mod state;
+
+pub use hyperperiod::{hyperperiod, Hyperperiod};
+use resource::EnergyLevel;
+pub use resource::{shared_resources, EnergyPool, ResourceKind, SharedResource};
+pub use state::{state_timing, StateTiming, StateTransition, WeaponState};
+
+use crate::config::TimingMode;
+use crate::model::{tick_interval_sec, WeaponDeclared, WeaponEffective};
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
 
 /// Single fire event in the schedule.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FireEvent {
     pub time_sec: f64,
     pub weapon_bp_id: String,
+    /// Index of the weapon in the slice passed to `simulate` (IDs are not always unique).
+    pub weapon_index: usize,
+    pub shot_index: u32,
+    /// When the shot lands: fire time plus the projectile's time of flight to MaxRadius.
+    pub impact_time_sec: f64,
+}
+
+/// What happens at a scheduled instant. Declaration order is the tie-break rank at equal times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EventKind {
+    /// Reload (cycle time) finished; the weapon charges or fires its next salvo.
+    ReloadDone,
+    /// RackSalvoChargeTime finished; the muzzle salvo starts.
+    ChargeDone,
+    /// Unpack finished; the weapon aims.
+    UnpackDone,
+    /// Turret is on target; the weapon charges.
+    AimDone,
+    /// RackReloadTimeout after losing the target mid-rack; the rack reloads.
+    RackTimeout,
+    /// WeaponRepackTimeout elapsed without a target; the weapon packs up.
+    RepackStart,
+    /// Packing finished; the weapon is idle.
+    RepackDone,
+    /// A shared turret or unpack lock the weapon waited on was released; it retries.
+    ResourceFree,
+    /// A shot leaves the weapon.
+    Fire,
+    /// A shot lands.
+    Impact,
+}
+
+/// An event taken off the queue, in processing order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledEvent {
+    pub time_sec: f64,
+    pub kind: EventKind,
+    pub weapon_index: usize,
     pub shot_index: u32,
 }
 
@@ -18,10 +84,19 @@ pub struct ScheduleResult {
     pub window_sec: f64,
     pub weapon_expected_shots: BTreeMap<String, u32>,
     pub weapon_actual_shots: BTreeMap<String, u32>,
+    /// Shots each weapon (by index) fires alone but not alongside the others.
+    pub deficit_shots: Vec<u32>,
+    /// `blocked_by_sec[i][j]`: seconds weapon `i` waited on a turret or unpack lock held by
+    /// weapon `j` (by index) inside the window.
+    pub blocked_by_sec: Vec<Vec<f64>>,
     pub gaps: Vec<Gap>,
+    /// Every event processed inside the window (fires, impacts, reload completions) in queue order.
+    pub timeline: Vec<ScheduledEvent>,
+    /// Weapon state changes inside the window, in time order.
+    pub transitions: Vec<StateTransition>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Gap {
     pub start_sec: f64,
     pub end_sec: f64,
@@ -29,94 +104,621 @@ pub struct Gap {
     pub weapons_around: Vec<String>,
 }
 
-/// Build a fire schedule for multiple weapons over `window_sec` seconds.
-/// Each weapon fires at its cycle rate; when two would fire at the same time (within 1ms),
-/// we serialize them (first weapon first, then second). Detects gaps larger than tolerance.
+/// Scheduling options beyond the weapons themselves.
+#[derive(Debug, Clone)]
+pub struct SimOptions {
+    /// Weapon key (BlueprintId, or Label when it has none) -> priority; at equal times
+    /// higher-priority weapons act first.
+    pub priority: HashMap<String, i32>,
+    /// With `TimingMode::Ticks`, cycle times and salvo spacing are rounded to engine ticks.
+    pub timing: TimingMode,
+    /// Unpack animation length at playback rate 1.0. Blueprints do not state it, so it is 0
+    /// (unpacking is instant) unless the caller supplies one.
+    pub unpack_sec: f64,
+    /// Spend the worst-case turret acquisition time in Aiming before the first salvo.
+    /// Off by default: the target is assumed to be in the firing arc already.
+    pub aim_before_first_shot: bool,
+    /// When the target dies or leaves range; weapons then stand down. None = never.
+    pub target_lost_sec: Option<f64>,
+    /// Shared turrets, unpack locks and energy pools. None derives turrets and unpack locks from
+    /// the weapons (`shared_resources` without an energy pool).
+    pub resources: Option<Vec<SharedResource>>,
+}
+
+impl Default for SimOptions {
+    fn default() -> Self {
+        Self {
+            priority: HashMap::new(),
+            timing: TimingMode::default(),
+            unpack_sec: 0.0,
+            aim_before_first_shot: false,
+            target_lost_sec: None,
+            resources: None,
+        }
+    }
+}
+
+/// Time resolution of the queue: one microsecond.
+const TICKS_PER_SEC: f64 = 1_000_000.0;
+
+fn to_tick(time_sec: f64) -> i64 {
+    (time_sec * TICKS_PER_SEC).round() as i64
+}
+
+/// Heap entry; `Ord` implements the documented tie-breaking rules (smallest first via `Reverse`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct QueueKey {
+    tick: i64,
+    kind: EventKind,
+    /// Negated priority, so higher priority sorts first.
+    neg_priority: i64,
+    /// Position of the weapon in the order-independent ranking.
+    weapon_rank: usize,
+    shot_index: u32,
+    seq: u64,
+    weapon_index: usize,
+}
+
+impl Ord for QueueKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (
+            self.tick,
+            self.kind,
+            self.neg_priority,
+            self.weapon_rank,
+            self.shot_index,
+            self.seq,
+        )
+            .cmp(&(
+                other.tick,
+                other.kind,
+                other.neg_priority,
+                other.weapon_rank,
+                other.shot_index,
+                other.seq,
+            ))
+    }
+}
+
+impl PartialOrd for QueueKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Min-queue of weapon events with deterministic ordering.
+pub struct EventQueue {
+    heap: BinaryHeap<Reverse<QueueKey>>,
+    times: HashMap<u64, f64>,
+    ranks: Vec<usize>,
+    priorities: Vec<i32>,
+    seq: u64,
+}
+
+impl EventQueue {
+    /// Queue for `weapons`; ranks them by (priority desc, key, parameters) so slice order does not matter.
+    pub fn new(
+        weapons: &[WeaponDeclared],
+        effective: &[WeaponEffective],
+        opts: &SimOptions,
+    ) -> Self {
+        let priorities: Vec<i32> = weapons
+            .iter()
+            .map(|w| opts.priority.get(weapon_key(w)).copied().unwrap_or(0))
+            .collect();
+        let mut order: Vec<usize> = (0..weapons.len()).collect();
+        order.sort_by(|&a, &b| {
+            priorities[b]
+                .cmp(&priorities[a])
+                .then_with(|| weapon_key(&weapons[a]).cmp(weapon_key(&weapons[b])))
+                .then_with(|| cadence_key(&effective[a]).cmp(&cadence_key(&effective[b])))
+                .then_with(|| content_order(&weapons[a], &weapons[b]))
+        });
+        let mut ranks = vec![0; weapons.len()];
+        for (rank, &i) in order.iter().enumerate() {
+            ranks[i] = rank;
+        }
+        Self {
+            heap: BinaryHeap::new(),
+            times: HashMap::new(),
+            ranks,
+            priorities,
+            seq: 0,
+        }
+    }
+
+    pub fn push(&mut self, time_sec: f64, kind: EventKind, weapon_index: usize, shot_index: u32) {
+        self.seq += 1;
+        self.times.insert(self.seq, time_sec);
+        self.heap.push(Reverse(QueueKey {
+            tick: to_tick(time_sec),
+            kind,
+            neg_priority: -(self.priorities[weapon_index] as i64),
+            weapon_rank: self.ranks[weapon_index],
+            shot_index,
+            seq: self.seq,
+            weapon_index,
+        }));
+    }
+
+    /// Next event in tie-break order.
+    pub fn pop(&mut self) -> Option<ScheduledEvent> {
+        let Reverse(key) = self.heap.pop()?;
+        let time_sec = self
+            .times
+            .remove(&key.seq)
+            .unwrap_or(key.tick as f64 / TICKS_PER_SEC);
+        Some(ScheduledEvent {
+            time_sec,
+            kind: key.kind,
+            weapon_index: key.weapon_index,
+            shot_index: key.shot_index,
+        })
+    }
+}
+
+/// Stable name of a weapon for priorities and ranking: its BlueprintId, else its Label. Real
+/// weapons rarely have a BlueprintId.
+fn weapon_key(w: &WeaponDeclared) -> &str {
+    match w.label.as_deref() {
+        Some(label) if w.weapon_bp_id == "unknown" => label,
+        _ => &w.weapon_bp_id,
+    }
+}
+
+/// Blueprint fields for ranking weapons that share a key and cadence; weapons equal here are
+/// interchangeable.
+fn content_order(a: &WeaponDeclared, b: &WeaponDeclared) -> Ordering {
+    let fields = |w: &WeaponDeclared| {
+        [
+            w.damage,
+            w.damage_radius,
+            w.range,
+            w.min_range,
+            w.projectiles_per_fire as f64,
+            w.rate_of_fire,
+            w.rack_salvo_charge_time.unwrap_or(0.0),
+        ]
+    };
+    fields(a)
+        .iter()
+        .zip(fields(b).iter())
+        .map(|(x, y)| x.total_cmp(y))
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Cadence parameters as integer ticks, for ranking weapons that share a key.
+fn cadence_key(e: &WeaponEffective) -> (i64, u32, i64, i64) {
+    (
+        to_tick(e.cycle_time_sec),
+        e.shots_per_cycle,
+        to_tick(e.salvo_duration_sec),
+        to_tick(e.time_of_flight_sec.unwrap_or(0.0)),
+    )
+}
+
+/// Build a fire schedule for multiple weapons over `window_sec` seconds. Every weapon starts Idle
+/// at t = 0, unpacks and charges if its blueprint says so, spreads a salvo evenly over its salvo
+/// duration, then waits its cycle time. Detects gaps larger than tolerance.
 pub fn simulate(
     weapons: &[WeaponDeclared],
     effective: &[WeaponEffective],
     window_sec: f64,
     gap_tolerance_sec: f64,
 ) -> ScheduleResult {
-    let mut events = Vec::new();
+    simulate_with(
+        weapons,
+        effective,
+        window_sec,
+        gap_tolerance_sec,
+        &SimOptions::default(),
+    )
+}
+
+/// `simulate` with explicit options (priorities, tick timing, aiming, target loss).
+pub fn simulate_with(
+    weapons: &[WeaponDeclared],
+    effective: &[WeaponEffective],
+    window_sec: f64,
+    gap_tolerance_sec: f64,
+    opts: &SimOptions,
+) -> ScheduleResult {
+    let n = weapons.len().min(effective.len());
+    let (weapons, effective) = (&weapons[..n], &effective[..n]);
+    let resources = opts
+        .resources
+        .clone()
+        .unwrap_or_else(|| shared_resources(weapons, None));
+    let run = run(weapons, effective, window_sec, opts, resources.clone());
+
     let mut weapon_actual_shots: BTreeMap<String, u32> = weapons
         .iter()
         .map(|w| (w.weapon_bp_id.clone(), 0))
         .collect();
+    for e in &run.events {
+        *weapon_actual_shots
+            .entry(e.weapon_bp_id.clone())
+            .or_insert(0) += 1;
+    }
+    // Expected shots: each weapon alone, so only contention with the others counts as lost.
+    // A weapon keeps its energy pools (alone it has them to itself) but no locks.
     let mut weapon_expected_shots: BTreeMap<String, u32> = weapons
         .iter()
         .map(|w| (w.weapon_bp_id.clone(), 0))
         .collect();
+    let mut deficit_shots = vec![0; n];
+    for i in 0..n {
+        let own: Vec<SharedResource> = resources
+            .iter()
+            .filter(|r| !r.is_lock() && r.users.contains(&i))
+            .map(|r| SharedResource {
+                users: vec![0],
+                ..r.clone()
+            })
+            .collect();
+        let solo = run_alone(weapons, effective, i, window_sec, opts, own);
+        let together = run.events.iter().filter(|e| e.weapon_index == i).count() as u32;
+        deficit_shots[i] = solo.saturating_sub(together);
+        *weapon_expected_shots
+            .entry(weapons[i].weapon_bp_id.clone())
+            .or_insert(0) += solo;
+    }
 
-    for (eff, w) in effective.iter().zip(weapons.iter()) {
-        if eff.cycle_time_sec > 0.0 {
-            let cycles = (window_sec / eff.cycle_time_sec).floor() as u32;
-            let expected = cycles * eff.shots_per_cycle;
-            let _ = weapon_expected_shots.insert(w.weapon_bp_id.clone(), expected);
-        }
+    let gaps = find_gaps(&run.events, window_sec, gap_tolerance_sec);
+    ScheduleResult {
+        events: run.events,
+        window_sec,
+        weapon_expected_shots,
+        weapon_actual_shots,
+        deficit_shots,
+        blocked_by_sec: run.blocked_by_sec,
+        gaps,
+        timeline: run.timeline,
+        transitions: run.transitions,
     }
+}
 
-    // Per-weapon: (next fire time, shot index in current cycle).
-    let mut next_fire: Vec<(f64, u32)> = Vec::with_capacity(weapons.len());
-    for _ in effective.iter() {
-        next_fire.push((0.0f64, 0));
+fn run_alone(
+    weapons: &[WeaponDeclared],
+    effective: &[WeaponEffective],
+    i: usize,
+    window_sec: f64,
+    opts: &SimOptions,
+    resources: Vec<SharedResource>,
+) -> u32 {
+    let r = run(
+        &weapons[i..=i],
+        &effective[i..=i],
+        window_sec,
+        opts,
+        resources,
+    );
+    r.events.len() as u32
+}
+
+/// Output of one pass of the state machine.
+struct Run {
+    events: Vec<FireEvent>,
+    timeline: Vec<ScheduledEvent>,
+    transitions: Vec<StateTransition>,
+    blocked_by_sec: Vec<Vec<f64>>,
+}
+
+fn run(
+    weapons: &[WeaponDeclared],
+    effective: &[WeaponEffective],
+    window_sec: f64,
+    opts: &SimOptions,
+    resources: Vec<SharedResource>,
+) -> Run {
+    let mut events = Vec::new();
+    let mut timeline = Vec::new();
+    let cycle_of = |eff: &WeaponEffective| match opts.timing {
+        TimingMode::Continuous => eff.cycle_time_sec,
+        TimingMode::Ticks => tick_interval_sec(eff.cycle_time_sec),
+    };
+    let n = weapons.len();
+    let timing: Vec<StateTiming> = (0..n)
+        .map(|i| state_timing(&weapons[i], &effective[i], opts))
+        .collect();
+    let energy = resources
+        .iter()
+        .map(|r| match r.kind {
+            ResourceKind::Energy { capacity, .. } => EnergyLevel::full(capacity),
+            _ => EnergyLevel::full(0.0),
+        })
+        .collect();
+    let mut m = Machine {
+        weapons,
+        timing,
+        states: vec![WeaponState::Idle; n],
+        transitions: Vec::new(),
+        queue: EventQueue::new(&weapons[..n], &effective[..n], opts),
+        target_lost_sec: opts.target_lost_sec,
+        holders: vec![None; resources.len()],
+        waiters: vec![Vec::new(); resources.len()],
+        blocked: vec![None; n],
+        waiting_on: vec![None; n],
+        blocked_by_sec: vec![vec![0.0; n]; n],
+        energy,
+        resources,
+    };
+    for (i, eff) in effective.iter().enumerate().take(n) {
+        // A weapon without a cycle would refire at the same instant forever.
+        if cycle_of(eff) > 0.0 {
+            m.engage(i, 0.0);
+        }
     }
 
-    const TIME_EPS: f64 = 0.001;
-    let mut sim_time = 0.0f64;
-    while sim_time < window_sec {
-        let mut next_any = f64::MAX;
-        let mut fire_weapon = None;
-        for (i, (t, _)) in next_fire.iter().enumerate() {
-            if *t <= sim_time + TIME_EPS && *t < window_sec {
-                fire_weapon = Some(i);
-                break;
+    let window_tick = to_tick(window_sec);
+    while let Some(ev) = m.queue.pop() {
+        if to_tick(ev.time_sec) >= window_tick {
+            break;
+        }
+        let i = ev.weapon_index;
+        let t = ev.time_sec;
+        let eff = &effective[i];
+        match ev.kind {
+            EventKind::UnpackDone => {
+                m.unlock(i, t, ResourceKind::UnpackLock);
+                m.aim(i, t);
+            }
+            EventKind::ResourceFree => match m.blocked[i].take() {
+                Some(Blocked::Engage) => m.engage(i, t),
+                Some(Blocked::Charge) => m.charge(i, t),
+                None => {}
+            },
+            EventKind::AimDone | EventKind::ReloadDone => m.charge(i, t),
+            EventKind::ChargeDone => {
+                m.set(i, t, WeaponState::MuzzleSalvo);
+                m.queue.push(t, EventKind::Fire, i, 0);
+            }
+            EventKind::RackTimeout => m.reload(i, t),
+            EventKind::RepackStart => m.repack(i, t),
+            EventKind::RepackDone => m.set(i, t, WeaponState::Idle),
+            EventKind::Fire if m.lost(t) => {
+                // Target gone before this shot: hold the partial rack until RackReloadTimeout.
+                m.unlock(i, t, ResourceKind::Turret);
+                let lost = m.target_lost_sec.unwrap_or(t);
+                let at = (lost + m.timing[i].rack_reload_timeout_sec).max(t);
+                m.queue.push(at, EventKind::RackTimeout, i, 0);
             }
-            if *t < next_any && *t < window_sec {
-                next_any = *t;
+            EventKind::Fire => {
+                let id = &weapons[i].weapon_bp_id;
+                let impact = t + eff.time_of_flight_sec.unwrap_or(0.0);
+                events.push(FireEvent {
+                    time_sec: t,
+                    weapon_bp_id: id.clone(),
+                    weapon_index: i,
+                    shot_index: ev.shot_index,
+                    impact_time_sec: impact,
+                });
+                m.queue.push(impact, EventKind::Impact, i, ev.shot_index);
+                let next_shot = ev.shot_index + 1;
+                // A spacing rounded to zero ticks fires the rest of the salvo in this tick.
+                if next_shot < eff.shots_per_cycle {
+                    let spacing = m.timing[i].shot_spacing_sec;
+                    m.queue.push(t + spacing, EventKind::Fire, i, next_shot);
+                } else {
+                    m.reload(i, t);
+                }
             }
+            EventKind::Impact => {}
         }
-        if let Some(i) = fire_weapon {
-            let (t, shot_idx) = next_fire[i];
-            sim_time = t;
-            let id = &weapons[i].weapon_bp_id;
-            events.push(FireEvent {
+        timeline.push(ev);
+    }
+
+    for i in 0..n {
+        m.stop_waiting(i, window_sec);
+    }
+    Run {
+        events,
+        timeline,
+        transitions: m.transitions,
+        blocked_by_sec: m.blocked_by_sec,
+    }
+}
+
+/// Step a weapon retries once the lock it waited on is released.
+#[derive(Debug, Clone, Copy)]
+enum Blocked {
+    Engage,
+    Charge,
+}
+
+/// Weapon states, shared resources and the queue that drives them.
+struct Machine<'a> {
+    weapons: &'a [WeaponDeclared],
+    timing: Vec<StateTiming>,
+    states: Vec<WeaponState>,
+    transitions: Vec<StateTransition>,
+    queue: EventQueue,
+    target_lost_sec: Option<f64>,
+    resources: Vec<SharedResource>,
+    /// Weapon holding each lock resource.
+    holders: Vec<Option<usize>>,
+    /// Weapons waiting on each lock resource, in arrival order.
+    waiters: Vec<Vec<usize>>,
+    blocked: Vec<Option<Blocked>>,
+    /// Since when, and on which weapon's lock, each weapon has been waiting.
+    waiting_on: Vec<Option<(f64, usize)>>,
+    blocked_by_sec: Vec<Vec<f64>>,
+    /// Level of each energy resource (unused for locks).
+    energy: Vec<EnergyLevel>,
+}
+
+impl Machine<'_> {
+    fn set(&mut self, i: usize, t: f64, to: WeaponState) {
+        let from = self.states[i];
+        if from != to {
+            self.transitions.push(StateTransition {
                 time_sec: t,
-                weapon_bp_id: id.clone(),
-                shot_index: shot_idx,
+                weapon_bp_id: self.weapons[i].weapon_bp_id.clone(),
+                weapon_index: i,
+                from,
+                to,
             });
-            weapon_actual_shots
-                .entry(id.clone())
-                .and_modify(|c| *c += 1)
-                .or_insert(1);
-            let eff = &effective[i];
-            let cycle = eff.cycle_time_sec;
-            let shots = eff.shots_per_cycle;
-            let salvo_dur = eff.salvo_duration_sec;
-            let next_shot = shot_idx + 1;
-            let (next_t, next_shot_idx) = if next_shot < shots && salvo_dur > 0.0 {
-                let spread = salvo_dur / shots as f64;
-                (t + spread, next_shot)
-            } else {
-                (t + cycle, 0)
-            };
-            next_fire[i] = (next_t, next_shot_idx);
-            continue;
-        }
-        if next_any < f64::MAX {
-            sim_time = next_any;
+            self.states[i] = to;
+        }
+    }
+
+    fn lost(&self, t: f64) -> bool {
+        self.target_lost_sec
+            .map(|l| to_tick(t) >= to_tick(l))
+            .unwrap_or(false)
+    }
+
+    /// Locks of `kind` used by weapon `i`.
+    fn locks(&self, i: usize, kind: ResourceKind) -> Vec<usize> {
+        (0..self.resources.len())
+            .filter(|&r| self.resources[r].kind == kind && self.resources[r].users.contains(&i))
+            .collect()
+    }
+
+    /// Take every lock of `kind` weapon `i` needs, or none: on a busy lock, queue as its waiter.
+    fn try_lock(&mut self, i: usize, t: f64, kind: ResourceKind, step: Blocked) -> bool {
+        let needed = self.locks(i, kind);
+        if let Some((busy, holder)) = needed
+            .iter()
+            .find_map(|&r| self.holders[r].filter(|&h| h != i).map(|h| (r, h)))
+        {
+            if !self.waiters[busy].contains(&i) {
+                self.waiters[busy].push(i);
+            }
+            self.blocked[i] = Some(step);
+            if self.waiting_on[i].is_some_and(|(_, h)| h != holder) {
+                self.stop_waiting(i, t);
+            }
+            self.waiting_on[i].get_or_insert((t, holder));
+            return false;
+        }
+        self.stop_waiting(i, t);
+        for r in needed {
+            self.holders[r] = Some(i);
+        }
+        true
+    }
+
+    /// Charge the wait of weapon `i` up to `t` to the weapon it was waiting on.
+    fn stop_waiting(&mut self, i: usize, t: f64) {
+        if let Some((since, holder)) = self.waiting_on[i].take() {
+            self.blocked_by_sec[i][holder] += (t - since).max(0.0);
+        }
+    }
+
+    /// Release the locks of `kind` weapon `i` holds and wake their waiters.
+    fn unlock(&mut self, i: usize, t: f64, kind: ResourceKind) {
+        for r in self.locks(i, kind) {
+            if self.holders[r] == Some(i) {
+                self.holders[r] = None;
+                for w in std::mem::take(&mut self.waiters[r]) {
+                    self.queue.push(t, EventKind::ResourceFree, w, 0);
+                }
+            }
+        }
+    }
+
+    /// When weapon `i`, reloading from `t`, has its energy: queued on every pool it draws from,
+    /// and no faster than EnergyDrainPerSecond allows.
+    fn energy_ready(&mut self, i: usize, t: f64) -> f64 {
+        let w = &self.weapons[i];
+        let amount = w.energy_required.unwrap_or(0.0);
+        let mut ready = t;
+        for r in 0..self.resources.len() {
+            if let ResourceKind::Energy {
+                income_per_sec,
+                capacity,
+            } = self.resources[r].kind
+            {
+                if !self.resources[r].users.contains(&i) {
+                    continue;
+                }
+                let at = self.energy[r].withdraw(t, amount, income_per_sec, capacity);
+                let drain = w.energy_drain_per_sec.filter(|d| *d > 0.0);
+                ready = ready
+                    .max(at)
+                    .max(drain.map(|d| t + amount / d).unwrap_or(t));
+            }
+        }
+        ready
+    }
+
+    /// Idle -> Unpacking, or straight on to aiming.
+    fn engage(&mut self, i: usize, t: f64) {
+        let unpack = self.timing[i].unpack_sec;
+        if unpack > 0.0 {
+            if !self.try_lock(i, t, ResourceKind::UnpackLock, Blocked::Engage) {
+                return;
+            }
+            self.set(i, t, WeaponState::Unpacking);
+            self.queue.push(t + unpack, EventKind::UnpackDone, i, 0);
         } else {
-            break;
+            self.aim(i, t);
         }
     }
 
-    let gaps = find_gaps(&events, window_sec, gap_tolerance_sec);
-    ScheduleResult {
-        events,
-        window_sec,
-        weapon_expected_shots,
-        weapon_actual_shots,
-        gaps,
+    fn aim(&mut self, i: usize, t: f64) {
+        let aim = self.timing[i].aim_sec;
+        if aim > 0.0 && !self.lost(t) {
+            self.set(i, t, WeaponState::Aiming);
+            self.queue.push(t + aim, EventKind::AimDone, i, 0);
+        } else {
+            self.charge(i, t);
+        }
+    }
+
+    /// Start the next rack: charge, then fire. Stands down instead once the target is lost.
+    fn charge(&mut self, i: usize, t: f64) {
+        if self.lost(t) {
+            self.stand_down(i, t);
+            return;
+        }
+        if !self.try_lock(i, t, ResourceKind::Turret, Blocked::Charge) {
+            return;
+        }
+        let charge = self.timing[i].charge_sec;
+        if charge > 0.0 {
+            self.set(i, t, WeaponState::RackSalvoCharge);
+            self.queue.push(t + charge, EventKind::ChargeDone, i, 0);
+        } else {
+            self.set(i, t, WeaponState::MuzzleSalvo);
+            self.queue.push(t, EventKind::Fire, i, 0);
+        }
+    }
+
+    /// Salvo done: free the turret and reload (waiting for energy if the pool is short).
+    fn reload(&mut self, i: usize, t: f64) {
+        self.unlock(i, t, ResourceKind::Turret);
+        let reload = self.timing[i].reload_sec;
+        let until = (t + reload).max(self.energy_ready(i, t));
+        if until > t {
+            self.set(i, t, WeaponState::RackSalvoReload);
+            self.queue.push(until, EventKind::ReloadDone, i, 0);
+        } else {
+            self.charge(i, t);
+        }
+    }
+
+    /// Loaded with no target: unpacked weapons wait WeaponRepackTimeout and pack up, others go idle.
+    fn stand_down(&mut self, i: usize, t: f64) {
+        self.stop_waiting(i, t);
+        if self.weapons[i].weapon_unpacks {
+            let at = t + self.timing[i].repack_timeout_sec;
+            self.queue.push(at, EventKind::RepackStart, i, 0);
+        } else {
+            self.set(i, t, WeaponState::Idle);
+        }
+    }
+
+    fn repack(&mut self, i: usize, t: f64) {
+        let unpack = self.timing[i].unpack_sec;
+        if unpack > 0.0 {
+            self.set(i, t, WeaponState::Repack);
+            self.queue.push(t + unpack, EventKind::RepackDone, i, 0);
+        } else {
+            self.set(i, t, WeaponState::Idle);
+        }
     }
 }
 
@@ -170,6 +772,7 @@ mod tests {
             muzzle_salvo_delay: None,
             turret_capable: true,
             target_categories: vec!["GROUND".to_string()],
+            ..Default::default()
         }];
         let effective = vec![WeaponEffective {
             weapon_bp_id: "W1".to_string(),
@@ -180,9 +783,260 @@ mod tests {
             salvo_duration_sec: 0.0,
             reload_sec: 0.5,
             target_class_modifiers: vec![],
+            time_of_flight_sec: Some(0.8),
+            reaches_max_range: None,
+            ..Default::default()
         }];
         let r = simulate(&weapons, &effective, 2.0, 0.05);
         assert!(!r.events.is_empty());
         assert!(r.weapon_actual_shots.get("W1").copied().unwrap_or(0) >= 2);
+        assert!((r.events[1].impact_time_sec - 1.3).abs() < 1e-9);
+    }
+
+    fn weapon(id: &str, cycle: f64, shots: u32, salvo: f64) -> (WeaponDeclared, WeaponEffective) {
+        (
+            WeaponDeclared {
+                weapon_bp_id: id.to_string(),
+                ..Default::default()
+            },
+            WeaponEffective {
+                weapon_bp_id: id.to_string(),
+                cycle_time_sec: cycle,
+                shots_per_cycle: shots,
+                salvo_duration_sec: salvo,
+                time_of_flight_sec: Some(0.2),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn trace(r: &ScheduleResult) -> Vec<(i64, String, u32)> {
+        r.events
+            .iter()
+            .map(|e| (to_tick(e.time_sec), e.weapon_bp_id.clone(), e.shot_index))
+            .collect()
+    }
+
+    #[test]
+    fn permuted_weapon_order_gives_same_timeline() {
+        let list = [
+            weapon("A", 1.0, 1, 0.0),
+            weapon("B", 0.5, 3, 0.3),
+            weapon("C", 2.0, 1, 0.0),
+        ];
+        let (w, e): (Vec<_>, Vec<_>) = list.iter().cloned().unzip();
+        let (wr, er): (Vec<_>, Vec<_>) = list.iter().rev().cloned().unzip();
+        let a = simulate(&w, &e, 6.0, 0.05);
+        let b = simulate(&wr, &er, 6.0, 0.05);
+        assert_eq!(trace(&a), trace(&b));
+        // All three fire at t = 0; ties fall back to weapon ID.
+        let first: Vec<&str> = a.events[..3]
+            .iter()
+            .map(|e| e.weapon_bp_id.as_str())
+            .collect();
+        assert_eq!(first, ["A", "B", "C"]);
+        assert!(a
+            .timeline
+            .windows(2)
+            .all(|p| to_tick(p[0].time_sec) <= to_tick(p[1].time_sec)));
+    }
+
+    #[test]
+    fn label_only_weapons_rank_by_label_and_content() {
+        let gun = |label: &str, damage: f64| {
+            let (mut w, e) = weapon("unknown", 1.0, 1, 0.0);
+            w.label = Some(label.to_string());
+            w.damage = damage;
+            (w, e)
+        };
+        let list = [gun("Secondary", 10.0), gun("Main", 10.0), gun("Main", 20.0)];
+        let (w, e): (Vec<_>, Vec<_>) = list.iter().cloned().unzip();
+        let (wr, er): (Vec<_>, Vec<_>) = list.iter().rev().cloned().unzip();
+        let order = |r: &ScheduleResult, w: &[WeaponDeclared]| -> Vec<(String, f64)> {
+            r.events[..3]
+                .iter()
+                .map(|e| {
+                    let x = &w[e.weapon_index];
+                    (x.label.clone().unwrap(), x.damage)
+                })
+                .collect()
+        };
+        let a = simulate(&w, &e, 3.0, 0.05);
+        let b = simulate(&wr, &er, 3.0, 0.05);
+        let expected = [
+            ("Main".to_string(), 10.0),
+            ("Main".to_string(), 20.0),
+            ("Secondary".to_string(), 10.0),
+        ];
+        assert_eq!(order(&a, &w), expected);
+        assert_eq!(order(&b, &wr), expected);
+
+        // A priority by label applies to that weapon only.
+        let mut opts = SimOptions::default();
+        opts.priority.insert("Secondary".to_string(), 5);
+        let r = simulate_with(&w, &e, 3.0, 0.05, &opts);
+        assert_eq!(order(&r, &w)[0], ("Secondary".to_string(), 10.0));
+        assert_eq!(order(&r, &w)[1], ("Main".to_string(), 10.0));
+    }
+
+    #[test]
+    fn stated_priority_breaks_ties_first() {
+        let list = vec![weapon("A", 1.0, 1, 0.0), weapon("C", 1.0, 1, 0.0)];
+        let (w, e): (Vec<_>, Vec<_>) = list.into_iter().unzip();
+        let mut opts = SimOptions::default();
+        opts.priority.insert("C".to_string(), 5);
+        let r = simulate_with(&w, &e, 3.0, 0.05, &opts);
+        assert_eq!(r.events[0].weapon_bp_id, "C");
+        assert_eq!(r.events[2].weapon_bp_id, "C");
+    }
+
+    #[test]
+    fn zero_cycle_weapon_is_not_scheduled() {
+        let list = vec![weapon("A", 0.0, 1, 0.0), weapon("B", 1.0, 1, 0.0)];
+        let (w, e): (Vec<_>, Vec<_>) = list.into_iter().unzip();
+        let r = simulate(&w, &e, 3.0, 0.05);
+        assert_eq!(r.weapon_actual_shots["A"], 0);
+        assert_eq!(r.weapon_actual_shots["B"], 3);
+    }
+
+    #[test]
+    fn tick_timing_snaps_cycles_to_ticks() {
+        let (w, e): (Vec<_>, Vec<_>) = [weapon("A", 1.0 / 15.0, 1, 0.0)].into_iter().unzip();
+        let opts = SimOptions {
+            timing: TimingMode::Ticks,
+            ..Default::default()
+        };
+        let cont = simulate(&w, &e, 1.0, 0.05);
+        let ticks = simulate_with(&w, &e, 1.0, 0.05, &opts);
+        assert_eq!(cont.weapon_actual_shots["A"], 15);
+        assert_eq!(ticks.weapon_actual_shots["A"], 10);
+        assert!((ticks.events[1].time_sec - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tick_timing_fires_sub_tick_salvo_in_one_tick() {
+        // 4 shots 0.04 s apart at RateOfFire 0.5: the delay rounds to zero ticks.
+        let (w, e): (Vec<_>, Vec<_>) = [weapon("A", 2.0, 4, 0.16)].into_iter().unzip();
+        let opts = SimOptions {
+            timing: TimingMode::Ticks,
+            ..Default::default()
+        };
+        let cont = simulate(&w, &e, 10.0, 0.05);
+        let ticks = simulate_with(&w, &e, 10.0, 0.05, &opts);
+        assert_eq!(cont.weapon_actual_shots["A"], 20);
+        assert_eq!(ticks.weapon_actual_shots["A"], 20);
+        assert!(ticks.events[..4].iter().all(|e| e.time_sec == 0.0));
+        assert!((ticks.events[4].time_sec - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn state_machine_logs_unpack_charge_reload_and_repack() {
+        let (mut w, e) = weapon("Arty", 2.0, 1, 0.0);
+        w.weapon_unpacks = true;
+        w.unpack_animation_rate = Some(0.5);
+        w.rack_salvo_charge_time = Some(0.5);
+        w.weapon_repack_timeout = Some(1.0);
+        let opts = SimOptions {
+            target_lost_sec: Some(5.0),
+            unpack_sec: 1.0,
+            ..Default::default()
+        };
+        let r = simulate_with(&[w], &[e], 12.0, 0.05, &opts);
+        // Unpacks for 1 / 0.5 = 2 s, charges 0.5 s, fires, reloads 1.5 s, charges again.
+        let fires: Vec<f64> = r.events.iter().map(|e| e.time_sec).collect();
+        assert_eq!(fires, [2.5, 4.5]);
+        let path: Vec<(f64, WeaponState)> = r
+            .transitions
+            .iter()
+            .map(|t| ((t.time_sec * 10.0).round() / 10.0, t.to))
+            .collect();
+        use WeaponState::*;
+        assert_eq!(
+            path,
+            [
+                (0.0, Unpacking),
+                (2.0, RackSalvoCharge),
+                (2.5, MuzzleSalvo),
+                (2.5, RackSalvoReload),
+                (4.0, RackSalvoCharge),
+                (4.5, MuzzleSalvo),
+                (4.5, RackSalvoReload),
+                // Reload ends at 6.0 with no target; repack after the 1 s timeout.
+                (7.0, Repack),
+                (9.0, Idle),
+            ]
+        );
+    }
+
+    #[test]
+    fn lost_target_abandons_rack_until_rack_reload_timeout() {
+        let (mut w, e) = weapon("Gun", 1.0, 3, 0.9);
+        w.rack_reload_timeout = Some(2.0);
+        let opts = SimOptions {
+            target_lost_sec: Some(0.5),
+            ..Default::default()
+        };
+        let r = simulate_with(&[w], &[e], 10.0, 0.05, &opts);
+        assert_eq!(r.events.len(), 2);
+        let last = r.transitions.iter().rev().take(2).collect::<Vec<_>>();
+        assert_eq!(last[1].to, WeaponState::RackSalvoReload);
+        assert!((last[1].time_sec - 2.5).abs() < 1e-9);
+        assert_eq!(last[0].to, WeaponState::Idle);
+        assert!((last[0].time_sec - 3.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn shared_turret_serializes_charge_and_salvo() {
+        let mut list = vec![weapon("A", 2.0, 1, 0.0), weapon("B", 2.0, 1, 0.0)];
+        for (w, _) in &mut list {
+            w.turret_bone_yaw = Some("Turret".to_string());
+            w.rack_salvo_charge_time = Some(1.0);
+        }
+        let (w, e): (Vec<_>, Vec<_>) = list.into_iter().unzip();
+        let r = simulate(&w, &e, 10.0, 0.05);
+        // Alone each fires at 1, 3, 5, 7, 9; together B charges only after A has fired.
+        assert_eq!(r.weapon_expected_shots["A"], 5);
+        assert_eq!(r.weapon_actual_shots["A"], 5);
+        assert!(r.weapon_actual_shots["B"] < 5);
+        let b_first = r.events.iter().find(|e| e.weapon_bp_id == "B").unwrap();
+        assert!((b_first.time_sec - 2.0).abs() < 1e-9);
+
+        // B's lost shots are charged to A, which held the turret while B waited.
+        assert_eq!(r.deficit_shots[0], 0);
+        assert_eq!(r.deficit_shots[1], 5 - r.weapon_actual_shots["B"]);
+        assert!(r.blocked_by_sec[1][0] >= 1.0);
+        assert!(r.blocked_by_sec[1][0] > r.blocked_by_sec[0][1]);
+        assert_eq!(r.blocked_by_sec[1][1], 0.0);
+
+        let mut w = w;
+        w[1].prefers_primary_target = true;
+        let r = simulate(&w, &e, 10.0, 0.05);
+        assert_eq!(r.weapon_actual_shots["B"], 5);
+        assert_eq!(r.deficit_shots, [0, 0]);
+        assert_eq!(r.blocked_by_sec, [[0.0, 0.0], [0.0, 0.0]]);
+    }
+
+    #[test]
+    fn energy_pool_shared_by_two_weapons() {
+        let mut list = vec![weapon("A", 1.0, 1, 0.0), weapon("B", 1.0, 1, 0.0)];
+        for (w, _) in &mut list {
+            w.energy_required = Some(100.0);
+        }
+        let (w, e): (Vec<_>, Vec<_>) = list.into_iter().unzip();
+        let pool = EnergyPool {
+            income_per_sec: 100.0,
+            storage: 200.0,
+        };
+        let opts = SimOptions {
+            resources: Some(shared_resources(&w, Some(pool))),
+            ..Default::default()
+        };
+        let r = simulate_with(&w, &e, 10.0, 0.05, &opts);
+        // Alone, 100 E/s covers one weapon's 100 E per second.
+        assert_eq!(r.weapon_expected_shots["A"], 10);
+        // Together they share it: one shot per second between them once storage runs out.
+        let total: u32 = r.weapon_actual_shots.values().sum();
+        assert!(total <= 13, "total {}", total);
+        assert!(r.weapon_actual_shots["A"] < 10 && r.weapon_actual_shots["B"] < 10);
     }
 }
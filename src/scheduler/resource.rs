@@ -0,0 +1,211 @@
+//! Resources that several weapons of one unit contend for.
+//!
+//! Cadence interference in the game comes from shared state: two weapons on one turret cannot
+//! aim at once, deploying weapons that lock motion unpack one at a time, and energy weapons
+//! draw from one pool. Weapons holding no shared resource never delay each other.
+
+use crate::model::{UnitStats, WeaponDeclared};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How a resource is shared.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ResourceKind {
+    /// One turret (yaw bone, or the unit body): held from rack charge through the muzzle salvo.
+    Turret,
+    /// Deploy with WeaponUnpackLocksMotion: held while unpacking.
+    UnpackLock,
+    /// Energy pool: each salvo withdraws EnergyRequired during the following reload.
+    Energy { income_per_sec: f64, capacity: f64 },
+}
+
+/// A resource and the weapons (indices into the scheduled slice) that use it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SharedResource {
+    pub name: String,
+    pub kind: ResourceKind,
+    pub users: Vec<usize>,
+}
+
+impl SharedResource {
+    pub fn is_lock(&self) -> bool {
+        !matches!(self.kind, ResourceKind::Energy { .. })
+    }
+}
+
+/// The unit's own energy economy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnergyPool {
+    pub income_per_sec: f64,
+    pub storage: f64,
+}
+
+impl EnergyPool {
+    /// Pool from Economy.ProductionPerSecondEnergy and StorageEnergy; None without own production
+    /// (the weapon then draws on the player's economy, which is not modelled).
+    pub fn from_stats(stats: &UnitStats) -> Option<Self> {
+        let income = stats.energy_production.filter(|p| *p > 0.0)?;
+        Some(Self {
+            income_per_sec: income,
+            storage: stats.energy_storage.unwrap_or(0.0).max(0.0),
+        })
+    }
+}
+
+/// Shared resources of `weapons`, derived from blueprint fields:
+/// - one turret per yaw bone (TurretBoneYaw, plus TurretBoneDualYaw for TurretDualManipulators)
+///   and one for SlavedToBody weapons, when two or more weapons use it. PrefersPrimaryWeaponTarget
+///   weapons aim where the primary aims and are left out;
+/// - one unpack lock over WeaponUnpacks weapons with WeaponUnpackLocksMotion, when two or more;
+/// - the unit's energy pool over weapons with EnergyRequired, when `energy` is given.
+pub fn shared_resources(
+    weapons: &[WeaponDeclared],
+    energy: Option<EnergyPool>,
+) -> Vec<SharedResource> {
+    let mut turrets: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (i, w) in weapons.iter().enumerate() {
+        if w.prefers_primary_target {
+            continue;
+        }
+        if w.slaved_to_body {
+            turrets.entry("body".to_string()).or_default().push(i);
+            continue;
+        }
+        for bone in [&w.turret_bone_yaw, &w.turret_bone_dual_yaw]
+            .into_iter()
+            .flatten()
+            .filter(|b| !b.is_empty())
+        {
+            turrets
+                .entry(format!("turret:{}", bone.to_lowercase()))
+                .or_default()
+                .push(i);
+        }
+    }
+    let mut out: Vec<SharedResource> = turrets
+        .into_iter()
+        .filter(|(_, users)| users.len() > 1)
+        .map(|(name, users)| SharedResource {
+            name,
+            kind: ResourceKind::Turret,
+            users,
+        })
+        .collect();
+
+    let unpackers: Vec<usize> = weapons
+        .iter()
+        .enumerate()
+        .filter(|(_, w)| w.weapon_unpacks && w.unpack_locks_motion)
+        .map(|(i, _)| i)
+        .collect();
+    if unpackers.len() > 1 {
+        out.push(SharedResource {
+            name: "unpack".to_string(),
+            kind: ResourceKind::UnpackLock,
+            users: unpackers,
+        });
+    }
+
+    if let Some(pool) = energy {
+        let users: Vec<usize> = weapons
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| w.energy_required.unwrap_or(0.0) > 0.0)
+            .map(|(i, _)| i)
+            .collect();
+        let largest = users
+            .iter()
+            .map(|&i| weapons[i].energy_required.unwrap_or(0.0))
+            .fold(0.0, f64::max);
+        if !users.is_empty() {
+            out.push(SharedResource {
+                name: "energy".to_string(),
+                kind: ResourceKind::Energy {
+                    income_per_sec: pool.income_per_sec,
+                    // Storage smaller than one charge still lets the weapon fire once it fills.
+                    capacity: pool.storage.max(largest),
+                },
+                users,
+            });
+        }
+    }
+    out
+}
+
+/// Energy level of a pool, advanced lazily. Withdrawals queue behind earlier ones.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct EnergyLevel {
+    level: f64,
+    at_sec: f64,
+}
+
+impl EnergyLevel {
+    pub(super) fn full(capacity: f64) -> Self {
+        Self {
+            level: capacity,
+            at_sec: 0.0,
+        }
+    }
+
+    /// Withdraw `amount` at `t`; returns when it is available.
+    pub(super) fn withdraw(&mut self, t: f64, amount: f64, income: f64, capacity: f64) -> f64 {
+        let base = t.max(self.at_sec);
+        let level = (self.level + income * (base - self.at_sec)).min(capacity);
+        if level >= amount {
+            self.level = level - amount;
+            self.at_sec = base;
+            return base;
+        }
+        let ready = if income > 0.0 {
+            base + (amount - level) / income
+        } else {
+            f64::INFINITY
+        };
+        self.level = 0.0;
+        self.at_sec = ready;
+        ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn on(bone: &str) -> WeaponDeclared {
+        WeaponDeclared {
+            turret_bone_yaw: Some(bone.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn derives_turrets_unpack_lock_and_energy() {
+        let mut weapons = vec![on("Turret"), on("turret"), on("Turret_Rear")];
+        weapons[2].weapon_unpacks = true;
+        weapons[2].unpack_locks_motion = true;
+        weapons[2].energy_required = Some(500.0);
+        let mut unpacker = on("Arm");
+        unpacker.weapon_unpacks = true;
+        unpacker.unpack_locks_motion = true;
+        weapons.push(unpacker);
+        let found = shared_resources(
+            &weapons,
+            Some(EnergyPool {
+                income_per_sec: 20.0,
+                storage: 100.0,
+            }),
+        );
+        let names: Vec<&str> = found.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["turret:turret", "unpack", "energy"]);
+        assert_eq!(found[0].users, [0, 1]);
+        assert_eq!(found[1].users, [2, 3]);
+        assert_eq!(
+            found[2].kind,
+            ResourceKind::Energy {
+                income_per_sec: 20.0,
+                capacity: 500.0
+            }
+        );
+        assert!(shared_resources(&weapons[..1], None).is_empty());
+    }
+}
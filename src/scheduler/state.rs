@@ -0,0 +1,110 @@
+//! Per-weapon state machine driven by the scheduler's event queue.
+//!
+//! Idle → Unpacking → Aiming → RackSalvoCharge → MuzzleSalvo → RackSalvoReload, then back to
+//! RackSalvoCharge while the target lives. Once it is lost, the weapon finishes or abandons its
+//! rack, reloads, and goes Idle (unpacking weapons first wait WeaponRepackTimeout and Repack).
+//! States that would last zero seconds are skipped and not logged.
+
+use super::SimOptions;
+use crate::config::TimingMode;
+use crate::model::{tick_delay_sec, tick_interval_sec, WeaponDeclared, WeaponEffective};
+use serde::{Deserialize, Serialize};
+
+/// What a weapon is doing between two transitions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WeaponState {
+    Idle,
+    /// Deploying (WeaponUnpacks).
+    Unpacking,
+    /// Turret swinging onto the target (only with `SimOptions::aim_before_first_shot`).
+    Aiming,
+    /// RackSalvoChargeTime wind-up.
+    RackSalvoCharge,
+    /// Firing the muzzle salvo, shots spaced by MuzzleSalvoDelay.
+    MuzzleSalvo,
+    /// Waiting out the cycle (ROF or RackSalvoReloadTime) before the next charge.
+    RackSalvoReload,
+    /// Packing up after WeaponRepackTimeout without a target.
+    Repack,
+}
+
+impl WeaponState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WeaponState::Idle => "idle",
+            WeaponState::Unpacking => "unpacking",
+            WeaponState::Aiming => "aiming",
+            WeaponState::RackSalvoCharge => "rack_salvo_charge",
+            WeaponState::MuzzleSalvo => "muzzle_salvo",
+            WeaponState::RackSalvoReload => "rack_salvo_reload",
+            WeaponState::Repack => "repack",
+        }
+    }
+}
+
+/// One logged state change, for timeline rendering.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateTransition {
+    pub time_sec: f64,
+    pub weapon_bp_id: String,
+    pub weapon_index: usize,
+    pub from: WeaponState,
+    pub to: WeaponState,
+}
+
+/// Durations of each state for one weapon, in the scheduler's timing mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StateTiming {
+    pub unpack_sec: f64,
+    pub aim_sec: f64,
+    pub charge_sec: f64,
+    /// Spacing of shots within the muzzle salvo.
+    pub shot_spacing_sec: f64,
+    /// RackSalvoReload: the cycle time less the charge that follows it.
+    pub reload_sec: f64,
+    pub repack_timeout_sec: f64,
+    pub rack_reload_timeout_sec: f64,
+}
+
+/// State durations from the blueprint fields. Unpack length is not in blueprints: it is
+/// `SimOptions::unpack_sec` divided by WeaponUnpackAnimationRate.
+pub fn state_timing(w: &WeaponDeclared, e: &WeaponEffective, opts: &SimOptions) -> StateTiming {
+    let interval = |s: f64| match opts.timing {
+        TimingMode::Continuous => s,
+        TimingMode::Ticks => tick_interval_sec(s),
+    };
+    let delay = |s: f64| match opts.timing {
+        TimingMode::Continuous => s.max(0.0),
+        TimingMode::Ticks => tick_delay_sec(s),
+    };
+    let unpack_sec = if w.weapon_unpacks {
+        let rate = w.unpack_animation_rate.filter(|r| *r > 0.0).unwrap_or(1.0);
+        delay(opts.unpack_sec / rate)
+    } else {
+        0.0
+    };
+    let aim_sec = if opts.aim_before_first_shot {
+        e.acquisition
+            .map(|a| a.worst_sec)
+            .filter(|t| t.is_finite())
+            .map(delay)
+            .unwrap_or(0.0)
+    } else {
+        0.0
+    };
+    let charge_sec = delay(w.rack_salvo_charge_time.unwrap_or(0.0));
+    let cycle = interval(e.cycle_time_sec);
+    StateTiming {
+        unpack_sec,
+        aim_sec,
+        charge_sec,
+        shot_spacing_sec: if e.shots_per_cycle > 0 {
+            delay(e.salvo_duration_sec / e.shots_per_cycle as f64)
+        } else {
+            0.0
+        },
+        reload_sec: (cycle - charge_sec).max(0.0),
+        repack_timeout_sec: delay(w.weapon_repack_timeout.unwrap_or(0.0)),
+        rack_reload_timeout_sec: delay(w.rack_reload_timeout.unwrap_or(0.0)),
+    }
+}
@@ -1,8 +1,9 @@
 //! SQLite persistence for scan history and diffs.
 
-use crate::model::UnitSummary;
+use crate::config::MonteCarloRuns;
+use crate::model::{UnitSummary, UnitTimeline};
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use std::path::Path;
 
 const SCHEMA: &str = "
@@ -10,7 +11,9 @@ CREATE TABLE IF NOT EXISTS scans (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     data_dir TEXT NOT NULL,
     created_at TEXT NOT NULL,
-    summary_json TEXT
+    summary_json TEXT,
+    mc_runs INTEGER,
+    mc_seed INTEGER
 );
 
 CREATE TABLE IF NOT EXISTS scan_units (
@@ -21,6 +24,13 @@ CREATE TABLE IF NOT EXISTS scan_units (
 );
 
 CREATE INDEX IF NOT EXISTS idx_scan_units_scan ON scan_units(scan_id);
+
+CREATE TABLE IF NOT EXISTS scan_timelines (
+    scan_id INTEGER NOT NULL REFERENCES scans(id),
+    unit_id TEXT NOT NULL,
+    timeline_json TEXT NOT NULL,
+    PRIMARY KEY (scan_id, unit_id)
+);
 ";
 
 pub struct Store {
@@ -31,16 +41,30 @@ impl Store {
     pub fn open(path: &Path) -> Result<Self, String> {
         let conn = Connection::open(path).map_err(|e| e.to_string())?;
         conn.execute_batch(SCHEMA).map_err(|e| e.to_string())?;
+        migrate(&conn)?;
         Ok(Store { conn })
     }
 
-    pub fn insert_scan(&self, data_dir: &str, units: &[UnitSummary]) -> Result<i64, String> {
+    /// Store a scan and its units. `monte_carlo` records the runs and seed so the Monte Carlo
+    /// figures can be reproduced.
+    pub fn insert_scan(
+        &self,
+        data_dir: &str,
+        units: &[UnitSummary],
+        monte_carlo: Option<MonteCarloRuns>,
+    ) -> Result<i64, String> {
         let now: DateTime<Utc> = Utc::now();
         let created = now.to_rfc3339();
+        // SQLite integers are signed: the seed is stored bit for bit as i64.
         self.conn
             .execute(
-                "INSERT INTO scans (data_dir, created_at) VALUES (?1, ?2)",
-                params![data_dir, created],
+                "INSERT INTO scans (data_dir, created_at, mc_runs, mc_seed) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    data_dir,
+                    created,
+                    monte_carlo.map(|m| m.runs),
+                    monte_carlo.map(|m| m.seed as i64)
+                ],
             )
             .map_err(|e| e.to_string())?;
         let id = self.conn.last_insert_rowid();
@@ -72,6 +96,57 @@ impl Store {
         Ok(out)
     }
 
+    /// Store the scheduler timelines of a scan's units.
+    pub fn insert_timelines(&self, scan_id: i64, timelines: &[UnitTimeline]) -> Result<(), String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "INSERT INTO scan_timelines (scan_id, unit_id, timeline_json) VALUES (?1, ?2, ?3)",
+            )
+            .map_err(|e| e.to_string())?;
+        for t in timelines {
+            let json = serde_json::to_string(t).map_err(|e| e.to_string())?;
+            stmt.execute(params![scan_id, t.unit_id, json])
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Scheduler timeline of one unit in a scan; None when the scan stored none for it.
+    pub fn get_timeline(
+        &self,
+        scan_id: i64,
+        unit_id: &str,
+    ) -> Result<Option<UnitTimeline>, String> {
+        let json: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT timeline_json FROM scan_timelines WHERE scan_id = ?1 AND unit_id = ?2",
+                params![scan_id, unit_id],
+                |r| r.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        json.map(|j| serde_json::from_str(&j).map_err(|e| e.to_string()))
+            .transpose()
+    }
+
+    /// Monte Carlo runs and seed of a scan; None when it ran without --runs.
+    pub fn scan_monte_carlo(&self, scan_id: i64) -> Result<Option<MonteCarloRuns>, String> {
+        let (runs, seed): (Option<u32>, Option<i64>) = self
+            .conn
+            .query_row(
+                "SELECT mc_runs, mc_seed FROM scans WHERE id = ?1",
+                params![scan_id],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(runs.map(|runs| MonteCarloRuns {
+            runs,
+            seed: seed.unwrap_or(0) as u64,
+        }))
+    }
+
     pub fn get_scan_units(&self, scan_id: i64) -> Result<Vec<UnitSummary>, String> {
         let mut stmt = self
             .conn
@@ -89,3 +164,22 @@ impl Store {
         Ok(out)
     }
 }
+
+/// Add columns introduced after the first schema to databases created before them.
+fn migrate(conn: &Connection) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare("SELECT name FROM pragma_table_info('scans')")
+        .map_err(|e| e.to_string())?;
+    let columns = stmt
+        .query_map([], |r| r.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    for column in ["mc_runs", "mc_seed"] {
+        if !columns.iter().any(|c| c == column) {
+            conn.execute_batch(&format!("ALTER TABLE scans ADD COLUMN {} INTEGER", column))
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
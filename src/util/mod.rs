@@ -36,6 +36,39 @@ pub fn check_file_bounds(path: &Path, root: &Path, max_bytes: usize) -> Result<u
     Ok(size)
 }
 
+/// Small deterministic PRNG (SplitMix64). Same seed gives the same stream on every platform,
+/// so seeded Monte Carlo results are reproducible.
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform sample in [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform point inside a disc of the given radius centred on the origin.
+    pub fn point_in_disc(&mut self, radius: f64) -> (f64, f64) {
+        let r = radius * self.next_f64().sqrt();
+        let a = std::f64::consts::TAU * self.next_f64();
+        (r * a.cos(), r * a.sin())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -45,4 +78,15 @@ mod tests {
         assert_eq!(normalize_id("  UEL0101  "), "uel0101");
         assert_eq!(normalize_id("Aeon T1 Tank"), "aeon t1 tank");
     }
+
+    #[test]
+    fn seeded_rng_is_reproducible() {
+        let mut a = SeededRng::new(42);
+        let mut b = SeededRng::new(42);
+        for _ in 0..16 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        let x = SeededRng::new(7).next_f64();
+        assert!((0.0..1.0).contains(&x));
+    }
 }